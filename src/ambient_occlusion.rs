@@ -0,0 +1,168 @@
+use three_d::*;
+
+use crate::effect;
+
+/// Settings of the screen space ambient occlusion, which darkens creases and contact areas.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AmbientOcclusion {
+    pub enabled: bool,
+    /// Distance around each point searched for occluders, in world units.
+    pub radius: f32,
+    /// How dark a fully occluded point gets, from 0 for not at all to 1 for black.
+    pub intensity: f32,
+    /// Shows the occlusion alone instead of darkening the scene with it, for judging the settings.
+    pub show_only: bool,
+}
+
+impl Default for AmbientOcclusion {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 0.2,
+            intensity: 1.0,
+            show_only: false,
+        }
+    }
+}
+
+/// Computes the occlusion of each pixel from a depth texture, from the surfaces seen at points in the hemisphere
+/// around its normal, like the scalable ambient obscurance of McGuire et al. The normals are reconstructed from
+/// the depth, so no normal target is needed, and the result is multiplied into the target, or replaces it with
+/// `show_only`.
+pub struct AmbientOcclusionEffect {
+    pub radius: f32,
+    pub intensity: f32,
+    pub show_only: bool,
+}
+
+impl AmbientOcclusionEffect {
+    const SAMPLES: u32 = 16;
+}
+
+impl Effect for AmbientOcclusionEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        let depth_texture = depth_texture.expect("the ambient occlusion needs a depth texture");
+        format!(
+            "{}{}
+            uniform mat4 projection;
+            uniform mat4 projectionInverse;
+            uniform float radius;
+            uniform float intensity;
+            uniform vec2 texelSize;
+
+            layout (location = 0) out vec4 outColor;
+
+            vec3 viewPosition(vec2 uv)
+            {{
+                vec4 position = projectionInverse * vec4(uv * 2.0 - 1.0, sample_depth(uv) * 2.0 - 1.0, 1.0);
+                return position.xyz / position.w;
+            }}
+
+            void main()
+            {{
+                // The pixel center exactly, as the interpolated uv coordinates can round to the same texel in
+                // neighbouring rows, which breaks the normals. The target is at the origin of the depth texture.
+                vec2 uv = gl_FragCoord.xy * texelSize;
+                // Nothing was drawn there, or only the sky at the far plane.
+                if (sample_depth(uv) >= 1.0) {{
+                    outColor = vec4(1.0);
+                    return;
+                }}
+                vec3 position = viewPosition(uv);
+                vec3 normal = normalize(cross(dFdx(position), dFdy(position)));
+                if (dot(normal, position) > 0.0) {{
+                    normal = -normal;
+                }}
+                // The hemisphere is turned by a different angle in neighbouring pixels, trading banding for grain.
+                float noise = fract(52.9829189 * fract(dot(gl_FragCoord.xy, vec2(0.06711056, 0.00583715))));
+                vec3 random = vec3(cos(noise * 6.2831853), sin(noise * 6.2831853), 0.0);
+                vec3 tangent = normalize(random - normal * dot(random, normal));
+                vec3 bitangent = cross(normal, tangent);
+
+                float occlusion = 0.0;
+                for (int i = 0; i < {samples}; i++) {{
+                    // A spiral over the hemisphere, with the points at varying distances, more of them close by.
+                    float t = (float(i) + 0.5) / {samples}.0;
+                    float angle = float(i) * 2.39996323;
+                    float scale = mix(0.1, 1.0, t * t);
+                    vec3 direction = vec3(sqrt(t) * cos(angle), sqrt(t) * sin(angle), sqrt(1.0 - t));
+                    vec3 offset = tangent * direction.x + bitangent * direction.y + normal * direction.z;
+                    vec4 clip = projection * vec4(position + offset * radius * scale, 1.0);
+
+                    // The surface seen where the point ends up occludes by how far it rises above the tangent plane,
+                    // so a flat surface doesn't occlude itself however coarse its depth is at grazing angles.
+                    // Surfaces out of reach don't count, so the background doesn't get dark around silhouettes.
+                    vec3 occluder = viewPosition(clip.xy / clip.w * 0.5 + 0.5) - position;
+                    float distanceSquared = dot(occluder, occluder);
+                    float falloff = max(0.0, 1.0 - distanceSquared / (radius * radius));
+                    float cosine = dot(occluder, normal) / sqrt(distanceSquared + 1e-8);
+                    occlusion += max(0.0, cosine - 0.1) * falloff;
+                }}
+                float ambient = clamp(1.0 - 2.0 * intensity * occlusion / {samples}.0, 0.0, 1.0);
+                outColor = vec4(vec3(ambient), 1.0);
+            }}
+            ",
+            effect::HIGHP_SAMPLERS,
+            depth_texture.fragment_shader_source(),
+            samples = Self::SAMPLES,
+        )
+    }
+
+    fn id(&self, _color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        // Below the depth of field, clear of the bits of the textures.
+        0b1u16 << 15 | 0b1u16 << 7 | depth_texture.map_or(0, |t| t.id())
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes::NONE
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        program.use_uniform("projection", camera.projection());
+        // Always succeeds for a valid camera, its projection is invertible.
+        if let Some(inverse) = camera.projection().invert() {
+            program.use_uniform("projectionInverse", inverse);
+        }
+        program.use_uniform("radius", self.radius);
+        program.use_uniform("intensity", self.intensity);
+        if let Some(depth_texture) = depth_texture {
+            let (width, height) = (depth_texture.width(), depth_texture.height());
+            program.use_uniform("texelSize", vec2(1.0 / width as f32, 1.0 / height as f32));
+            depth_texture.use_uniforms(program);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        // Multiplies the colors of the target by the occlusion, keeping its alpha.
+        let blend = if self.show_only {
+            Blend::Disabled
+        } else {
+            Blend::Enabled {
+                source_rgb_multiplier: BlendMultiplierType::Zero,
+                source_alpha_multiplier: BlendMultiplierType::Zero,
+                destination_rgb_multiplier: BlendMultiplierType::SrcColor,
+                destination_alpha_multiplier: BlendMultiplierType::One,
+                rgb_equation: BlendEquationType::Add,
+                alpha_equation: BlendEquationType::Add,
+            }
+        };
+        RenderStates {
+            depth_test: DepthTest::Always,
+            write_mask: WriteMask::COLOR,
+            cull: Cull::Back,
+            blend,
+        }
+    }
+}
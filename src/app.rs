@@ -0,0 +1,1901 @@
+use eframe::egui;
+
+use crate::custom3d::{Custom3d, Navigation, ProjectionMode, Shading, ViewPreset};
+use crate::debug_view::DebugView;
+use crate::depth_of_field::DepthOfFieldEffect;
+use crate::environment::BuiltinSky;
+use crate::framebuffer_srgb::FramebufferSrgb;
+use crate::key_bindings::{Action, KeyBindings};
+use crate::lights::SceneLight;
+use crate::primitives::Primitive;
+use crate::rotation;
+use crate::scene_object::SceneObject;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::screenshot::{ScreenshotFormat, ScreenshotRequest, TurntableRequest};
+use crate::stats::FrameStats;
+use crate::view::ThreeDView;
+
+/// The demo, or the reason it couldn't start, shown in the window instead of crashing.
+pub struct MyApp {
+    demo: Result<Demo, String>,
+}
+
+impl MyApp {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        Self {
+            demo: Demo::new(cc),
+        }
+    }
+}
+
+impl eframe::App for MyApp {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        match &mut self.demo {
+            Ok(demo) => demo.update(ctx, frame),
+            Err(err) => {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    ui.heading("The 3D view couldn't be started");
+                    ui.colored_label(ui.visuals().error_fg_color, err.as_str());
+                });
+            }
+        }
+    }
+}
+
+/// When the demo repaints.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum RepaintMode {
+    /// Only after input, like moving the cursor over the window, or while something is animated.
+    OnDemand,
+    /// Every frame, as fast as the frame rate cap allows.
+    Continuous,
+}
+
+impl RepaintMode {
+    const ALL: [Self; 2] = [Self::OnDemand, Self::Continuous];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::OnDemand => "On demand",
+            Self::Continuous => "Continuous",
+        }
+    }
+}
+
+struct Demo {
+    view: ThreeDView,
+    angle: f32,
+    load_error: Option<String>,
+    frame_stats: FrameStats,
+    /// Which of the X, Y and Z axes the model rotates around, combined when more than one is selected.
+    rotation_axes: [bool; 3],
+    /// Uniform scale of the model.
+    scale: f32,
+    /// Spins the model around by itself, paused while the canvas is dragged.
+    auto_rotate: bool,
+    /// Auto-rotation speed in degrees per second.
+    rotation_speed: f32,
+    /// Whether the canvas was being dragged last frame.
+    dragging: bool,
+    /// Morphs the triangle back and forth by itself.
+    morphing: bool,
+    /// Angle in radians of the cosine easing the morph in and out, a full turn being one morph there and back.
+    morph_phase: f32,
+    /// Point entered to turn the camera towards.
+    look_at: [f32; 3],
+    /// Keys of the 3D views, shared by all of them.
+    key_bindings: KeyBindings,
+    /// Action waiting for the next key press to be bound to it.
+    rebinding: Option<Action>,
+    /// Repaints continuously while anything is animated, whatever the mode.
+    repaint_mode: RepaintMode,
+    /// Highest frame rate of the continuous repaint and the animations, `None` to repaint as fast as possible.
+    max_fps: Option<u32>,
+    /// Views in windows of their own, each with its own scene but all sharing the glow context of the app.
+    windows: Vec<ViewWindow>,
+    /// Number of windows opened so far, for giving each a unique id.
+    windows_opened: usize,
+    /// Draws the bounds and center of each visible model with [`crate::DebugDraw`] every frame, as an example of it.
+    debug_draw_bounds: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    screenshot_size: [u32; 2],
+    #[cfg(not(target_arch = "wasm32"))]
+    screenshot_format: ScreenshotFormat,
+    /// Frames of a turntable export, rendered at the screenshot size.
+    #[cfg(not(target_arch = "wasm32"))]
+    turntable_frames: u32,
+    /// A model file being read on a background thread.
+    #[cfg(not(target_arch = "wasm32"))]
+    loading: Option<ModelLoad>,
+}
+
+struct ViewWindow {
+    id: usize,
+    view: ThreeDView,
+    open: bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct ModelLoad {
+    path: std::path::PathBuf,
+    /// Adds the model to the scene instead of replacing it.
+    add: bool,
+    receiver: std::sync::mpsc::Receiver<Result<three_d::CpuModel, String>>,
+}
+
+impl Demo {
+    /// Seconds for morphing the triangle there and back again.
+    const MORPH_PERIOD: f32 = 4.0;
+
+    /// Whether anything moves by itself, which needs a repaint every frame.
+    fn is_animating(&self) -> bool {
+        self.auto_rotate
+            || self.morphing
+            || std::iter::once(&self.view)
+                .chain(self.windows.iter().map(|window| &window.view))
+                .any(|view| {
+                    let scene = view.scene();
+                    scene.is_animating() || scene.show_particles || scene.pulse
+                })
+    }
+
+    /// Asks for another frame, as soon as the frame rate cap allows.
+    fn request_animation_frame(&self, ctx: &egui::Context) {
+        match self.max_fps {
+            Some(fps) => {
+                // The frame started a while ago, so only wait for what's left of the frame period.
+                let period = std::time::Duration::from_secs_f32(1.0 / fps as f32);
+                ctx.request_repaint_after(period.saturating_sub(self.frame_stats.time_in_frame()));
+            }
+            None => ctx.request_repaint(),
+        }
+    }
+
+    fn new(cc: &eframe::CreationContext<'_>) -> Result<Self, String> {
+        let gl = cc
+            .gl
+            .as_ref()
+            .ok_or("The demo needs eframe to run with the glow backend, which isn't available.")?;
+
+        // An optional .obj, .gltf or .glb path can be passed as the first command line argument.
+        #[cfg(not(target_arch = "wasm32"))]
+        let (custom_3d, load_error) = match std::env::args().nth(1) {
+            Some(path) => match Self::load_model(gl, std::path::Path::new(&path)) {
+                Ok(custom_3d) => (custom_3d, None),
+                Err(err) => (
+                    Custom3d::new(gl)?,
+                    Some(format!("Failed to load {path}: {err}")),
+                ),
+            },
+            None => (Custom3d::new(gl)?, None),
+        };
+        #[cfg(target_arch = "wasm32")]
+        let (custom_3d, load_error) = (Custom3d::new(gl)?, None);
+
+        Ok(Self {
+            view: ThreeDView::from_scene(custom_3d),
+            angle: 0.0,
+            rotation_axes: [false, true, false],
+            scale: 1.0,
+            load_error,
+            frame_stats: FrameStats::default(),
+            auto_rotate: false,
+            rotation_speed: 45.0,
+            dragging: false,
+            morphing: false,
+            morph_phase: 0.0,
+            look_at: [0.0; 3],
+            key_bindings: KeyBindings::default(),
+            rebinding: None,
+            repaint_mode: RepaintMode::OnDemand,
+            max_fps: Some(60),
+            windows: Vec::new(),
+            windows_opened: 0,
+            debug_draw_bounds: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            screenshot_size: [1920, 1080],
+            #[cfg(not(target_arch = "wasm32"))]
+            screenshot_format: ScreenshotFormat::Png,
+            #[cfg(not(target_arch = "wasm32"))]
+            turntable_frames: 36,
+            #[cfg(not(target_arch = "wasm32"))]
+            loading: None,
+        })
+    }
+}
+
+impl eframe::App for Demo {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        self.frame_stats.record_frame();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.handle_dropped_files(ctx);
+
+        // Everything moving by itself advances by the time egui expects the frame to take, which unlike the
+        // wall-clock time between frames doesn't jump ahead on the first frame after the app was idle.
+        // Clamped as well, so a stalled frame doesn't skip most of an animation.
+        let dt = ctx.input(|i| i.stable_dt).min(0.1);
+        if self.auto_rotate && !self.dragging {
+            self.angle = rotation::advance_angle(self.angle, self.rotation_speed, dt);
+        }
+        if self.morphing {
+            let tau = std::f32::consts::TAU;
+            self.morph_phase = (self.morph_phase + dt / Self::MORPH_PERIOD * tau) % tau;
+            self.view
+                .scene()
+                .set_morph(0.5 - 0.5 * self.morph_phase.cos());
+        }
+
+        self.view.scene().animate(dt);
+        for window in &self.windows {
+            window.view.scene().animate(dt);
+        }
+
+        egui::SidePanel::left("scene_panel").show(ctx, |ui| self.scene_panel_ui(ui));
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 0.0;
+                ui.label("The triangle is being painted using ");
+                ui.hyperlink_to("three-d", "https://github.com/asny/three-d");
+                ui.label(", a 3D rendering library for Rust.")
+            });
+
+            if let Some(err) = &self.load_error {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "{:.0} FPS ({:.2} ms)",
+                    self.frame_stats.fps(),
+                    self.frame_stats.frame_time_ms()
+                ));
+                // Checked before locking the scene, which it locks too.
+                let animating = self.is_animating();
+                let custom_3d = self.view.scene();
+                if custom_3d.instancing {
+                    ui.label(format!("{} instances", custom_3d.instance_count));
+                }
+                ui.label("Repaint:");
+                let repaint_mode = &mut self.repaint_mode;
+                egui::ComboBox::from_id_source("repaint_mode")
+                    .selected_text(if animating {
+                        "Continuous (animating)"
+                    } else {
+                        repaint_mode.name()
+                    })
+                    .show_ui(ui, |ui| {
+                        for option in RepaintMode::ALL {
+                            ui.selectable_value(repaint_mode, option, option.name());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Repaint only after input, or every frame. Anything animated repaints every frame while it's on");
+                let max_fps = &mut self.max_fps;
+                let name = |fps: Option<u32>| {
+                    fps.map_or("Uncapped".to_owned(), |fps| format!("{fps} FPS"))
+                };
+                egui::ComboBox::from_id_source("max_fps")
+                    .selected_text(name(*max_fps))
+                    .show_ui(ui, |ui| {
+                        for option in [Some(30), Some(60), Some(120), None] {
+                            ui.selectable_value(max_fps, option, name(option));
+                        }
+                    })
+                    .response
+                    .on_hover_text("Highest frame rate of the continuous repaint and the animations");
+            });
+
+            ui.collapsing("Frame times", |ui| self.frame_graph_ui(ui));
+            ui.collapsing("Render statistics", |ui| {
+                let stats = self.view.scene().render_stats();
+                egui::Grid::new("render_stats").show(ui, |ui| {
+                    ui.label("Draw calls:");
+                    ui.label(stats.draw_calls.to_string());
+                    ui.end_row();
+                    ui.label("Triangles:");
+                    ui.label(stats.triangles.to_string());
+                    ui.end_row();
+                    ui.label("Model buffers:")
+                        .on_hover_text("Estimated from the vertex and index counts of the models");
+                    ui.label(format!("{:.2} MiB", stats.buffer_bytes as f64 / (1024.0 * 1024.0)));
+                    ui.end_row();
+                    ui.label("Models:");
+                    ui.label(format!(
+                        "{} rendered, {} culled",
+                        stats.rendered_objects, stats.culled_objects
+                    ));
+                    ui.end_row();
+                });
+                let mut custom_3d = self.view.scene();
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut custom_3d.frustum_culling, "Frustum culling")
+                        .on_hover_text("Skip the models outside the view");
+                    ui.add_enabled(
+                        custom_3d.frustum_culling,
+                        egui::Checkbox::new(&mut custom_3d.show_culled, "Show culled"),
+                    )
+                    .on_hover_text("Render the culled models in magenta, which the top-down view of the split view shows");
+                });
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Background:");
+                let mut custom_3d = self.view.scene();
+                let c = custom_3d.clear_color;
+                let mut color = egui::Color32::from_rgba_unmultiplied(c.r, c.g, c.b, c.a);
+                if egui::color_picker::color_edit_button_srgba(
+                    ui,
+                    &mut color,
+                    egui::color_picker::Alpha::Opaque,
+                )
+                .changed()
+                {
+                    let [r, g, b, a] = color.to_array();
+                    custom_3d.clear_color = three_d::Srgba::new(r, g, b, a);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                ui.checkbox(&mut custom_3d.show_skybox, "Skybox");
+                let current = custom_3d.builtin_sky();
+                let mut selected = current;
+                #[cfg(not(target_arch = "wasm32"))]
+                let mut load = false;
+                egui::ComboBox::from_id_source("sky")
+                    .selected_text(current.map_or("Loaded", BuiltinSky::name))
+                    .show_ui(ui, |ui| {
+                        for builtin in BuiltinSky::ALL {
+                            ui.selectable_value(&mut selected, Some(builtin), builtin.name());
+                        }
+                        #[cfg(not(target_arch = "wasm32"))]
+                        {
+                            load = ui.selectable_label(false, "Load custom…").clicked();
+                        }
+                    });
+                if let Some(builtin) = selected.filter(|_| selected != current) {
+                    custom_3d.set_builtin_sky(builtin);
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                if load {
+                    let file = rfd::FileDialog::new()
+                        .add_filter("Panoramas", &["hdr", "png", "jpg", "jpeg"])
+                        .pick_file();
+                    if let Some(path) = file {
+                        self.load_error = custom_3d
+                            .load_skybox(&path)
+                            .err()
+                            .map(|err| format!("Failed to load {}: {err}", path.display()));
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Antialiasing:");
+                let mut custom_3d = self.view.scene();
+                let max_samples = custom_3d.max_msaa_samples();
+                let name = |samples| match samples {
+                    1 => "Off".to_owned(),
+                    samples => format!("{samples}x MSAA"),
+                };
+                egui::ComboBox::from_id_source("msaa_samples")
+                    .selected_text(name(custom_3d.msaa_samples))
+                    .show_ui(ui, |ui| {
+                        for samples in [1, 2, 4, 8].into_iter().filter(|s| *s <= max_samples) {
+                            ui.selectable_value(
+                                &mut custom_3d.msaa_samples,
+                                samples,
+                                name(samples),
+                            );
+                        }
+                    });
+            });
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                ui.checkbox(&mut custom_3d.post_processing, "Tone mapping:")
+                    .on_hover_text("Apply the exposure and tone mapping in a pass after rendering");
+                ui.add_enabled_ui(custom_3d.post_processing, |ui| {
+                    let name = |tone_mapping| match tone_mapping {
+                        three_d::ToneMapping::None => "None",
+                        three_d::ToneMapping::Reinhard => "Reinhard",
+                        three_d::ToneMapping::Aces => "ACES",
+                        three_d::ToneMapping::Filmic => "Filmic",
+                    };
+                    egui::ComboBox::from_id_source("tone_mapping")
+                        .selected_text(name(custom_3d.tone_mapping))
+                        .show_ui(ui, |ui| {
+                            for tone_mapping in [
+                                three_d::ToneMapping::None,
+                                three_d::ToneMapping::Reinhard,
+                                three_d::ToneMapping::Aces,
+                                three_d::ToneMapping::Filmic,
+                            ] {
+                                ui.selectable_value(
+                                    &mut custom_3d.tone_mapping,
+                                    tone_mapping,
+                                    name(tone_mapping),
+                                );
+                            }
+                        });
+                    ui.add(
+                        egui::Slider::new(&mut custom_3d.exposure, 0.1..=10.0)
+                            .logarithmic(true)
+                            .text("exposure"),
+                    );
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                let dof = &mut custom_3d.depth_of_field;
+                ui.checkbox(&mut dof.enabled, "Depth of field:")
+                    .on_hover_text("Blur what's in front of or behind the focus distance in a pass after rendering");
+                ui.add_enabled_ui(dof.enabled, |ui| {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    ui.checkbox(&mut dof.auto_focus, "Auto focus")
+                        .on_hover_text("Focus on what's under the cursor, or in the center of the view");
+                    ui.add(
+                        egui::DragValue::new(&mut dof.focus_distance)
+                            .speed(0.01)
+                            .clamp_range(0.01..=1000.0)
+                            .prefix("focus: "),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut dof.aperture, 0.0..=DepthOfFieldEffect::MAX_RADIUS)
+                            .text("aperture"),
+                    )
+                    .on_hover_text("Blur radius in pixels of the far background");
+                });
+            });
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                let ao = &mut custom_3d.ambient_occlusion;
+                ui.checkbox(&mut ao.enabled, "Ambient occlusion:")
+                    .on_hover_text("Darken creases and contact areas in a pass after rendering");
+                ui.add_enabled_ui(ao.enabled, |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut ao.radius, 0.01..=2.0)
+                            .logarithmic(true)
+                            .text("radius"),
+                    );
+                    ui.add(egui::Slider::new(&mut ao.intensity, 0.0..=2.0).text("intensity"));
+                    ui.checkbox(&mut ao.show_only, "Show AO only");
+                });
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Projection:");
+                let mut custom_3d = self.view.scene();
+                let mut mode = custom_3d.projection_mode;
+                ui.radio_value(&mut mode, ProjectionMode::Perspective, "Perspective");
+                ui.radio_value(&mut mode, ProjectionMode::Orthographic, "Orthographic");
+                if mode != custom_3d.projection_mode {
+                    custom_3d.set_projection_mode(mode);
+                }
+                ui.checkbox(&mut custom_3d.split_view, "Split view")
+                    .on_hover_text("Show a top-down orthographic view next to the main view");
+                ui.add_enabled_ui(custom_3d.split_view, |ui| {
+                    ui.checkbox(&mut custom_3d.show_frustums, "Frustums")
+                        .on_hover_text("Draw what each camera of the split view sees in the other view");
+                });
+            });
+
+            ui.checkbox(&mut self.view.render_to_texture, "Render to texture")
+                .on_hover_text(
+                    "Show the scene as an egui image rendered offscreen, reading the pixels back every frame",
+                );
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                let mut field_of_view = custom_3d.field_of_view();
+                let (mut z_near, mut z_far) = custom_3d.clip_planes();
+                let perspective = custom_3d.projection_mode == ProjectionMode::Perspective;
+                let fov = ui.add_enabled(
+                    perspective,
+                    egui::Slider::new(&mut field_of_view, 10.0..=120.0)
+                        .suffix("°")
+                        .text("FOV"),
+                );
+                let manual = !custom_3d.auto_clip_planes;
+                ui.label("Near:");
+                let near = ui.add_enabled(
+                    manual,
+                    egui::DragValue::new(&mut z_near)
+                        .speed(0.01)
+                        .clamp_range(0.001..=z_far),
+                );
+                ui.label("Far:");
+                let far = ui.add_enabled(
+                    manual,
+                    egui::DragValue::new(&mut z_far)
+                        .speed(0.1)
+                        .clamp_range(z_near..=10_000.0),
+                );
+                ui.checkbox(&mut custom_3d.auto_clip_planes, "Auto")
+                    .on_hover_text("Fit the clipping planes around the scene on every frame, for good depth precision at any scale");
+                if custom_3d.auto_clip_planes {
+                    ui.add(
+                        egui::DragValue::new(&mut custom_3d.auto_near_factor)
+                            .speed(0.01)
+                            .clamp_range(0.01..=1.0),
+                    )
+                    .on_hover_text("Near plane distance as a fraction of the distance to the scene");
+                }
+                if fov.changed() || near.changed() || far.changed() {
+                    custom_3d.set_lens(field_of_view, z_near, z_far);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                let mut roll = custom_3d.roll();
+                if ui
+                    .add(egui::Slider::new(&mut roll, -180.0..=180.0).suffix("°").text("Roll"))
+                    .on_hover_text("Roll the camera around its view direction, also with the roll keys")
+                    .changed()
+                {
+                    custom_3d.set_roll(roll);
+                }
+                if ui.button("Level horizon").clicked() {
+                    custom_3d.level_horizon();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                ui.checkbox(&mut custom_3d.show_grid, "Grid");
+                ui.add_enabled_ui(custom_3d.show_grid, |ui| {
+                    ui.label("Extent:");
+                    let extent = ui.add(
+                        egui::DragValue::new(&mut custom_3d.grid_extent)
+                            .speed(0.1)
+                            .clamp_range(1.0..=50.0),
+                    );
+                    ui.label("Spacing:");
+                    let spacing = ui.add(
+                        egui::DragValue::new(&mut custom_3d.grid_spacing)
+                            .speed(0.01)
+                            .clamp_range(0.1..=10.0),
+                    );
+                    if extent.changed() || spacing.changed() {
+                        custom_3d.rebuild_grid();
+                    }
+                });
+                ui.separator();
+                ui.add(egui::Slider::new(&mut custom_3d.line_width, 0.5..=8.0).text("Line width"))
+                    .on_hover_text("Width of the grid, wireframe, normal, bounding box and measurement lines");
+            });
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                ui.label("Cull:");
+                ui.radio_value(&mut custom_3d.cull, three_d::Cull::None, "None");
+                ui.radio_value(&mut custom_3d.cull, three_d::Cull::Back, "Back faces");
+                ui.radio_value(&mut custom_3d.cull, three_d::Cull::Front, "Front faces");
+            });
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                ui.checkbox(&mut custom_3d.show_wireframe, "Wireframe");
+                ui.checkbox(&mut custom_3d.show_occluder, "Occluding sphere");
+                ui.checkbox(&mut custom_3d.show_bounding_box, "Bounding box");
+                if custom_3d.show_bounding_box {
+                    let size = custom_3d.bounding_box().size();
+                    ui.label(format!("{:.3} × {:.3} × {:.3}", size.x, size.y, size.z));
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                ui.checkbox(&mut custom_3d.show_points, "Points");
+                if custom_3d.show_points {
+                    let mut size = custom_3d.point_size();
+                    ui.add(egui::Slider::new(&mut size, 0.0..=0.05).text("size"))
+                        .on_hover_text("Fraction of the model size");
+                    custom_3d.set_point_size(size);
+                    let c = custom_3d.point_color;
+                    let mut color = egui::Color32::from_rgb(c.r, c.g, c.b);
+                    if ui.color_edit_button_srgba(&mut color).changed() {
+                        let [r, g, b, _] = color.to_array();
+                        custom_3d.point_color = three_d::Srgba::new(r, g, b, 255);
+                    }
+                }
+                ui.checkbox(&mut custom_3d.show_normals, "Normals");
+                if custom_3d.show_normals {
+                    let mut length = custom_3d.normal_length();
+                    ui.add(egui::Slider::new(&mut length, 0.0..=0.5).text("length"))
+                        .on_hover_text("Fraction of the model size");
+                    custom_3d.set_normal_length(length);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                ui.checkbox(&mut custom_3d.show_panes, "Transparent panes");
+                ui.add_enabled(
+                    custom_3d.show_panes,
+                    egui::Slider::new(&mut custom_3d.pane_alpha, 0.0..=1.0).text("alpha"),
+                );
+            });
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                ui.checkbox(&mut custom_3d.show_ground, "Ground plane");
+                ui.checkbox(&mut custom_3d.shadows, "Shadows");
+                // Slide over the exponent, as the shadow map size is kept to a power of two.
+                let mut exponent = custom_3d.shadow_map_size.ilog2();
+                let resolution = egui::Slider::new(&mut exponent, 8..=12)
+                    .custom_formatter(|exponent, _| format!("{}", 1u32 << exponent as u32))
+                    .text("resolution");
+                if ui.add_enabled(custom_3d.shadows, resolution).changed() {
+                    custom_3d.shadow_map_size = 1 << exponent;
+                }
+            });
+
+            ui.collapsing("Lights", |ui| self.lights_ui(ui));
+
+            ui.collapsing("Textures", |ui| self.texture_settings_ui(ui));
+
+            ui.collapsing("Particles", |ui| self.particles_ui(ui));
+
+            let vertex_colors = self.view.scene().vertex_colors();
+            if let Some(mut colors) = vertex_colors {
+                ui.horizontal(|ui| {
+                    ui.label("Vertex colors:");
+                    let mut changed = false;
+                    for c in &mut colors {
+                        let mut color = egui::Color32::from_rgba_unmultiplied(c.r, c.g, c.b, c.a);
+                        if egui::color_picker::color_edit_button_srgba(
+                            ui,
+                            &mut color,
+                            egui::color_picker::Alpha::Opaque,
+                        )
+                        .changed()
+                        {
+                            let [r, g, b, a] = color.to_array();
+                            *c = three_d::Srgba::new(r, g, b, a);
+                            changed = true;
+                        }
+                    }
+                    if changed {
+                        self.view.scene().set_vertex_colors(colors);
+                    }
+                    ui.checkbox(&mut self.view.scene().calibration, "Calibrate")
+                        .on_hover_text(
+                            "Render the triangle unlit next to egui swatches of the same colors, they should match",
+                        );
+                    ui.checkbox(&mut self.view.scene().pulse, "Pulse")
+                        .on_hover_text("Pulse the colors of the triangle with a shader animated by the time");
+                    ui.checkbox(&mut self.view.scene().compare_interpolation, "Compare interpolation")
+                        .on_hover_text(
+                            "Render the triangle unlit next to a copy blending the raw sRGB values, on the right",
+                        );
+                });
+
+                ui.horizontal(|ui| {
+                    let mut custom_3d = self.view.scene();
+                    let mut textured = custom_3d.textured();
+                    ui.label("Surface:");
+                    ui.radio_value(&mut textured, false, "Vertex colors");
+                    ui.radio_value(&mut textured, true, "Texture");
+                    custom_3d.set_textured(textured);
+
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui.button("Load texture…").clicked() {
+                        let file = rfd::FileDialog::new()
+                            .add_filter("Images", &["png", "jpg", "jpeg"])
+                            .pick_file();
+                        if let Some(path) = file {
+                            self.load_error = custom_3d
+                                .load_texture(&path)
+                                .err()
+                                .map(|err| format!("Failed to load {}: {err}", path.display()));
+                        }
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    let mut custom_3d = self.view.scene();
+                    let mut morph = custom_3d.morph();
+                    let slider = ui.add(egui::Slider::new(&mut morph, 0.0..=1.0).text("Morph"));
+                    if slider.changed() {
+                        custom_3d.set_morph(morph);
+                        self.morphing = false;
+                    }
+                    let label = if self.morphing { "Pause" } else { "Play" };
+                    if ui.button(label).clicked() {
+                        self.morphing = !self.morphing;
+                        // Carry on from the current morph, on its way towards the flattened triangle.
+                        self.morph_phase = (1.0 - 2.0 * custom_3d.morph()).acos();
+                    }
+                });
+            }
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                let support = custom_3d.srgb_support();
+                ui.label("sRGB framebuffer:");
+                let srgb = &mut custom_3d.framebuffer_srgb;
+                ui.add_enabled_ui(support.write_control, |ui| {
+                    egui::ComboBox::from_id_source("framebuffer_srgb")
+                        .selected_text(srgb.name())
+                        .show_ui(ui, |ui| {
+                            for option in FramebufferSrgb::ALL {
+                                ui.selectable_value(srgb, option, option.name());
+                            }
+                        })
+                        .response
+                        .on_hover_text("Override GL_FRAMEBUFFER_SRGB while painting the scene, for colors that look too dark or too bright");
+                });
+                ui.weak(support.to_string());
+            });
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                ui.checkbox(&mut custom_3d.instancing, "Instancing");
+                ui.add_enabled_ui(custom_3d.instancing, |ui| {
+                    let count = ui.add(
+                        egui::Slider::new(&mut custom_3d.instance_count, 1..=100_000)
+                            .logarithmic(true)
+                            .text("instances"),
+                    );
+                    if count.changed() {
+                        custom_3d.rebuild_instances();
+                    }
+                });
+            });
+
+            ui.add(
+                egui::Slider::new(&mut self.scale, 0.1..=10.0)
+                    .logarithmic(true)
+                    .text("Scale"),
+            );
+
+            ui.horizontal(|ui| {
+                ui.label("Rotate around:");
+                for (selected, name) in self.rotation_axes.iter_mut().zip(["X", "Y", "Z"]) {
+                    ui.checkbox(selected, name);
+                }
+                ui.checkbox(&mut self.auto_rotate, "Auto-rotate");
+                ui.add_enabled(
+                    self.auto_rotate,
+                    egui::DragValue::new(&mut self.rotation_speed)
+                        .clamp_range(-720.0..=720.0)
+                        .suffix("°/s"),
+                );
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Navigation:");
+                let mut custom_3d = self.view.scene();
+                ui.radio_value(&mut custom_3d.navigation, Navigation::Orbit, "Orbit");
+                ui.radio_value(&mut custom_3d.navigation, Navigation::Fly, "Fly");
+                if custom_3d.navigation == Navigation::Fly {
+                    ui.label("Speed:");
+                    ui.add(
+                        egui::DragValue::new(&mut custom_3d.fly.speed)
+                            .speed(0.01)
+                            .clamp_range(0.01..=100.0)
+                            .suffix("/s"),
+                    );
+                }
+            });
+
+            #[cfg(target_arch = "wasm32")]
+            ui.checkbox(&mut self.view.page_scroll, "Scroll the page")
+                .on_hover_text(
+                    "Leave scrolling over the view to the web page embedding it, and zoom with ctrl-scroll instead",
+                );
+
+            ui.horizontal(|ui| {
+                ui.label("Zoom range:");
+                let orbit = &mut self.view.scene().orbit;
+                let max_distance = orbit.max_distance;
+                ui.add(
+                    egui::DragValue::new(&mut orbit.min_distance)
+                        .speed(0.01)
+                        .clamp_range(0.01..=max_distance),
+                );
+                ui.label("to");
+                let min_distance = orbit.min_distance;
+                ui.add(
+                    egui::DragValue::new(&mut orbit.max_distance)
+                        .speed(0.01)
+                        .clamp_range(min_distance..=100.0),
+                );
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Shape:");
+                let mut custom_3d = self.view.scene();
+                let current = custom_3d.primitive();
+                let mut selected = current;
+                egui::ComboBox::from_id_source("primitive")
+                    .selected_text(current.map_or("Loaded model", Primitive::name))
+                    .show_ui(ui, |ui| {
+                        for primitive in Primitive::ALL {
+                            ui.selectable_value(&mut selected, Some(primitive), primitive.name());
+                        }
+                    });
+                if let Some(primitive) = selected.filter(|_| selected != current) {
+                    custom_3d.set_primitive(primitive);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Shading:");
+                let mut custom_3d = self.view.scene();
+                let mut shading = custom_3d.shading();
+                ui.radio_value(&mut shading, Shading::Smooth, "Smooth")
+                    .on_hover_text("Interpolate the vertex colors and normals across each triangle");
+                ui.radio_value(&mut shading, Shading::Flat, "Flat")
+                    .on_hover_text("Give each triangle the normal of its face and the color of its last corner");
+                custom_3d.set_shading(shading);
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Debug view:");
+                let mut custom_3d = self.view.scene();
+                egui::ComboBox::from_id_source("debug_view")
+                    .selected_text(custom_3d.debug_view.name())
+                    .show_ui(ui, |ui| {
+                        for view in DebugView::ALL {
+                            ui.selectable_value(&mut custom_3d.debug_view, view, view.name());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Color the models by their normals, texture coordinates or depth");
+            });
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                let mut lod = custom_3d.level_of_detail();
+                ui.checkbox(&mut lod.enabled, "Simplify:")
+                    .on_hover_text("Show simplified meshes of the models, merging nearby vertices");
+                ui.add_enabled_ui(lod.enabled, |ui| {
+                    ui.add(egui::Slider::new(&mut lod.quality, 0.0..=1.0).text("quality"));
+                    ui.checkbox(&mut lod.automatic, "Automatic")
+                        .on_hover_text("Only simplify the models farther away than the distance");
+                    ui.add_enabled(
+                        lod.automatic,
+                        egui::DragValue::new(&mut lod.distance)
+                            .speed(0.05)
+                            .clamp_range(0.0..=100.0)
+                            .prefix("beyond: "),
+                    )
+                    .on_hover_text("Distance in sizes of each model");
+                });
+                custom_3d.set_level_of_detail(lod);
+            });
+            if let Some((simplified, full)) = self.view.scene().simplified_triangles() {
+                let percent = 100.0 * simplified as f32 / full.max(1) as f32;
+                ui.label(format!(
+                    "Simplified to {simplified} of {full} triangles ({percent:.0}%)"
+                ));
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            self.open_model_ui(ui);
+
+            #[cfg(not(target_arch = "wasm32"))]
+            self.view_state_ui(ui);
+
+            #[cfg(not(target_arch = "wasm32"))]
+            self.project_ui(ui);
+
+            #[cfg(not(target_arch = "wasm32"))]
+            self.screenshot_ui(ui);
+
+            #[cfg(not(target_arch = "wasm32"))]
+            self.turntable_ui(ui);
+
+            let picked = {
+                let custom_3d = self.view.scene();
+                custom_3d
+                    .picked
+                    .map(|pick| (custom_3d.objects()[pick.model].name.clone(), pick.position))
+            };
+            match picked {
+                Some((name, p)) => ui.label(format!(
+                    "Picked {name} at ({:.3}, {:.3}, {:.3})",
+                    p.x, p.y, p.z
+                )),
+                None => ui.label("Click the model to pick it."),
+            };
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                ui.label("Outline:");
+                let c = custom_3d.outline_color;
+                let mut color = egui::Color32::from_rgb(c.r, c.g, c.b);
+                if ui.color_edit_button_srgba(&mut color).changed() {
+                    let [r, g, b, _] = color.to_array();
+                    custom_3d.outline_color = three_d::Srgba::new(r, g, b, 255);
+                }
+                let max_thickness = crate::outline::OutlineEffect::MAX_THICKNESS;
+                ui.add(
+                    egui::Slider::new(&mut custom_3d.outline_thickness, 1.0..=max_thickness)
+                        .text("thickness"),
+                );
+            });
+
+            ui.horizontal(|ui| {
+                let mut custom_3d = self.view.scene();
+                ui.checkbox(&mut custom_3d.measuring, "Measure");
+                if let Some(distance) = custom_3d.measured_distance() {
+                    ui.label(format!("Distance: {distance:.3}"));
+                } else if custom_3d.measuring {
+                    let remaining = 2 - custom_3d.measurement_points();
+                    ui.label(format!("Click {remaining} more point(s) on the model."));
+                }
+                if ui.button("Clear measurement").clicked() {
+                    custom_3d.clear_measurement();
+                }
+                ui.separator();
+                ui.checkbox(&mut custom_3d.highlight_hovered, "Highlight hovered")
+                    .on_hover_text("Tint the model under the cursor, which a click would select");
+                ui.checkbox(&mut custom_3d.show_cursor_ray, "Cursor ray")
+                    .on_hover_text("Show the picking ray under the cursor and where it hits the model");
+                ui.checkbox(&mut self.debug_draw_bounds, "Debug draw")
+                    .on_hover_text("Draw the bounds and center of each model with the debug draw API");
+                if self.debug_draw_bounds {
+                    let bounds: Vec<_> = custom_3d
+                        .objects()
+                        .iter()
+                        .filter(|object| object.visible)
+                        .map(|object| three_d::Geometry::aabb(&object.model))
+                        .collect();
+                    for aabb in bounds {
+                        custom_3d
+                            .debug_draw
+                            .aabb(&aabb, three_d::Srgba::new(0, 255, 255, 255));
+                        custom_3d
+                            .debug_draw
+                            .point(aabb.center(), three_d::Srgba::new(255, 0, 255, 255));
+                    }
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    ui.separator();
+                    ui.checkbox(&mut custom_3d.probe_depth, "Depth under cursor")
+                        .on_hover_text("Read the depth buffer under the cursor back every frame");
+                    if custom_3d.probe_depth {
+                        match custom_3d.cursor_depth() {
+                            Some(sample) => ui.label(format!("{:.3} away", sample.distance)),
+                            None => ui.label("Nothing under the cursor"),
+                        };
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Look at:");
+                for value in &mut self.look_at {
+                    ui.add(egui::DragValue::new(value).speed(0.01));
+                }
+                if ui.button("Look here").clicked() {
+                    self.view.scene().look_at(self.look_at.into());
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("View:");
+                for (action, preset) in [
+                    (Action::FrontView, ViewPreset::Front),
+                    (Action::SideView, ViewPreset::Right),
+                    (Action::TopView, ViewPreset::Top),
+                ] {
+                    let key = self.key_bindings.key(action).name();
+                    for (preset, shortcut) in [
+                        (preset, key.to_owned()),
+                        (preset.opposite(), format!("ctrl+{key}")),
+                    ] {
+                        if ui
+                            .button(preset.name())
+                            .on_hover_text(format!("Orthographic view of all models ({shortcut})"))
+                            .clicked()
+                        {
+                            self.view.scene().snap_to_view(preset);
+                        }
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                #[cfg(target_arch = "wasm32")]
+                let page_scroll = self.view.page_scroll;
+                #[cfg(not(target_arch = "wasm32"))]
+                let page_scroll = false;
+                let help = match self.view.scene().navigation {
+                    Navigation::Orbit if page_scroll => {
+                        "Drag to orbit, right-, middle- or shift-drag to pan, ctrl-scroll or pinch to zoom, double-click to focus!"
+                            .to_owned()
+                    }
+                    Navigation::Orbit => {
+                        "Drag to orbit, right-, middle- or shift-drag to pan, scroll or pinch to zoom, double-click to focus!"
+                            .to_owned()
+                    }
+                    Navigation::Fly => {
+                        let key = |action| self.key_bindings.key(action).name();
+                        format!(
+                            "Drag to look around, {}{}{}{} to move, {}/{} for down/up, shift to sprint!",
+                            key(Action::MoveForward),
+                            key(Action::MoveLeft),
+                            key(Action::MoveBack),
+                            key(Action::MoveRight),
+                            key(Action::MoveDown),
+                            key(Action::MoveUp),
+                        )
+                    }
+                };
+                ui.label(help);
+                if ui.button("Reset view").clicked() {
+                    self.view.scene().reset_view();
+                    self.angle = 0.0;
+                }
+                if ui
+                    .button("Frame")
+                    .on_hover_text(format!(
+                        "Fit the selected model, or all models, into the view ({})",
+                        self.key_bindings.key(Action::FrameSelection).name()
+                    ))
+                    .clicked()
+                {
+                    self.view.scene().frame_selection();
+                }
+                if let Some(gl) = frame.gl().filter(|_| ui.button("New window").clicked()) {
+                    match ThreeDView::new(gl) {
+                        Ok(view) => {
+                            self.windows_opened += 1;
+                            self.windows.push(ViewWindow {
+                                id: self.windows_opened,
+                                view,
+                                open: true,
+                            });
+                        }
+                        Err(err) => self.load_error = Some(err),
+                    }
+                }
+            });
+            ui.collapsing("Key bindings", |ui| self.key_bindings_ui(ui));
+            egui::Frame::canvas(ui.style()).show(ui, |ui| {
+                self.view.transformation = self.model_transformation();
+                self.view.key_bindings = self.key_bindings.clone();
+                self.dragging = self.view.ui(ui).dragged();
+            });
+        });
+
+        self.windows_ui(ctx);
+
+        // Checked after the buttons that start animations, so they run from the next frame on.
+        if self.repaint_mode == RepaintMode::Continuous || self.is_animating() {
+            self.request_animation_frame(ctx);
+        }
+    }
+}
+
+impl Demo {
+    /// Picks the loader by the file extension, falling back to .obj.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_model(
+        gl: &std::sync::Arc<eframe::glow::Context>,
+        path: &std::path::Path,
+    ) -> Result<Custom3d, String> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("gltf") || ext.eq_ignore_ascii_case("glb") => {
+                Custom3d::from_gltf(gl, path)
+            }
+            _ => Custom3d::from_obj(gl, path),
+        }
+    }
+
+    /// Parsing large models takes a while, so files are read on a background thread and only uploaded to the GPU here.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_model_ui(&mut self, ui: &mut egui::Ui) {
+        if let Some(loading) = &self.loading {
+            match loading.receiver.try_recv() {
+                Ok(result) => {
+                    let path = loading.path.display().to_string();
+                    let add = loading.add;
+                    self.load_error = result
+                        .and_then(|cpu_model| {
+                            let mut custom_3d = self.view.scene();
+                            if add {
+                                custom_3d.add_model(cpu_model, Some(&loading.path))
+                            } else {
+                                custom_3d.set_model(cpu_model, Some(&loading.path))
+                            }
+                            .map_err(|err| err.to_string())
+                        })
+                        .err()
+                        .map(|err| format!("Failed to load {path}: {err}"));
+                    self.loading = None;
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => ui.ctx().request_repaint(),
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    self.load_error = Some(format!(
+                        "Failed to load {}: the loader thread panicked",
+                        loading.path.display()
+                    ));
+                    self.loading = None;
+                }
+            }
+        }
+
+        ui.horizontal(|ui| {
+            let open = ui.add_enabled(self.loading.is_none(), egui::Button::new("Open model…"));
+            let add = ui.add_enabled(self.loading.is_none(), egui::Button::new("Add model…"));
+            if open.clicked() || add.clicked() {
+                let file = rfd::FileDialog::new()
+                    .add_filter("Models", Self::MODEL_EXTENSIONS)
+                    .pick_file();
+                if let Some(path) = file {
+                    self.start_loading(path, add.clicked());
+                }
+            }
+            if let Some(loading) = &self.loading {
+                ui.spinner();
+                ui.label(format!("Loading {}…", loading.path.display()));
+            }
+        });
+        self.import_stats_ui(ui);
+    }
+
+    /// Shows the counts and bounds of the model loaded last, with a row for each mesh of files with several.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn import_stats_ui(&self, ui: &mut egui::Ui) {
+        let custom_3d = self.view.scene();
+        let Some(stats) = custom_3d.import_stats() else {
+            return;
+        };
+        let vector = |v: three_d::Vec3| format!("{:.3}, {:.3}, {:.3}", v.x, v.y, v.z);
+        let name = stats
+            .path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map_or_else(
+                || "Imported model".to_owned(),
+                |name| format!("Imported {}", name.to_string_lossy()),
+            );
+        egui::CollapsingHeader::new(name)
+            .id_source("import_stats")
+            .default_open(true)
+            .show(ui, |ui| {
+                let aabb = stats.aabb();
+                egui::Grid::new("import_totals").show(ui, |ui| {
+                    ui.label("Vertices:");
+                    ui.label(stats.vertices().to_string());
+                    ui.end_row();
+                    ui.label("Triangles:");
+                    ui.label(stats.triangles().to_string());
+                    ui.end_row();
+                    if !aabb.is_empty() {
+                        ui.label("Min:");
+                        ui.label(vector(aabb.min()));
+                        ui.end_row();
+                        ui.label("Max:");
+                        ui.label(vector(aabb.max()));
+                        ui.end_row();
+                        ui.label("Size:");
+                        ui.label(vector(aabb.size()));
+                        ui.end_row();
+                    }
+                });
+                if stats.meshes.len() > 1 {
+                    ui.collapsing(format!("{} meshes", stats.meshes.len()), |ui| {
+                        egui::Grid::new("import_meshes")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for heading in ["Mesh", "Vertices", "Triangles", "Size"] {
+                                    ui.strong(heading);
+                                }
+                                ui.end_row();
+                                for mesh in &stats.meshes {
+                                    ui.label(&mesh.name);
+                                    ui.label(mesh.vertices.to_string());
+                                    ui.label(mesh.triangles.to_string());
+                                    ui.label(vector(mesh.aabb.size())).on_hover_text(format!(
+                                        "From {} to {}",
+                                        vector(mesh.aabb.min()),
+                                        vector(mesh.aabb.max())
+                                    ));
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                }
+            });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    const MODEL_EXTENSIONS: &'static [&'static str] = &["obj", "gltf", "glb"];
+
+    /// Reads the model on a background thread, `open_model_ui` uploads it once it's ready, adding it to the scene
+    /// with `add` or replacing the scene otherwise.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start_loading(&mut self, path: std::path::PathBuf, add: bool) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let thread_path = path.clone();
+        std::thread::spawn(move || {
+            let result = Custom3d::read_model(&thread_path).map_err(|err| err.to_string());
+            sender.send(result).ok();
+        });
+        self.loading = Some(ModelLoad {
+            path,
+            add,
+            receiver,
+        });
+    }
+
+    /// Loads a model file dropped onto the window, and shows where to drop while one is dragged over it.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| !i.raw.hovered_files.is_empty()) {
+            let painter = ctx.layer_painter(egui::LayerId::new(
+                egui::Order::Foreground,
+                "file_drop".into(),
+            ));
+            let screen_rect = ctx.screen_rect();
+            painter.rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(160));
+            painter.text(
+                screen_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "Drop a .obj, .gltf or .glb file to load it",
+                egui::FontId::proportional(20.0),
+                egui::Color32::WHITE,
+            );
+        }
+
+        let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
+        let Some(path) = dropped_files.into_iter().find_map(|file| file.path) else {
+            return;
+        };
+        let supported = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                Self::MODEL_EXTENSIONS
+                    .iter()
+                    .any(|supported| ext.eq_ignore_ascii_case(supported))
+            });
+        if !supported {
+            self.load_error = Some(format!(
+                "Can't load {}, only .obj, .gltf and .glb files are supported",
+                path.display()
+            ));
+        } else if self.loading.is_some() {
+            self.load_error = Some(format!(
+                "Can't load {} while another model is loading",
+                path.display()
+            ));
+        } else {
+            self.start_loading(path, false);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn view_state_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let dialog = || rfd::FileDialog::new().add_filter("View", &["json"]);
+            if ui.button("Save view…").clicked() {
+                if let Some(path) = dialog().set_file_name("view.json").save_file() {
+                    self.load_error = self
+                        .view
+                        .scene()
+                        .save_state(&path, self.angle)
+                        .err()
+                        .map(|err| format!("Failed to save {}: {err}", path.display()));
+                }
+            }
+            if ui.button("Load view…").clicked() {
+                if let Some(path) = dialog().pick_file() {
+                    match self.view.scene().load_state(&path) {
+                        Ok(angle) => {
+                            self.angle = angle;
+                            self.load_error = None;
+                        }
+                        Err(err) => {
+                            self.load_error =
+                                Some(format!("Failed to load {}: {err}", path.display()))
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn project_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let dialog = || rfd::FileDialog::new().add_filter("Project", &["json"]);
+            if ui.button("Save project…").clicked() {
+                if let Some(path) = dialog().set_file_name("project.json").save_file() {
+                    self.load_error = self
+                        .view
+                        .scene()
+                        .save_project(&path, self.angle)
+                        .err()
+                        .map(|err| format!("Failed to save {}: {err}", path.display()));
+                }
+            }
+            if ui.button("Open project…").clicked() {
+                if let Some(path) = dialog().pick_file() {
+                    match self.view.scene().open_project(&path) {
+                        Ok(angle) => {
+                            self.angle = angle;
+                            self.load_error = None;
+                        }
+                        Err(err) => {
+                            self.load_error =
+                                Some(format!("Failed to open {}: {err}", path.display()))
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Files are written to the working directory, which doesn't exist on the web.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn screenshot_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let [width, height] = &mut self.screenshot_size;
+            ui.add(egui::DragValue::new(width).clamp_range(1..=8192));
+            ui.label("x");
+            ui.add(egui::DragValue::new(height).clamp_range(1..=8192));
+            let format = &mut self.screenshot_format;
+            egui::ComboBox::from_id_source("screenshot_format")
+                .selected_text(format.name())
+                .show_ui(ui, |ui| {
+                    for option in ScreenshotFormat::ALL {
+                        ui.selectable_value(format, option, option.name());
+                    }
+                });
+            let mut custom_3d = self.view.scene();
+            if ui.button("Save screenshot").clicked() {
+                let file_name = format!(
+                    "screenshot-{}.{}",
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs(),
+                    format.extension()
+                );
+                custom_3d.screenshot_request = Some(ScreenshotRequest {
+                    width: *width,
+                    height: *height,
+                    path: std::env::current_dir().unwrap_or_default().join(file_name),
+                    format: *format,
+                });
+                // The capture happens while painting, so repaint once more to show the result.
+                ui.ctx().request_repaint();
+            }
+            match &custom_3d.screenshot_result {
+                Some(Ok(path)) => {
+                    ui.label(format!("Saved {}", path.display()));
+                }
+                Some(Err(err)) => {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+                None => {}
+            }
+        });
+    }
+
+    /// Exports numbered PNGs of the camera orbiting the scene once, at the screenshot size, for making animations.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn turntable_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let mut custom_3d = self.view.scene();
+            if let Some((saved, frames)) = custom_3d.turntable_progress() {
+                ui.add(
+                    egui::ProgressBar::new(saved as f32 / frames as f32)
+                        .desired_width(160.0)
+                        .text(format!("{saved} / {frames}")),
+                );
+                if ui.button("Cancel").clicked() {
+                    custom_3d.cancel_turntable();
+                }
+                return;
+            }
+            ui.add(
+                egui::DragValue::new(&mut self.turntable_frames)
+                    .clamp_range(1..=3600)
+                    .suffix(" frames"),
+            );
+            if ui.button("Render turntable").clicked() {
+                let [width, height] = self.screenshot_size;
+                let directory = format!(
+                    "turntable-{}",
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs()
+                );
+                let request = TurntableRequest {
+                    frames: self.turntable_frames,
+                    width,
+                    height,
+                    directory: std::env::current_dir().unwrap_or_default().join(directory),
+                };
+                if let Err(err) = custom_3d.start_turntable(request) {
+                    custom_3d.turntable_result =
+                        Some(Err(format!("Failed to start the turntable: {err}")));
+                }
+            }
+            match &custom_3d.turntable_result {
+                Some(Ok(directory)) => {
+                    ui.label(format!("Saved to {}", directory.display()));
+                }
+                Some(Err(err)) => {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+                None => {}
+            }
+        });
+    }
+
+    /// Shows each extra view in a window of its own. Each paint callback only covers the canvas rect of its
+    /// window, and three-d sets the viewport and scissor box of every draw, so the views don't affect each other.
+    fn windows_ui(&mut self, ctx: &egui::Context) {
+        for window in &mut self.windows {
+            egui::Window::new(format!("3D view {}", window.id))
+                .id(egui::Id::new(("3d_window", window.id)))
+                .open(&mut window.open)
+                .default_size(egui::vec2(320.0, 240.0))
+                .resizable(true)
+                .show(ctx, |ui| {
+                    if ui.button("Reset view").clicked() {
+                        window.view.scene().reset_view();
+                    }
+                    window.view.key_bindings = self.key_bindings.clone();
+                    window.view.ui(ui);
+                });
+        }
+        // Dropping a view frees its GPU resources once the last paint callback holding on to it is gone.
+        self.windows.retain(|window| window.open);
+    }
+
+    /// Lists the controls of the 3D views. Clicking the key of an action binds it to the next key pressed,
+    /// or keeps it on Escape.
+    fn key_bindings_ui(&mut self, ui: &mut egui::Ui) {
+        if let Some(action) = self.rebinding {
+            let pressed = ui.input(|i| {
+                i.events.iter().find_map(|event| match event {
+                    egui::Event::Key {
+                        key, pressed: true, ..
+                    } => Some(*key),
+                    _ => None,
+                })
+            });
+            if let Some(key) = pressed {
+                if KeyBindings::BINDABLE_KEYS.contains(&key) {
+                    self.key_bindings.set(action, key);
+                }
+                if key == egui::Key::Escape || KeyBindings::BINDABLE_KEYS.contains(&key) {
+                    self.rebinding = None;
+                }
+            }
+        }
+
+        egui::Grid::new("key_bindings").show(ui, |ui| {
+            for (control, input) in [
+                ("Orbit / look around", "Drag"),
+                (
+                    "Pan",
+                    "Right-, middle- or shift-drag, or drag with two fingers",
+                ),
+                ("Zoom", "Scroll or pinch"),
+                ("Orbit around the vertical axis", "Twist two fingers"),
+                ("Pick", "Click"),
+                ("Focus", "Double-click"),
+                ("Move the selected model", "Drag its arrows"),
+                ("Sprint", "Shift"),
+            ] {
+                ui.label(control);
+                ui.label(input);
+                ui.end_row();
+            }
+            for action in Action::ALL {
+                ui.label(action.name());
+                let text = if self.rebinding == Some(action) {
+                    "Press a key…"
+                } else {
+                    self.key_bindings.key(action).name()
+                };
+                if ui.button(text).clicked() {
+                    self.rebinding = Some(action);
+                }
+                ui.end_row();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("Defaults").clicked() {
+                self.key_bindings = KeyBindings::default();
+                self.rebinding = None;
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            self.save_key_bindings_ui(ui);
+        });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_key_bindings_ui(&mut self, ui: &mut egui::Ui) {
+        let dialog = || rfd::FileDialog::new().add_filter("Key bindings", &["json"]);
+        if ui.button("Save bindings…").clicked() {
+            if let Some(path) = dialog().set_file_name("key_bindings.json").save_file() {
+                self.load_error = self
+                    .key_bindings
+                    .to_json()
+                    .map_err(std::io::Error::from)
+                    .and_then(|json| std::fs::write(&path, json))
+                    .err()
+                    .map(|err| format!("Failed to save {}: {err}", path.display()));
+            }
+        }
+        if ui.button("Load bindings…").clicked() {
+            if let Some(path) = dialog().pick_file() {
+                let bindings = std::fs::read_to_string(&path)
+                    .and_then(|json| Ok(KeyBindings::from_json(&json)?));
+                match bindings {
+                    Ok(bindings) => {
+                        self.key_bindings = bindings;
+                        self.load_error = None;
+                    }
+                    Err(err) => {
+                        self.load_error = Some(format!("Failed to load {}: {err}", path.display()))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Plots the times of the last frames, marking the ones that took longer than a frame at 60 FPS.
+    fn frame_graph_ui(&self, ui: &mut egui::Ui) {
+        use egui_plot::{HLine, Line, Plot, Points};
+
+        const HITCH_MS: f64 = 1000.0 / 60.0;
+
+        let frame_times: Vec<[f64; 2]> = self
+            .frame_stats
+            .history()
+            .iter()
+            .enumerate()
+            .map(|(i, &frame_time)| [i as f64, frame_time as f64 * 1000.0])
+            .collect();
+        let hitches: Vec<[f64; 2]> = frame_times
+            .iter()
+            .copied()
+            .filter(|[_, ms]| *ms > HITCH_MS)
+            .collect();
+        ui.label(format!(
+            "{} of the last {} frames took longer than {HITCH_MS:.1} ms",
+            hitches.len(),
+            frame_times.len()
+        ));
+        Plot::new("frame_times")
+            .height(120.0)
+            .include_x(FrameStats::HISTORY_LENGTH as f64)
+            .include_y(0.0)
+            .include_y(HITCH_MS * 2.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .y_axis_label("ms")
+            .show(ui, |plot_ui| {
+                plot_ui.hline(HLine::new(HITCH_MS).color(egui::Color32::GRAY));
+                plot_ui.line(Line::new(frame_times).name("Frame time"));
+                plot_ui.points(
+                    Points::new(hitches)
+                        .radius(2.5)
+                        .color(egui::Color32::RED)
+                        .name("Hitch"),
+                );
+            });
+    }
+
+    /// Lists the models of the scene. Clicking a row selects and outlines the model, with its transform edited
+    /// below the list.
+    fn scene_panel_ui(&mut self, ui: &mut egui::Ui) {
+        let mut custom_3d = self.view.scene();
+        ui.heading("Scene");
+        let mut removed = None;
+        egui::ScrollArea::vertical()
+            .max_height(300.0)
+            .show(ui, |ui| {
+                for i in 0..custom_3d.objects().len() {
+                    ui.horizontal(|ui| {
+                        let mut visible = custom_3d.objects()[i].visible;
+                        if ui
+                            .checkbox(&mut visible, "")
+                            .on_hover_text("Visible")
+                            .changed()
+                        {
+                            custom_3d.set_object_visible(i, visible);
+                        }
+                        let selected = custom_3d.selected() == Some(i);
+                        let name = custom_3d.objects()[i].name.clone();
+                        if ui.selectable_label(selected, name).clicked() {
+                            custom_3d.select((!selected).then_some(i));
+                        }
+                        if ui.button("Remove").clicked() {
+                            removed = Some(i);
+                        }
+                    });
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                for missing in custom_3d.missing_objects() {
+                    ui.label(&missing.object.name)
+                        .on_hover_text("Missing from the opened project");
+                    ui.colored_label(egui::Color32::RED, &missing.error);
+                }
+                if custom_3d.objects().is_empty() {
+                    ui.label("Nothing loaded.");
+                }
+            });
+        if let Some(i) = removed {
+            custom_3d.remove_object(i);
+        }
+
+        let Some(index) = custom_3d.selected() else {
+            return;
+        };
+        ui.separator();
+        let mut transform = custom_3d.objects()[index].transform;
+        egui::Grid::new("object_transform")
+            .num_columns(4)
+            .show(ui, |ui| {
+                ui.label("Position");
+                let t = &mut transform.translation;
+                for value in [&mut t.x, &mut t.y, &mut t.z] {
+                    ui.add(egui::DragValue::new(value).speed(0.01));
+                }
+                ui.end_row();
+                ui.label("Rotation");
+                let r = &mut transform.rotation;
+                for value in [&mut r.x, &mut r.y, &mut r.z] {
+                    ui.add(egui::DragValue::new(value).suffix("°"));
+                }
+                ui.end_row();
+                ui.label("Scale");
+                ui.add(
+                    egui::DragValue::new(&mut transform.scale)
+                        .speed(0.01)
+                        .clamp_range(0.001..=1000.0),
+                );
+                ui.end_row();
+                ui.label("Nudge step").on_hover_text(
+                    "Distance the arrow keys move the object along the screen, and page up and down in depth. Shift moves ten steps",
+                );
+                ui.add(
+                    egui::DragValue::new(&mut custom_3d.nudge_step)
+                        .speed(0.001)
+                        .clamp_range(0.0001..=100.0),
+                );
+                ui.end_row();
+            });
+        if transform != custom_3d.objects()[index].transform {
+            custom_3d.set_object_transform(index, transform);
+        }
+
+        ui.separator();
+        Self::material_ui(ui, &mut custom_3d, index);
+    }
+
+    /// Edits the material of the selected object live. Models with several materials have an object for each of
+    /// their meshes, which the list selects between.
+    fn material_ui(ui: &mut egui::Ui, custom_3d: &mut Custom3d, index: usize) {
+        let label =
+            |object: &SceneObject| format!("{} ({})", object.model.material.name, object.name);
+        ui.horizontal(|ui| {
+            ui.label("Material:");
+            egui::ComboBox::from_id_source("material")
+                .selected_text(&custom_3d.objects()[index].model.material.name)
+                .show_ui(ui, |ui| {
+                    let mut selected = index;
+                    for (i, object) in custom_3d.objects().iter().enumerate() {
+                        ui.selectable_value(&mut selected, i, label(object));
+                    }
+                    if selected != index {
+                        custom_3d.select(Some(selected));
+                    }
+                });
+        });
+        let Some(index) = custom_3d.selected() else {
+            return;
+        };
+        let material = custom_3d.object_material_mut(index);
+        let color_edit = |ui: &mut egui::Ui, color: &mut three_d::Srgba| {
+            let mut edited = egui::Color32::from_rgb(color.r, color.g, color.b);
+            if egui::color_picker::color_edit_button_srgba(
+                ui,
+                &mut edited,
+                egui::color_picker::Alpha::Opaque,
+            )
+            .changed()
+            {
+                let [r, g, b, _] = edited.to_array();
+                *color = three_d::Srgba::new(r, g, b, color.a);
+            }
+        };
+        egui::Grid::new("material").num_columns(2).show(ui, |ui| {
+            ui.label("Albedo");
+            color_edit(ui, &mut material.albedo);
+            ui.end_row();
+            ui.label("Metallic");
+            ui.add(egui::Slider::new(&mut material.metallic, 0.0..=1.0));
+            ui.end_row();
+            ui.label("Roughness");
+            ui.add(egui::Slider::new(&mut material.roughness, 0.0..=1.0));
+            ui.end_row();
+            ui.label("Emissive");
+            color_edit(ui, &mut material.emissive);
+            ui.end_row();
+        });
+        if material.albedo_texture.is_some() || material.metallic_roughness_texture.is_some() {
+            ui.label("The textures of the material multiply the values.");
+        }
+    }
+
+    /// Edits how the textures are sampled, with a large checkerboard plane to compare the settings on.
+    fn texture_settings_ui(&mut self, ui: &mut egui::Ui) {
+        use three_d::{Interpolation, Wrapping};
+
+        let mut custom_3d = self.view.scene();
+        let mut settings = custom_3d.texture_settings();
+        ui.checkbox(&mut custom_3d.show_texture_plane, "Checkerboard plane")
+            .on_hover_text(
+                "A large plane below the triangle, seen at a grazing angle from the start",
+            );
+        ui.checkbox(&mut settings.mipmaps, "Mipmaps").on_hover_text(
+            "Without mipmaps, distant parts of the plane shimmer while the camera moves",
+        );
+        egui::Grid::new("texture_settings").show(ui, |ui| {
+            for (label, filter) in [
+                ("Minification", &mut settings.min_filter),
+                ("Magnification", &mut settings.mag_filter),
+            ] {
+                ui.label(label);
+                ui.radio_value(filter, Interpolation::Linear, "Linear");
+                ui.radio_value(filter, Interpolation::Nearest, "Nearest");
+                ui.end_row();
+            }
+            ui.label("Wrapping");
+            ui.radio_value(&mut settings.wrapping, Wrapping::Repeat, "Repeat");
+            ui.radio_value(
+                &mut settings.wrapping,
+                Wrapping::ClampToEdge,
+                "Clamp to edge",
+            );
+            ui.radio_value(
+                &mut settings.wrapping,
+                Wrapping::MirroredRepeat,
+                "Mirrored repeat",
+            );
+            ui.end_row();
+        });
+        custom_3d.set_texture_settings(settings);
+        ui.label("Applies to the triangle texture, the plane and models loaded from now on.");
+    }
+
+    /// Shows the particle fountain and edits how it's spawned.
+    fn particles_ui(&mut self, ui: &mut egui::Ui) {
+        let mut custom_3d = self.view.scene();
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut custom_3d.show_particles, "Particles");
+            if ui.button("Clear").clicked() {
+                custom_3d.particles.clear();
+            }
+            ui.label(format!("{} alive", custom_3d.particles.count()));
+        });
+        let particles = &mut custom_3d.particles;
+        ui.add(egui::Slider::new(&mut particles.max_count, 1..=20_000).text("max count"));
+        ui.add(
+            egui::Slider::new(&mut particles.spawn_rate, 0.0..=5000.0).text("spawned per second"),
+        );
+        ui.add(egui::Slider::new(&mut particles.gravity, -5.0..=10.0).text("gravity"));
+    }
+
+    /// Edits the point and spot lights, each in a row of its own.
+    fn lights_ui(&mut self, ui: &mut egui::Ui) {
+        let mut custom_3d = self.view.scene();
+        ui.horizontal(|ui| {
+            if ui.button("Add point light").clicked() {
+                custom_3d.add_point_light();
+            }
+            if ui.button("Add spot light").clicked() {
+                custom_3d.add_spot_light();
+            }
+            if ui.button("Add 50 point lights").clicked() {
+                custom_3d.add_light_ring(50);
+            }
+            if ui.button("Remove all").clicked() {
+                custom_3d.lights.clear();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Renderer:");
+            ui.radio_value(&mut custom_3d.deferred, false, "Forward");
+            ui.radio_value(&mut custom_3d.deferred, true, "Deferred");
+            ui.label(format!(
+                "{:.2} ms per frame with {} lights",
+                self.frame_stats.frame_time_ms(),
+                custom_3d.lights.len() + 2
+            ));
+        });
+
+        let mut removed = None;
+        // Scrolls once there are more lights than fit, like after adding a ring of them.
+        egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for (i, light) in custom_3d.lights.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(match light {
+                            SceneLight::Point(_) => "Point",
+                            SceneLight::Spot(_) => "Spot",
+                        });
+                        let (position, color, intensity) = light.common_mut();
+                        for value in [&mut position.x, &mut position.y, &mut position.z] {
+                            ui.add(egui::DragValue::new(value).speed(0.01));
+                        }
+                        let mut color32 = egui::Color32::from_rgb(color.r, color.g, color.b);
+                        if egui::color_picker::color_edit_button_srgba(
+                            ui,
+                            &mut color32,
+                            egui::color_picker::Alpha::Opaque,
+                        )
+                        .changed()
+                        {
+                            *color =
+                                three_d::Srgba::new(color32.r(), color32.g(), color32.b(), 255);
+                        }
+                        ui.add(
+                            egui::DragValue::new(intensity)
+                                .speed(0.05)
+                                .clamp_range(0.0..=100.0)
+                                .prefix("intensity "),
+                        );
+                        if let SceneLight::Spot(spot) = light {
+                            let mut cutoff = three_d::Deg::from(spot.cutoff).0;
+                            if ui
+                                .add(egui::Slider::new(&mut cutoff, 1.0..=89.0).suffix("°"))
+                                .changed()
+                            {
+                                spot.cutoff = three_d::degrees(cutoff).into();
+                            }
+                        }
+                        if ui.button("Remove").clicked() {
+                            removed = Some(i);
+                        }
+                    });
+                }
+            });
+        if let Some(i) = removed {
+            custom_3d.lights.remove(i);
+        }
+    }
+
+    /// Scales the model, then rotates it by the angle around each selected axis in turn,
+    /// which makes the model tumble when several are selected.
+    fn model_transformation(&self) -> three_d::Mat4 {
+        use three_d::*;
+
+        let axes = [
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            vec3(0.0, 0.0, 1.0),
+        ];
+        axes.into_iter()
+            .zip(self.rotation_axes)
+            .filter(|(_, selected)| *selected)
+            .fold(Mat4::identity(), |rotation, (axis, _)| {
+                rotation * Mat4::from_axis_angle(axis, radians(self.angle))
+            })
+            * Mat4::from_scale(self.scale)
+    }
+}
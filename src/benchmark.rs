@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use eframe::{egui, egui_glow::glow};
+use three_d::{Mat4, SquareMatrix};
+use web_time::Instant;
+
+use crate::custom3d::Custom3d;
+use crate::orbit::{OrbitControl, OrbitInput};
+use crate::primitives::Primitive;
+
+/// A reproducible run of frames rendered offscreen while the camera orbits the scene once, for comparing the
+/// frame times across changes.
+///
+/// Runs on any glow context current on the calling thread, like the surfaceless EGL context of
+/// [`headless_gl`](crate::headless_gl) `--bench` uses in CI, or the one of the window it opens without EGL.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Benchmark {
+    pub frames: u32,
+    pub width: u32,
+    pub height: u32,
+    pub primitive: Primitive,
+    /// Renders this many instanced copies of the triangle instead of the shape, which is left out then.
+    pub instances: Option<u32>,
+}
+
+impl Default for Benchmark {
+    fn default() -> Self {
+        Self {
+            frames: 300,
+            width: 512,
+            height: 512,
+            primitive: Primitive::Triangle,
+            instances: None,
+        }
+    }
+}
+
+impl Benchmark {
+    /// Parses the options following `--bench` on the command line: `--frames N`, `--size WIDTHxHEIGHT`,
+    /// `--shape NAME` and `--instances N`, with the defaults for those left out.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut benchmark = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+            let parse_count = |value: String| {
+                value
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|&count| count > 0)
+                    .ok_or(format!("{arg} needs a positive number, got {value:?}"))
+            };
+            match arg.as_str() {
+                "--frames" => benchmark.frames = parse_count(value()?)?,
+                "--instances" => benchmark.instances = Some(parse_count(value()?)?),
+                "--size" => {
+                    let size = value()?;
+                    let (width, height) = size
+                        .split_once('x')
+                        .ok_or(format!("--size needs WIDTHxHEIGHT, got {size:?}"))?;
+                    benchmark.width = parse_count(width.to_owned())?;
+                    benchmark.height = parse_count(height.to_owned())?;
+                }
+                "--shape" => {
+                    let name = value()?;
+                    benchmark.primitive = Primitive::ALL
+                        .into_iter()
+                        .find(|primitive| primitive.name().eq_ignore_ascii_case(&name))
+                        .ok_or(format!("unknown shape {name:?}"))?;
+                }
+                _ => return Err(format!("unknown benchmark option {arg:?}")),
+            }
+        }
+        Ok(benchmark)
+    }
+
+    /// Renders the frames and times each of them. Reading back the pixels of every frame waits for the GPU to
+    /// finish it, so the times include the GPU work, and the small cost of the readback.
+    pub fn run(&self, gl: &Arc<glow::Context>) -> Result<BenchmarkReport, String> {
+        let mut custom_3d = Custom3d::new(gl)?;
+        if let Some(instances) = self.instances {
+            custom_3d.instancing = true;
+            custom_3d.instance_count = instances;
+            custom_3d.rebuild_instances();
+        } else {
+            custom_3d.set_primitive(self.primitive);
+        }
+        // One full turn over all frames, in the points of a drag that the orbit control turns by.
+        let step = std::f32::consts::TAU / OrbitControl::ORBIT_SPEED / self.frames as f32;
+        let frame_times = (0..self.frames)
+            .map(|_| {
+                custom_3d.handle_input(&OrbitInput {
+                    orbit: egui::vec2(step, 0.0),
+                    ..Default::default()
+                });
+                let start = Instant::now();
+                custom_3d.paint_to_pixels(self.width, self.height, 1.0, Mat4::identity());
+                start.elapsed().as_secs_f32() * 1000.0
+            })
+            .collect();
+        Ok(BenchmarkReport { frame_times })
+    }
+}
+
+/// Frame times of a [`Benchmark`] run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchmarkReport {
+    /// Milliseconds each frame took, in the order they were rendered.
+    pub frame_times: Vec<f32>,
+}
+
+impl BenchmarkReport {
+    /// Average frame time in milliseconds.
+    pub fn average(&self) -> f32 {
+        self.frame_times.iter().sum::<f32>() / self.frame_times.len().max(1) as f32
+    }
+
+    /// Frame time in milliseconds that `percent` of the frames took at most, by the nearest rank.
+    pub fn percentile(&self, percent: f32) -> f32 {
+        let mut sorted = self.frame_times.clone();
+        sorted.sort_by(f32::total_cmp);
+        let rank = (percent / 100.0 * sorted.len() as f32).ceil() as usize;
+        sorted
+            .get(rank.clamp(1, sorted.len().max(1)) - 1)
+            .copied()
+            .unwrap_or(0.0)
+    }
+}
+
+impl std::fmt::Display for BenchmarkReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let average = self.average();
+        writeln!(f, "frames: {}", self.frame_times.len())?;
+        writeln!(
+            f,
+            "average: {average:.3} ms ({:.1} fps)",
+            1000.0 / average.max(f32::EPSILON)
+        )?;
+        for percent in [50.0, 95.0, 99.0] {
+            writeln!(f, "p{percent}: {:.3} ms", self.percentile(percent))?;
+        }
+        Ok(())
+    }
+}
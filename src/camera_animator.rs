@@ -0,0 +1,65 @@
+use three_d::{Camera, Rad, VectorSpace};
+use three_d_asset::ProjectionType;
+
+/// Moves a camera to another view over a number of frames, easing in and out with smoothstep.
+pub struct CameraAnimator {
+    start: Camera,
+    end: Camera,
+    /// Length of the animation in seconds.
+    duration: f32,
+    elapsed: f32,
+}
+
+impl CameraAnimator {
+    pub fn new(start: Camera, end: Camera, duration: f32) -> Self {
+        Self {
+            start,
+            end,
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Advances the animation by `dt` seconds and moves the camera along, keeping its viewport.
+    /// Returns whether the animation is still running, once it's done the camera has the exact end view.
+    pub fn update(&mut self, camera: &mut Camera, dt: f32) -> bool {
+        self.elapsed += dt;
+        let t = if self.duration > 0.0 {
+            (self.elapsed / self.duration).min(1.0)
+        } else {
+            1.0
+        };
+        if t >= 1.0 {
+            let viewport = camera.viewport();
+            *camera = self.end.clone();
+            camera.set_viewport(viewport);
+            return false;
+        }
+
+        let s = t * t * (3.0 - 2.0 * t);
+        let (start, end) = (&self.start, &self.end);
+        camera.set_view(
+            start.position().lerp(*end.position(), s),
+            start.target().lerp(*end.target(), s),
+            start.up().lerp(*end.up(), s),
+        );
+        let lerp = |a: f32, b: f32| a + (b - a) * s;
+        let (z_near, z_far) = (
+            lerp(start.z_near(), end.z_near()),
+            lerp(start.z_far(), end.z_far()),
+        );
+        // A change between perspective and orthographic can't be eased, so it happens at the end.
+        match (start.projection_type(), end.projection_type()) {
+            (
+                ProjectionType::Perspective { field_of_view_y: a },
+                ProjectionType::Perspective { field_of_view_y: b },
+            ) => camera.set_perspective_projection(Rad(lerp(a.0, b.0)), z_near, z_far),
+            (
+                ProjectionType::Orthographic { height: a },
+                ProjectionType::Orthographic { height: b },
+            ) => camera.set_orthographic_projection(lerp(*a, *b), z_near, z_far),
+            _ => {}
+        }
+        true
+    }
+}
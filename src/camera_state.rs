@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+use crate::custom3d::ProjectionMode;
+
+/// The view saved to disk, as `three_d::Camera` can't be serialized itself.
+#[derive(Serialize, Deserialize)]
+pub struct CameraState {
+    pub position: [f32; 3],
+    pub target: [f32; 3],
+    pub up: [f32; 3],
+    pub projection: ProjectionMode,
+    /// Vertical field of view in degrees, also kept for orthographic projections.
+    pub field_of_view: f32,
+    pub z_near: f32,
+    pub z_far: f32,
+    /// Rotation of the model in radians.
+    pub angle: f32,
+}
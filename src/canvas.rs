@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use eframe::egui::mutex::Mutex;
+use eframe::{egui, egui_glow};
+
+/// An egui widget painting with three-d through a user supplied closure, for scenes that don't need
+/// the camera controls and settings of [`crate::ThreeDView`].
+///
+/// ```no_run
+/// # use eframe::egui;
+/// # fn ui(ui: &mut egui::Ui) {
+/// egui_three_d_demo::ThreeDCanvas::new(|context, viewport| {
+///     // Render three-d objects here, the viewport is already cleared.
+/// })
+/// .size(egui::vec2(320.0, 240.0))
+/// .clear_color(egui::Color32::from_rgb(30, 30, 30))
+/// .show(ui);
+/// # }
+/// ```
+///
+/// The closure runs in the paint callback, after the widget is shown, so it only gets shared state like an
+/// `Arc<Mutex<_>>` it captures.
+pub struct ThreeDCanvas<F> {
+    size: Option<egui::Vec2>,
+    capture_input: bool,
+    clear_color: Option<egui::Color32>,
+    render: F,
+}
+
+impl<F> ThreeDCanvas<F>
+where
+    F: Fn(&three_d::Context, three_d::Viewport) + Send + Sync + 'static,
+{
+    /// The canvas never shrinks below this, a zero sized viewport would break the projection matrix.
+    const MIN_SIZE: egui::Vec2 = egui::vec2(64.0, 64.0);
+
+    /// `render` draws into the canvas with the three-d context and the viewport of the canvas in physical pixels.
+    pub fn new(render: F) -> Self {
+        Self {
+            size: None,
+            capture_input: true,
+            clear_color: None,
+            render,
+        }
+    }
+
+    /// Size of the canvas in points, all of the available space by default.
+    pub fn size(mut self, size: egui::Vec2) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Whether the canvas takes clicks and drags, which the returned response reports. On by default,
+    /// turned off the canvas only senses hovering and lets the input through to widgets behind it.
+    pub fn capture_input(mut self, capture_input: bool) -> Self {
+        self.capture_input = capture_input;
+        self
+    }
+
+    /// Fills the canvas with the color before rendering, by default it keeps the egui background.
+    pub fn clear_color(mut self, color: egui::Color32) -> Self {
+        self.clear_color = Some(color);
+        self
+    }
+
+    /// Allocates the canvas and adds the paint callback rendering into it. Nothing is rendered if three-d can't
+    /// render with the GL context of the painter.
+    pub fn show(self, ui: &mut egui::Ui) -> egui::Response {
+        let size = self
+            .size
+            .unwrap_or_else(|| ui.available_size())
+            .max(Self::MIN_SIZE);
+        let sense = if self.capture_input {
+            egui::Sense::click_and_drag()
+        } else {
+            egui::Sense::hover()
+        };
+        let (rect, response) = ui.allocate_exact_size(size, sense);
+
+        let shared = ui.data_mut(|data| {
+            data.get_temp_mut_or_default::<SharedContext>(egui::Id::new("three_d_context"))
+                .clone()
+        });
+        let Self {
+            clear_color,
+            render,
+            ..
+        } = self;
+        let callback = egui_glow::CallbackFn::new(move |info, painter| {
+            let viewport = canvas_viewport(&info);
+            if viewport.width == 0 || viewport.height == 0 {
+                return;
+            }
+            let Some(context) = shared.get_or_create(painter.gl()) else {
+                return;
+            };
+
+            // Only clear inside the canvas, the rest of the window belongs to egui.
+            let [width, height] = info.screen_size_px;
+            let clear_state = match clear_color {
+                // egui paints into a framebuffer without sRGB conversion, so the color goes in as it is.
+                Some(c) => {
+                    let [r, g, b, a] = c.to_array().map(|c| c as f32 / 255.0);
+                    three_d::ClearState::color_and_depth(r, g, b, a, 1.0)
+                }
+                None => three_d::ClearState::depth(1.0),
+            };
+            three_d::RenderTarget::screen(&context, width, height)
+                .clear_partially(three_d::ScissorBox::from(viewport), clear_state);
+            render(&context, viewport);
+        });
+        ui.painter().add(egui::PaintCallback {
+            rect,
+            callback: Arc::new(callback),
+        });
+        response
+    }
+}
+
+/// The three-d context for the GL context of the painter, set up by the first canvas painted and shared by all of
+/// them so the shader programs three-d compiles are reused.
+#[derive(Clone, Default)]
+struct SharedContext(Arc<Mutex<Option<three_d::Context>>>);
+
+// SAFETY: The glow context inside `three_d::Context` isn't `Send` on the web, but the web is single threaded
+// and eframe only ever runs the paint callback on the main thread.
+#[cfg(target_arch = "wasm32")]
+unsafe impl Send for SharedContext {}
+#[cfg(target_arch = "wasm32")]
+unsafe impl Sync for SharedContext {}
+
+impl SharedContext {
+    fn get_or_create(&self, gl: &Arc<egui_glow::glow::Context>) -> Option<three_d::Context> {
+        let mut context = self.0.lock();
+        if context.is_none() {
+            *context = three_d::Context::from_gl_context(gl.clone()).ok();
+        }
+        context.clone()
+    }
+}
+
+/// The canvas rect in physical pixels, as the viewport three-d expects with the origin in the lower left corner.
+pub(crate) fn canvas_viewport(info: &egui::PaintCallbackInfo) -> three_d::Viewport {
+    let viewport_pixels = info.viewport_in_pixels();
+
+    // Round the edges rather than the size, otherwise a fractional position (common with a
+    // pixels_per_point other than 1) can leave the far edge a pixel short of the egui frame.
+    let left = viewport_pixels.left_px.round();
+    let right = (viewport_pixels.left_px + viewport_pixels.width_px).round();
+    let bottom = viewport_pixels.from_bottom_px.round();
+    let top = (viewport_pixels.from_bottom_px + viewport_pixels.height_px).round();
+    let viewport = three_d::Viewport {
+        x: left as _,
+        y: bottom as _,
+        width: (right - left) as _,
+        height: (top - bottom) as _,
+    };
+
+    // Every edge of the viewport must be within half a pixel of the physical canvas rect.
+    let (rect, ppp) = (info.viewport, info.pixels_per_point);
+    let [_, screen_height] = info.screen_size_px;
+    let top_down = |y: i32| screen_height as f32 - y as f32;
+    let edges = [
+        (viewport.x as f32, rect.left() * ppp),
+        (
+            (viewport.x + viewport.width as i32) as f32,
+            rect.right() * ppp,
+        ),
+        (top_down(viewport.y), rect.bottom() * ppp),
+        (
+            top_down(viewport.y + viewport.height as i32),
+            rect.top() * ppp,
+        ),
+    ];
+    debug_assert!(
+        edges
+            .iter()
+            .all(|(edge, expected)| (edge - expected).abs() <= 0.5 + 1e-3),
+        "viewport {viewport:?} doesn't match the canvas rect {rect:?} at {ppp} pixels per point"
+    );
+    viewport
+}
@@ -0,0 +1,70 @@
+use three_d::*;
+
+/// Re-encodes `colors` so the linear colors three-d uploads for them are their raw sRGB values, interpolating the
+/// encoded values like renderers that skip the conversion do.
+pub(crate) fn raw_colors(colors: [Srgba; 3]) -> [Srgba; 3] {
+    let encode = |value: u8| {
+        let linear = value as f32 / 255.0;
+        let srgb = if linear <= 0.0031308 {
+            linear * 12.92
+        } else {
+            1.055 * linear.powf(1.0 / 2.4) - 0.055
+        };
+        (srgb * 255.0).round() as u8
+    };
+    colors.map(|color| Srgba::new(encode(color.r), encode(color.g), encode(color.b), color.a))
+}
+
+/// Shows the vertex colors of [`raw_colors`] unlit, decoding the interpolated values back to linear for the color
+/// mapping, so the triangle looks like the vertex colors were blended in sRGB.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct RawInterpolationMaterial;
+
+impl Material for RawInterpolationMaterial {
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        format!(
+            "
+            in vec4 col;
+
+            layout (location = 0) out vec4 outColor;
+
+            {}
+
+            vec3 to_linear(vec3 srgb)
+            {{
+                return mix(srgb / 12.92, pow((srgb + 0.055) / 1.055, vec3(2.4)), step(0.04045, srgb));
+            }}
+
+            void main()
+            {{
+                outColor = vec4(color_mapping(to_linear(col.rgb)), col.a);
+            }}
+            ",
+            ColorMapping::fragment_shader_source()
+        )
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            color: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.color_mapping.use_uniforms(program);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates::default()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+
+    fn id(&self) -> u16 {
+        // Next to the pulsing triangle.
+        0b1u16 << 15 | 0b1u16 << 12
+    }
+}
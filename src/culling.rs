@@ -0,0 +1,45 @@
+use three_d::*;
+
+/// The six planes bounding what a camera sees, for skipping objects outside of it before rendering them.
+#[derive(Clone, Copy, Debug)]
+pub struct Frustum {
+    /// Planes with their normals pointing inwards, as `(normal, distance)` packed into a `Vec4`, so a point `p` is
+    /// inside when `plane.truncate().dot(p) + plane.w >= 0` for all of them.
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes from the rows of the view-projection matrix, which works for both projections.
+    pub fn new(camera: &Camera) -> Self {
+        let m = camera.projection() * camera.view();
+        let row = |i: usize| vec4(m.x[i], m.y[i], m.z[i], m.w[i]);
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        let planes = [w + x, w - x, w + y, w - y, w + z, w - z].map(|plane| {
+            let length = plane.truncate().magnitude();
+            if length > 0.0 {
+                plane / length
+            } else {
+                plane
+            }
+        });
+        Self { planes }
+    }
+
+    /// Whether any of the box may be inside. Boxes near a corner of the frustum can pass without being seen, which
+    /// only costs rendering them. Empty boxes are never inside.
+    pub fn intersects(&self, aabb: &AxisAlignedBoundingBox) -> bool {
+        if aabb.is_empty() {
+            return false;
+        }
+        let (min, max) = (aabb.min(), aabb.max());
+        self.planes.iter().all(|plane| {
+            // The corner furthest along the normal is outside only if the whole box is.
+            let corner = vec3(
+                if plane.x >= 0.0 { max.x } else { min.x },
+                if plane.y >= 0.0 { max.y } else { min.y },
+                if plane.z >= 0.0 { max.z } else { min.z },
+            );
+            plane.truncate().dot(corner) + plane.w >= 0.0
+        })
+    }
+}
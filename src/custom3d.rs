@@ -0,0 +1,3509 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use eframe::{egui, egui_glow::glow};
+use three_d_asset::ProjectionType;
+
+use crate::ambient_occlusion::{AmbientOcclusion, AmbientOcclusionEffect};
+use crate::camera_animator::CameraAnimator;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::camera_state::CameraState;
+use crate::canvas;
+use crate::color_interpolation::{self, RawInterpolationMaterial};
+use crate::culling::Frustum;
+use crate::debug_draw::{DebugDraw, DebugDrawModels};
+use crate::debug_view::{AttributeMaterial, DebugView};
+use crate::depth_of_field::{DepthOfField, DepthOfFieldEffect};
+use crate::environment::{BuiltinSky, Sky};
+use crate::fly::FlyControl;
+use crate::framebuffer_srgb::{FramebufferSrgb, SrgbSupport};
+use crate::instancing;
+use crate::lights::{self, SceneLight};
+use crate::lines::{self, LineModel};
+use crate::orbit::{OrbitControl, OrbitInput};
+use crate::outline::OutlineEffect;
+use crate::particles::ParticleFountain;
+use crate::picking::{DepthSample, Pick, Ray};
+use crate::primitives::{self, Primitive};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::project::{
+    self, MissingObject, ProjectFile, ProjectLight, ProjectLightKind, ProjectObject, ProjectSource,
+};
+use crate::pulse::PulseMaterial;
+use crate::scene_object::{ObjectSource, SceneObject, Transform};
+use crate::screenshot::{self, ScreenshotFormat, ScreenshotRequest, Turntable, TurntableRequest};
+use crate::simplify::{self, LevelOfDetail, SimplifiedMesh};
+use crate::stats::{DrawCalls, RenderStats};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::stats::{ImportStats, MeshStats};
+use crate::texture_settings::TextureSettings;
+use crate::tone_mapping::{self, ExposureEffect};
+use crate::translate_gizmo::TranslateGizmo;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Navigation {
+    Orbit,
+    Fly,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ProjectionMode {
+    Perspective,
+    Orthographic,
+}
+
+/// How the vertex colors and normals vary across the triangles of the models.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shading {
+    /// Interpolated between the corners.
+    Smooth,
+    /// The same across each triangle, taken from its last corner.
+    Flat,
+}
+
+/// Views along the world axes, like the numpad views of Blender.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ViewPreset {
+    Front,
+    Back,
+    Right,
+    Left,
+    Top,
+    Bottom,
+}
+
+impl ViewPreset {
+    pub const ALL: [ViewPreset; 6] = [
+        ViewPreset::Front,
+        ViewPreset::Back,
+        ViewPreset::Right,
+        ViewPreset::Left,
+        ViewPreset::Top,
+        ViewPreset::Bottom,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ViewPreset::Front => "Front",
+            ViewPreset::Back => "Back",
+            ViewPreset::Right => "Right",
+            ViewPreset::Left => "Left",
+            ViewPreset::Top => "Top",
+            ViewPreset::Bottom => "Bottom",
+        }
+    }
+
+    /// The view from the other side.
+    pub fn opposite(self) -> Self {
+        match self {
+            ViewPreset::Front => ViewPreset::Back,
+            ViewPreset::Back => ViewPreset::Front,
+            ViewPreset::Right => ViewPreset::Left,
+            ViewPreset::Left => ViewPreset::Right,
+            ViewPreset::Top => ViewPreset::Bottom,
+            ViewPreset::Bottom => ViewPreset::Top,
+        }
+    }
+
+    /// Direction from the target to the camera, and the up direction of the camera.
+    fn direction_and_up(self) -> (three_d::Vec3, three_d::Vec3) {
+        use three_d::vec3;
+
+        match self {
+            ViewPreset::Front => (vec3(0.0, 0.0, 1.0), vec3(0.0, 1.0, 0.0)),
+            ViewPreset::Back => (vec3(0.0, 0.0, -1.0), vec3(0.0, 1.0, 0.0)),
+            ViewPreset::Right => (vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)),
+            ViewPreset::Left => (vec3(-1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)),
+            // Looking down with the back of the scene at the top, so the front view is below it like on paper.
+            ViewPreset::Top => (vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, -1.0)),
+            ViewPreset::Bottom => (vec3(0.0, -1.0, 0.0), vec3(0.0, 0.0, 1.0)),
+        }
+    }
+}
+
+/// Offscreen target for antialiasing, kept between frames while the view size and sample count stay the same.
+struct MsaaTarget<T: three_d::TextureDataType> {
+    target: three_d::RenderTargetMultisample<T, f32>,
+    /// The resolved colors, copied into the canvas.
+    color: three_d::Texture2D,
+    /// The resolved depth, only updated for the depth of field.
+    depth: three_d::DepthTexture2D,
+}
+
+/// Color and depth textures to render into offscreen, kept between frames while the size stays the same.
+struct OffscreenTarget {
+    color: three_d::Texture2D,
+    depth: three_d::DepthTexture2D,
+}
+
+impl OffscreenTarget {
+    fn new<T: three_d::TextureDataType>(
+        context: &three_d::Context,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        use three_d::*;
+
+        Self {
+            color: Texture2D::new_empty::<T>(
+                context,
+                width,
+                height,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ),
+            depth: DepthTexture2D::new::<f32>(
+                context,
+                width,
+                height,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ),
+        }
+    }
+
+    /// The target kept from before if it has the size, or else a new one with colors of `T`.
+    fn reuse<T: three_d::TextureDataType>(
+        kept: Option<Self>,
+        context: &three_d::Context,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        kept.filter(|target| (target.color.width(), target.color.height()) == (width, height))
+            .unwrap_or_else(|| Self::new::<T>(context, width, height))
+    }
+
+    fn render_target(&mut self) -> three_d::RenderTarget<'_> {
+        three_d::RenderTarget::new(
+            self.color.as_color_target(None),
+            self.depth.as_depth_target(),
+        )
+    }
+}
+
+/// The targets of [`Custom3d::render_to_pixels`], kept between frames while the size stays the same.
+#[derive(Default)]
+struct PixelTargets {
+    target: Option<OffscreenTarget>,
+    /// The scene before the effects are applied, with linear colors above 1 for the tone mapping.
+    scene: Option<OffscreenTarget>,
+    outline: Option<OffscreenTarget>,
+}
+
+/// The offscreen targets of a view, at most one of the antialiased ones is kept depending on the settings.
+#[derive(Default)]
+struct ViewTargets {
+    msaa: Option<MsaaTarget<[u8; 4]>>,
+    /// Keeps the linear colors above 1 for the tone mapping when post-processing.
+    hdr: Option<MsaaTarget<[three_d::f16; 4]>>,
+    /// The mask of the selected object the outline is drawn around, once something was selected.
+    outline: Option<OffscreenTarget>,
+}
+
+pub struct Custom3d {
+    three_d: three_d::Context,
+    camera: three_d::Camera,
+    /// Kept separately from the camera, as an orthographic projection has no field of view.
+    field_of_view: f32,
+    /// The camera to reset to, framing the loaded model.
+    home_camera: three_d::Camera,
+    pub projection_mode: ProjectionMode,
+    pub orbit: OrbitControl,
+    pub navigation: Navigation,
+    pub fly: FlyControl,
+    /// Moves the camera to a new view, `None` when it's not moving by itself.
+    animator: Option<CameraAnimator>,
+    objects: Vec<SceneObject>,
+    /// Rebuilds the lines drawn from the objects on the next paint, after one was moved, hidden or removed.
+    objects_dirty: bool,
+    /// The built-in shape shown, `None` when a model was loaded instead.
+    primitive: Option<Primitive>,
+    shading: Shading,
+    /// Models of the opened project whose files couldn't be loaded, kept to save them again.
+    #[cfg(not(target_arch = "wasm32"))]
+    missing_objects: Vec<MissingObject>,
+    /// Counts and bounds of the model file loaded last, cleared when the models are replaced otherwise.
+    #[cfg(not(target_arch = "wasm32"))]
+    import_stats: Option<ImportStats>,
+    level_of_detail: LevelOfDetail,
+    /// Draw calls of the main and side views, counted from one paint of the main view to the next.
+    draw_calls: DrawCalls,
+    /// Corner colors of the built-in triangle, `None` when a model was loaded instead.
+    vertex_colors: Option<[three_d::Srgba; 3]>,
+    /// Maps `triangle_texture` onto the built-in triangle instead of coloring it by the vertex colors.
+    textured: bool,
+    triangle_texture: three_d::Texture2DRef,
+    /// The image of `triangle_texture`, kept for uploading it again with other texture settings.
+    triangle_cpu_texture: three_d::CpuTexture,
+    texture_settings: TextureSettings,
+    /// Shows a large plane tiled with the checkerboard, for comparing the texture settings at grazing angles.
+    pub show_texture_plane: bool,
+    texture_plane: three_d::Gm<three_d::Mesh, three_d::ColorMaterial>,
+    /// Shows a fountain of particles at the origin, advanced by `animate`.
+    pub show_particles: bool,
+    /// Renders the built-in triangle with [`PulseMaterial`], its colors pulsing over [`Self::time`].
+    pub pulse: bool,
+    /// Seconds advanced by [`Self::animate`], for the time uniform of animated shaders.
+    time: f32,
+    pub particles: ParticleFountain,
+    /// Renders the built-in triangle unlit, so its corners show exactly the vertex colors, for comparing them with
+    /// egui swatches of the same values.
+    ///
+    /// The colors match without any conversion: three-d uploads the `Srgba` vertex colors as linear colors and
+    /// the materials encode their output back to sRGB (`ColorMapping::ComputeToSrgb`), while egui_glow leaves
+    /// `GL_FRAMEBUFFER_SRGB` disabled and draws its own sRGB encoded colors into the same framebuffer as is.
+    /// Post-processing and the tone mapping intentionally change the colors, so they won't match with it on.
+    pub calibration: bool,
+    /// Renders the built-in triangle unlit next to a copy blending its vertex colors as raw sRGB values, to the
+    /// right of it, for comparing them with the gamma-correct blending three-d does.
+    pub compare_interpolation: bool,
+    /// The copy for `compare_interpolation`, built when it's first shown after the triangle changed.
+    raw_triangle: Option<three_d::Gm<three_d::Mesh, RawInterpolationMaterial>>,
+    /// Overrides the GPU encoding to sRGB while painting into the canvas.
+    pub framebuffer_srgb: FramebufferSrgb,
+    srgb_support: SrgbSupport,
+    /// Colors all models by one of their attributes instead of shading them.
+    pub debug_view: DebugView,
+    /// Set when the vertex colors, the texturing or the morph changed, so the meshes are only rebuilt on the next paint.
+    triangle_dirty: bool,
+    /// How far the built-in triangle is morphed into its flattened variant, from 0 to 1.
+    morph: f32,
+    /// Which sides of the model triangles are left out, copied to their materials before each render.
+    /// Culling the front faces shows whether a model with inverted winding is inside out.
+    pub cull: three_d::Cull,
+    /// Fits the clipping planes around the scene from where the camera is on every frame, instead of keeping the
+    /// planes set with [`Self::set_lens`], so the depth precision suits models of any size.
+    pub auto_clip_planes: bool,
+    /// Distance of the near plane as a fraction of the distance to the scene, with `auto_clip_planes`.
+    pub auto_near_factor: f32,
+    /// Renders copies of the triangle in a single draw call instead of the models.
+    pub instancing: bool,
+    pub instance_count: u32,
+    instanced_model: three_d::Gm<three_d::InstancedMesh, three_d::PhysicalMaterial>,
+    pub show_bounding_box: bool,
+    bounding_box: LineModel,
+    /// Bounds of the visible objects as placed by their transforms, before the transformation of the whole scene.
+    model_aabb: three_d::AxisAlignedBoundingBox,
+    /// Bounds of the models as last rendered.
+    world_aabb: three_d::AxisAlignedBoundingBox,
+    /// A sphere partly behind the triangle, showing that depth testing works inside the canvas.
+    pub show_occluder: bool,
+    occluder: three_d::Gm<three_d::Mesh, three_d::PhysicalMaterial>,
+    /// Two overlapping see-through squares, one in front of and one behind the triangle.
+    pub show_panes: bool,
+    /// Opacity of the panes, copied to their materials before each render.
+    pub pane_alpha: f32,
+    panes: [three_d::Gm<three_d::Mesh, three_d::PhysicalMaterial>; 2],
+    /// A large plane below the models, catching their shadows.
+    pub show_ground: bool,
+    ground: three_d::Gm<three_d::Mesh, three_d::PhysicalMaterial>,
+    /// Lets the models cast shadows from the directional light, regenerating the shadow map every frame.
+    pub shadows: bool,
+    /// Side length of the shadow map in texels.
+    pub shadow_map_size: u32,
+    /// Renders the edges of the models instead of their surfaces.
+    pub show_wireframe: bool,
+    wireframe: LineModel,
+    /// Renders each vertex of the models as a small sphere instead of their surfaces, as a point cloud viewer.
+    pub show_points: bool,
+    /// Color of the points, copied to their material before each render.
+    pub point_color: three_d::Srgba,
+    /// Radius of the points, as a fraction of the diagonal of the model bounds.
+    point_size: f32,
+    points: three_d::Gm<three_d::InstancedMesh, three_d::ColorMaterial>,
+    /// Draws a line from each vertex along its normal, colored by its direction.
+    pub show_normals: bool,
+    /// Length of the normal lines, as a fraction of the diagonal of the model bounds.
+    normal_length: f32,
+    normals: LineModel,
+    ambient: three_d::AmbientLight,
+    directional: three_d::DirectionalLight,
+    /// Direction the directional light shines in, copied to the light before each render.
+    light_direction: three_d::Vec3,
+    /// Point and spot lights on top of the ambient and directional light.
+    pub lights: Vec<SceneLight>,
+    light_markers: three_d::Gm<three_d::InstancedMesh, three_d::ColorMaterial>,
+    /// Renders the lit surfaces of the models with three-d's deferred renderer, which lights a G-buffer of the
+    /// visible surfaces in one pass instead of lighting every fragment of every model.
+    pub deferred: bool,
+    /// Number of samples per pixel when antialiasing, 1 renders directly into the canvas without antialiasing.
+    pub msaa_samples: u32,
+    /// The largest sample count the GPU supports.
+    max_msaa_samples: u32,
+    targets: ViewTargets,
+    /// Shows a top-down orthographic view of the models to the right of the main view.
+    pub split_view: bool,
+    side_targets: ViewTargets,
+    /// Used from shared references while rendering, so kept in a cell.
+    pixel_targets: std::cell::Cell<PixelTargets>,
+    /// Draws the frustum of the main camera in the top-down view of the split view, and the other way around.
+    pub show_frustums: bool,
+    /// Frustum of the main camera, drawn in the top-down view.
+    main_frustum: LineModel,
+    /// Frustum of the top-down camera as last painted, drawn in the main view.
+    side_frustum: LineModel,
+    /// Renders the scene in linear colors and applies the exposure and tone mapping afterwards,
+    /// instead of the materials tone mapping their colors directly.
+    pub post_processing: bool,
+    pub tone_mapping: three_d::ToneMapping,
+    /// Factor the linear colors are scaled by before the tone mapping.
+    pub exposure: f32,
+    pub depth_of_field: DepthOfField,
+    pub ambient_occlusion: AmbientOcclusion,
+    /// Color the canvas is cleared to before rendering.
+    pub clear_color: three_d::Srgba,
+    /// Renders the sky behind the scene and lights the models by it instead of the ambient light.
+    pub show_skybox: bool,
+    /// Created when the skybox is first shown, as deriving the lighting from it takes a moment.
+    sky: Option<Sky>,
+    /// The built-in panorama of the sky, `None` when one was loaded instead.
+    builtin_sky: Option<BuiltinSky>,
+    pub show_grid: bool,
+    /// Half the side length of the grid.
+    pub grid_extent: f32,
+    pub grid_spacing: f32,
+    grid: LineModel,
+    /// Width of the grid, wireframe, normal, bounding box and measurement lines, in points.
+    pub line_width: f32,
+    /// Renders the axes in the lower left corner, mirroring the rotation of the main camera.
+    gizmo_camera: three_d::Camera,
+    axes: three_d::Axes,
+    pub screenshot_request: Option<ScreenshotRequest>,
+    pub screenshot_result: Option<Result<PathBuf, String>>,
+    turntable: Option<Turntable>,
+    /// The directory of the last finished turntable export, or why it failed.
+    pub turntable_result: Option<Result<PathBuf, String>>,
+    /// Ray of the last click, intersected with the models on the next paint.
+    pick_ray: Option<Ray>,
+    /// Ray of the last double-click, the camera target moves to where it hits the models on the next paint.
+    focus_ray: Option<Ray>,
+    /// The model under the last click, `None` if the click missed.
+    pub picked: Option<Pick>,
+    /// Index of the outlined object, picked by clicking it or chosen in the scene panel.
+    selected: Option<usize>,
+    /// Skips the models outside the view of the main camera.
+    pub frustum_culling: bool,
+    /// Renders the culled models in magenta instead of leaving them out, seen in the top-down view of the split view.
+    pub show_culled: bool,
+    /// Distance the nudge keys move the selected object by.
+    pub nudge_step: f32,
+    /// Tints the model under the cursor, showing what a click would pick.
+    pub highlight_hovered: bool,
+    /// Cursor position in the canvas as last set, the hovered model is only picked again once it moves.
+    hover_pos: Option<egui::Pos2>,
+    /// Ray under the cursor since it moved, intersected with the models on the next paint.
+    hover_ray: Option<Ray>,
+    /// Index of the tinted model under the cursor.
+    hovered: Option<usize>,
+    /// Arrows at the selected object, dragged to move it.
+    translate_gizmo: TranslateGizmo,
+    /// Color and width in pixels of the outline around the selected object.
+    pub outline_color: three_d::Srgba,
+    pub outline_thickness: f32,
+    /// Adds the point of each click on a model to the measurement.
+    pub measuring: bool,
+    /// Up to two measured points in model space, so they stay on the model as it moves.
+    measurement: Vec<three_d::Vec3>,
+    measurement_line: LineModel,
+    /// Draws the ray under the cursor and marks where it first hits the models, for debugging picking.
+    /// The ray points straight away from the main camera, so it's best seen in the top-down view.
+    pub show_cursor_ray: bool,
+    /// Ray under the cursor, kept once the cursor leaves the canvas.
+    cursor_ray: Option<Ray>,
+    cursor_ray_line: LineModel,
+    /// Where the cursor ray first hits the models, `None` if it misses them.
+    cursor_hit: Option<three_d::Vec3>,
+    cursor_hit_marker: three_d::Gm<three_d::Mesh, three_d::ColorMaterial>,
+    /// Lines, boxes and points to render on the next paint, cleared by it.
+    pub debug_draw: DebugDraw,
+    debug_draw_models: DebugDrawModels,
+    /// Position of the cursor as a fraction of the canvas size with y down, `None` while it's elsewhere.
+    #[cfg(not(target_arch = "wasm32"))]
+    cursor: Option<egui::Vec2>,
+    /// Renders the depth of the scene and reads it back under the cursor every frame, for [`Self::cursor_depth`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub probe_depth: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    cursor_depth: Option<DepthSample>,
+    /// The target [`Self::sample_depth`] renders into. The scene renders through shared references, so it's kept
+    /// in a cell.
+    #[cfg(not(target_arch = "wasm32"))]
+    depth_probe: std::cell::Cell<Option<OffscreenTarget>>,
+    /// Transformation of the models as last rendered.
+    transformation: three_d::Mat4,
+}
+
+// SAFETY: The glow context inside `three_d::Context` isn't `Send` on the web, but the web is single threaded
+// and eframe only ever runs the paint callback on the main thread.
+#[cfg(target_arch = "wasm32")]
+unsafe impl Send for Custom3d {}
+
+impl Custom3d {
+    const FIELD_OF_VIEW_DEGREES: f32 = 45.0;
+    /// Smallest distance allowed for the near clipping plane.
+    const MIN_Z_NEAR: f32 = 0.001;
+    /// Side length of the axes gizmo viewport, in points.
+    const GIZMO_SIZE: f32 = 80.0;
+    const GIZMO_DISTANCE: f32 = 3.0;
+    /// Initial width of the lines, in points.
+    const DEFAULT_LINE_WIDTH: f32 = 1.5;
+    /// Length of the camera moves when resetting, framing or looking at a point, in seconds.
+    const CAMERA_ANIMATION_SECONDS: f32 = 0.3;
+    const TRIANGLE_POSITIONS: [three_d::Vec3; 3] = [
+        three_d::vec3(0.5, -0.5, 0.0),  // bottom right
+        three_d::vec3(-0.5, -0.5, 0.0), // bottom left
+        three_d::vec3(0.0, 0.5, 0.0),   // top
+    ];
+    /// The flattened triangle the built-in one morphs into, wide and low with the top tipped away from the camera.
+    const MORPHED_TRIANGLE_POSITIONS: [three_d::Vec3; 3] = [
+        three_d::vec3(0.8, -0.2, 0.2),
+        three_d::vec3(-0.8, -0.2, 0.2),
+        three_d::vec3(0.0, 0.1, -0.4),
+    ];
+    const DEFAULT_VERTEX_COLORS: [three_d::Srgba; 3] = [
+        three_d::Srgba::new(255, 0, 0, 255), // bottom right
+        three_d::Srgba::new(0, 255, 0, 255), // bottom left
+        three_d::Srgba::new(0, 0, 255, 255), // top
+    ];
+
+    /// Fails if three-d can't render with the GL context, like when it lacks the OpenGL (ES) version or
+    /// extensions three-d needs.
+    pub fn new(gl: &Arc<glow::Context>) -> Result<Self, String> {
+        use three_d::*;
+
+        let three_d = Context::from_gl_context(gl.clone())
+            .map_err(|err| format!("Failed to set up three-d with the GL context: {err}"))?;
+
+        // Construct a model, with a white physical material tinted by the per vertex colors, thereby transferring the mesh data to the GPU
+        let cpu_mesh = Self::triangle_mesh(Some(Self::DEFAULT_VERTEX_COLORS), 0.0);
+        let model = Gm::new(Mesh::new(&three_d, &cpu_mesh), PhysicalMaterial::default());
+        let mut object = SceneObject::new(Primitive::Triangle.name(), model, cpu_mesh);
+        object.source = Some(ObjectSource::Primitive(Primitive::Triangle));
+
+        Ok(Self {
+            vertex_colors: Some(Self::DEFAULT_VERTEX_COLORS),
+            ..Self::with_objects(three_d, vec![object])
+        })
+    }
+
+    /// Corners of the built-in triangle, `morph` of the way from its own shape to the flattened one.
+    fn triangle_positions(morph: f32) -> [three_d::Vec3; 3] {
+        use three_d::*;
+
+        let mut positions = Self::TRIANGLE_POSITIONS;
+        for (position, morphed) in positions.iter_mut().zip(Self::MORPHED_TRIANGLE_POSITIONS) {
+            *position = position.lerp(morphed, morph);
+        }
+        positions
+    }
+
+    /// The built-in triangle, left white for texturing when there are no `colors`.
+    fn triangle_mesh(colors: Option<[three_d::Srgba; 3]>, morph: f32) -> three_d::CpuMesh {
+        use three_d::*;
+
+        let mut cpu_mesh = CpuMesh {
+            positions: Positions::F32(Self::triangle_positions(morph).to_vec()),
+            // The texture coordinates follow the unmorphed corners, so the texture stretches along.
+            // The first row of a texture is the top of the image, so v grows downwards.
+            uvs: Some(
+                Self::TRIANGLE_POSITIONS
+                    .iter()
+                    .map(|p| vec2(p.x + 0.5, 0.5 - p.y))
+                    .collect(),
+            ),
+            colors: colors.map(|colors| colors.to_vec()),
+            ..Default::default()
+        };
+        cpu_mesh.compute_normals();
+        cpu_mesh
+    }
+
+    /// Loads an .obj file (and the .mtl file it references) instead of the built-in triangle.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_obj(gl: &Arc<glow::Context>, path: &std::path::Path) -> Result<Self, String> {
+        Self::load_model(gl, path)
+    }
+
+    /// Loads a .gltf or .glb file (and the buffers and textures it references) instead of the built-in triangle.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_gltf(gl: &Arc<glow::Context>, path: &std::path::Path) -> Result<Self, String> {
+        Self::load_model(gl, path)
+    }
+
+    /// Loads any model format three-d-asset can deserialize, chosen by the file extension,
+    /// with a camera framing the whole model.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_model(gl: &Arc<glow::Context>, path: &std::path::Path) -> Result<Self, String> {
+        let mut custom_3d = Self::new(gl)?;
+        Self::read_model(path)
+            .and_then(|cpu_model| custom_3d.set_model(cpu_model, Some(path)))
+            .map_err(|err| err.to_string())?;
+        Ok(custom_3d)
+    }
+
+    /// Reads and parses a model file without touching the GPU, so it can be done on another thread.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_model(path: &std::path::Path) -> three_d_asset::Result<three_d::CpuModel> {
+        let mut raw_assets = three_d_asset::io::load(&[path])?;
+        raw_assets.deserialize(path)
+    }
+
+    /// Replaces the models with the triangle meshes of `cpu_model` and frames the camera to fit them.
+    /// `path` is the file it was read from, which projects refer to.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_model(
+        &mut self,
+        mut cpu_model: three_d::CpuModel,
+        path: Option<&std::path::Path>,
+    ) -> three_d_asset::Result<()> {
+        self.prepare_model(&mut cpu_model)?;
+        self.drop_objects();
+        let objects = self.model_objects(cpu_model, path);
+        self.set_objects(objects);
+        self.import_stats = Some(Self::stats_of(&self.objects, path));
+        self.primitive = None;
+        Ok(())
+    }
+
+    /// Adds the triangle meshes of `cpu_model` to the models and frames the camera to fit all of them.
+    /// `path` is the file it was read from, which projects refer to.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_model(
+        &mut self,
+        mut cpu_model: three_d::CpuModel,
+        path: Option<&std::path::Path>,
+    ) -> three_d_asset::Result<()> {
+        self.prepare_model(&mut cpu_model)?;
+        let mut objects = self.model_objects(cpu_model, path);
+        self.import_stats = Some(Self::stats_of(&objects, path));
+        let start = self.objects.len();
+        self.objects.append(&mut objects);
+        self.finish_objects(start..self.objects.len());
+        self.primitive = None;
+        self.rebuild_lines();
+        self.frame_to_fit(self.model_aabb);
+        Ok(())
+    }
+
+    /// Adds a model built from `cpu_mesh`, which can share its vertices between triangles through its indices.
+    /// Fails without changing the scene if an index is out of range, or the buffers don't match.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_mesh(
+        &mut self,
+        name: impl Into<String>,
+        cpu_mesh: three_d::CpuMesh,
+    ) -> three_d_asset::Result<()> {
+        use three_d::*;
+
+        let name = name.into();
+        let cpu_model = CpuModel {
+            name: name.clone(),
+            geometries: vec![three_d_asset::Primitive {
+                name,
+                transformation: Mat4::identity(),
+                animations: Vec::new(),
+                geometry: CpuGeometry::Triangles(cpu_mesh),
+                material_index: None,
+            }],
+            materials: Vec::new(),
+        };
+        self.add_model(cpu_model, None)
+    }
+
+    /// Checks the triangle meshes of `cpu_model` and gets them ready for [`Self::model_objects`] without touching
+    /// the GPU, so a broken file fails before the models it replaces are dropped.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn prepare_model(&self, cpu_model: &mut three_d::CpuModel) -> three_d_asset::Result<()> {
+        use three_d::*;
+
+        for cpu_material in &mut cpu_model.materials {
+            self.texture_settings.apply_to_material(cpu_material);
+        }
+        for primitive in &mut cpu_model.geometries {
+            if let CpuGeometry::Triangles(cpu_mesh) = &mut primitive.geometry {
+                // three-d would panic uploading it, and computing the normals would index out of bounds before that.
+                cpu_mesh.validate()?;
+                // Bake the primitive transformation into the mesh so the model transformation can be set freely when painting.
+                cpu_mesh.transform(&primitive.transformation)?;
+                primitive.transformation = Mat4::identity();
+                if cpu_mesh.normals.is_none() {
+                    cpu_mesh.compute_normals();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts and bounds of the meshes of freshly loaded `objects`, which aren't placed yet.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn stats_of(objects: &[SceneObject], path: Option<&std::path::Path>) -> ImportStats {
+        ImportStats {
+            path: path.map(|path| path.to_owned()),
+            meshes: objects
+                .iter()
+                .map(|object| MeshStats::new(&object.name, &object.cpu_mesh))
+                .collect(),
+        }
+    }
+
+    /// Uploads a scene object for each triangle mesh of `cpu_model` prepared by [`Self::prepare_model`], named after
+    /// the mesh or else the model. The meshes keep their indices.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn model_objects(
+        &self,
+        cpu_model: three_d::CpuModel,
+        path: Option<&std::path::Path>,
+    ) -> Vec<SceneObject> {
+        use three_d::*;
+
+        let mut objects = Vec::new();
+        for primitive in cpu_model.geometries {
+            if let CpuGeometry::Triangles(cpu_mesh) = primitive.geometry {
+                let material = match primitive
+                    .material_index
+                    .and_then(|i| cpu_model.materials.get(i))
+                {
+                    Some(cpu_material) => PhysicalMaterial::new(&self.three_d, cpu_material),
+                    None => PhysicalMaterial::default(),
+                };
+                let name = if primitive.name.is_empty() {
+                    format!("{} {}", cpu_model.name, objects.len() + 1)
+                } else {
+                    primitive.name
+                };
+                let model = Gm::new(Mesh::new(&self.three_d, &cpu_mesh), material);
+                let mut object = SceneObject::new(name, model, cpu_mesh);
+                object.source = path.map(|path| ObjectSource::File {
+                    path: path.to_owned(),
+                    mesh: objects.len(),
+                });
+                objects.push(object);
+            }
+        }
+        objects
+    }
+
+    /// Replaces the models with one of the built-in shapes and frames the camera to fit it.
+    pub fn set_primitive(&mut self, primitive: Primitive) {
+        self.drop_objects();
+        let object = self.primitive_object(primitive);
+        self.set_objects(vec![object]);
+        if primitive == Primitive::Triangle {
+            self.vertex_colors = Some(Self::DEFAULT_VERTEX_COLORS);
+        }
+        self.primitive = Some(primitive);
+    }
+
+    /// A scene object of one of the built-in shapes, the triangle with the default vertex colors.
+    fn primitive_object(&self, primitive: Primitive) -> SceneObject {
+        use three_d::*;
+
+        let (cpu_mesh, material) = match primitive.cpu_mesh() {
+            Some(cpu_mesh) => {
+                let material = PhysicalMaterial::new_opaque(
+                    &self.three_d,
+                    &CpuMaterial {
+                        albedo: Srgba::new(200, 200, 200, 255),
+                        roughness: 0.5,
+                        ..Default::default()
+                    },
+                );
+                (cpu_mesh, material)
+            }
+            None => (
+                Self::triangle_mesh(Some(Self::DEFAULT_VERTEX_COLORS), 0.0),
+                PhysicalMaterial::default(),
+            ),
+        };
+        let model = Gm::new(Mesh::new(&self.three_d, &cpu_mesh), material);
+        let mut object = SceneObject::new(primitive.name(), model, cpu_mesh);
+        object.source = Some(ObjectSource::Primitive(primitive));
+        object
+    }
+
+    /// The built-in shape shown, `None` when a model was loaded instead.
+    pub fn primitive(&self) -> Option<Primitive> {
+        self.primitive
+    }
+
+    /// Drops the models before the ones replacing them are uploaded, so they don't take up GPU memory side by side.
+    /// three-d frees the buffers and textures of a model when its `Gm` is dropped. Must be followed by
+    /// [`Self::set_objects`], the objects derived from the models still refer to them until then.
+    fn drop_objects(&mut self) {
+        self.objects.clear();
+        self.raw_triangle = None;
+    }
+
+    /// Replaces the models and everything derived from their meshes, and frames the camera to fit them.
+    fn set_objects(&mut self, objects: Vec<SceneObject>) {
+        self.objects = objects;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.missing_objects.clear();
+            self.import_stats = None;
+        }
+        self.finish_objects(0..self.objects.len());
+        self.rebuild_lines();
+        self.forget_triangle();
+        self.pick_ray = None;
+        self.focus_ray = None;
+        self.picked = None;
+        self.selected = None;
+        self.hover_ray = None;
+        self.hovered = None;
+        self.translate_gizmo.release();
+        self.measurement.clear();
+        self.measurement_line = Self::measurement_line_model(&self.three_d);
+        self.frame_to_fit(self.model_aabb);
+    }
+
+    /// Stops treating the first object as the built-in triangle, once it was replaced or removed.
+    fn forget_triangle(&mut self) {
+        self.vertex_colors = None;
+        self.raw_triangle = None;
+        self.textured = false;
+        self.triangle_dirty = false;
+        self.morph = 0.0;
+    }
+
+    /// The meshes of the visible objects, placed by their transforms.
+    fn placed_meshes(&self) -> Vec<three_d::CpuMesh> {
+        self.objects
+            .iter()
+            .filter(|object| object.visible)
+            .map(SceneObject::placed_mesh)
+            .collect()
+    }
+
+    /// Rebuilds the lines drawn from the visible objects and their bounds.
+    fn rebuild_lines(&mut self) {
+        let cpu_meshes = self.placed_meshes();
+        let aabb = Self::meshes_aabb(&cpu_meshes);
+        self.wireframe = Self::wireframe_model(&self.three_d, &cpu_meshes);
+        self.normals = Self::normals_model(&self.three_d, &cpu_meshes, self.normal_length);
+        self.points = Self::points_model(&self.three_d, &cpu_meshes, self.point_size);
+        self.bounding_box = Self::bounding_box_model(&self.three_d, aabb);
+        self.model_aabb = aabb;
+        self.objects_dirty = false;
+    }
+
+    /// The models of the scene, in the order they were added.
+    pub fn objects(&self) -> &[SceneObject] {
+        &self.objects
+    }
+
+    /// The visible models, each with its simplified mesh where that's shown and the hovered one with `tinted`.
+    fn visible_models<'a>(
+        &'a self,
+        tinted: Option<&'a three_d::PhysicalMaterial>,
+    ) -> impl Iterator<Item = three_d::Gm<&'a three_d::Mesh, &'a three_d::PhysicalMaterial>> {
+        self.objects
+            .iter()
+            .enumerate()
+            .filter(|(_, object)| object.is_drawn())
+            .map(move |(index, object)| {
+                let material = tinted
+                    .filter(|_| self.hovered == Some(index))
+                    .unwrap_or(&object.model.material);
+                three_d::Gm::new(object.shown_geometry(), material)
+            })
+    }
+
+    /// The material of the hovered model, brightened by adding to its emissive color.
+    fn hovered_material(&self) -> Option<three_d::PhysicalMaterial> {
+        const TINT: u8 = 64;
+
+        let object = &self.objects[self.hovered.filter(|_| self.highlight_hovered)?];
+        let mut material = object.model.material.clone();
+        let e = material.emissive;
+        material.emissive = three_d::Srgba::new(
+            e.r.saturating_add(TINT),
+            e.g.saturating_add(TINT),
+            e.b.saturating_add(TINT),
+            e.a,
+        );
+        Some(material)
+    }
+
+    /// Index of the model under the cursor, `None` if the cursor is elsewhere or misses the models.
+    pub fn hovered(&self) -> Option<usize> {
+        self.hovered
+    }
+
+    pub fn set_object_visible(&mut self, index: usize, visible: bool) {
+        self.objects[index].visible = visible;
+        self.objects_dirty = true;
+    }
+
+    pub fn set_object_transform(&mut self, index: usize, transform: Transform) {
+        self.objects[index].transform = transform;
+        self.objects_dirty = true;
+    }
+
+    /// The material of an object, for editing it in place. Changes show on the next paint without reloading the model.
+    pub fn object_material_mut(&mut self, index: usize) -> &mut three_d::PhysicalMaterial {
+        &mut self.objects[index].model.material
+    }
+
+    /// Removes an object from the scene, keeping the selection and pick on the objects after it.
+    pub fn remove_object(&mut self, index: usize) {
+        self.objects.remove(index);
+        if index == 0 && self.vertex_colors.is_some() {
+            self.forget_triangle();
+        }
+        self.primitive = None;
+        let shift = |i: usize| match i.cmp(&index) {
+            std::cmp::Ordering::Less => Some(i),
+            std::cmp::Ordering::Equal => None,
+            std::cmp::Ordering::Greater => Some(i - 1),
+        };
+        self.selected = self.selected.and_then(shift);
+        self.hovered = self.hovered.and_then(shift);
+        self.picked = self
+            .picked
+            .and_then(|pick| shift(pick.model).map(|model| Pick { model, ..pick }));
+        self.translate_gizmo.release();
+        self.objects_dirty = true;
+    }
+
+    /// Index of the outlined object, `None` if nothing is selected.
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn select(&mut self, index: Option<usize>) {
+        self.selected = index;
+        self.translate_gizmo.release();
+    }
+
+    /// Index, world space center and handle length of the object the translate handles are shown for.
+    fn translate_handles(&self) -> Option<(usize, three_d::Vec3, f32)> {
+        let index = self.selected.filter(|_| !self.instancing)?;
+        let object = &self.objects[index];
+        if !object.visible {
+            return None;
+        }
+        let origin = three_d::Geometry::aabb(&object.model).center();
+        Some((
+            index,
+            origin,
+            TranslateGizmo::handle_length(&self.camera, origin),
+        ))
+    }
+
+    /// Grabs the translate handle of the selected object under `pos` in the canvas `rect`, returning whether one
+    /// was hit. The object then follows [`Self::drag_handle`] until [`Self::release_handle`].
+    pub fn grab_handle(&mut self, rect: egui::Rect, pos: egui::Pos2) -> bool {
+        let Some((index, origin, length)) = self.translate_handles() else {
+            return false;
+        };
+        let Some(ray) = Ray::from_canvas(&self.camera, rect, pos) else {
+            return false;
+        };
+        let translation = self.objects[index].transform.translation;
+        self.translate_gizmo.grab(&ray, origin, length, translation)
+    }
+
+    /// Moves the selected object along the grabbed handle, to where the axis is closest to `pos`.
+    pub fn drag_handle(&mut self, rect: egui::Rect, pos: egui::Pos2) {
+        use three_d::*;
+
+        let Some(index) = self.selected else {
+            return;
+        };
+        let Some(ray) = Ray::from_canvas(&self.camera, rect, pos) else {
+            return;
+        };
+        let Some((offset, start)) = self.translate_gizmo.drag(&ray) else {
+            return;
+        };
+        // The handles move the object in world space, the translation applies before the scene transformation.
+        let Some(inverse) = self.transformation.invert() else {
+            return;
+        };
+        let mut transform = self.objects[index].transform;
+        transform.translation = start + (inverse * offset.extend(0.0)).truncate();
+        self.set_object_transform(index, transform);
+    }
+
+    pub fn release_handle(&mut self) {
+        self.translate_gizmo.release();
+    }
+
+    pub fn is_dragging_handle(&self) -> bool {
+        self.translate_gizmo.is_dragging()
+    }
+
+    /// Moves the selected object by `steps` of the nudge step along the right, up and viewing directions of the
+    /// camera.
+    pub fn nudge_selected(&mut self, steps: three_d::Vec3) {
+        use three_d::*;
+
+        let Some(index) = self.selected else {
+            return;
+        };
+        let camera = &self.camera;
+        let right = camera.right_direction().normalize();
+        let forward = camera.view_direction();
+        let up = right.cross(forward).normalize();
+        let offset = (right * steps.x + up * steps.y + forward * steps.z) * self.nudge_step;
+        // Like the handles, the offset is in world space and the translation applies before the scene transformation.
+        let Some(inverse) = self.transformation.invert() else {
+            return;
+        };
+        let mut transform = self.objects[index].transform;
+        transform.translation += (inverse * offset.extend(0.0)).truncate();
+        self.set_object_transform(index, transform);
+    }
+
+    fn meshes_aabb(cpu_meshes: &[three_d::CpuMesh]) -> three_d::AxisAlignedBoundingBox {
+        let mut aabb = three_d::AxisAlignedBoundingBox::EMPTY;
+        for cpu_mesh in cpu_meshes {
+            aabb.expand_with_aabb(&cpu_mesh.compute_aabb());
+        }
+        aabb
+    }
+
+    /// Builds the edges of all meshes as lines.
+    fn wireframe_model(three_d: &three_d::Context, cpu_meshes: &[three_d::CpuMesh]) -> LineModel {
+        use three_d::*;
+
+        let segments: Vec<_> = cpu_meshes.iter().flat_map(lines::edge_segments).collect();
+        lines::line_model(
+            three_d,
+            &segments,
+            Srgba::new(255, 200, 0, 255),
+            Self::DEFAULT_LINE_WIDTH,
+        )
+    }
+
+    /// Builds the normal lines of all meshes.
+    fn normals_model(
+        three_d: &three_d::Context,
+        cpu_meshes: &[three_d::CpuMesh],
+        length: f32,
+    ) -> LineModel {
+        let (segments, colors) = Self::normal_segments(cpu_meshes, length);
+        lines::colored_line_model(three_d, &segments, &colors, Self::DEFAULT_LINE_WIDTH)
+    }
+
+    /// `length` is a fraction of the diagonal of the mesh bounds.
+    fn normal_segments(
+        cpu_meshes: &[three_d::CpuMesh],
+        length: f32,
+    ) -> (Vec<[three_d::Vec3; 2]>, Vec<three_d::Srgba>) {
+        use three_d::*;
+
+        let length = length * Self::meshes_aabb(cpu_meshes).size().magnitude();
+        let (mut segments, mut colors) = (Vec::new(), Vec::new());
+        for cpu_mesh in cpu_meshes {
+            let (mesh_segments, mesh_colors) = lines::normal_segments(cpu_mesh, length);
+            segments.extend(mesh_segments);
+            colors.extend(mesh_colors);
+        }
+        (segments, colors)
+    }
+
+    pub fn normal_length(&self) -> f32 {
+        self.normal_length
+    }
+
+    /// Changes the length of the normal lines, as a fraction of the diagonal of the model bounds.
+    pub fn set_normal_length(&mut self, length: f32) {
+        if length != self.normal_length {
+            self.normal_length = length;
+            let (segments, colors) = Self::normal_segments(&self.placed_meshes(), length);
+            lines::set_colored_segments(&mut self.normals, &segments, &colors);
+        }
+    }
+
+    /// A sphere on each vertex of the meshes.
+    fn points_model(
+        three_d: &three_d::Context,
+        cpu_meshes: &[three_d::CpuMesh],
+        size: f32,
+    ) -> three_d::Gm<three_d::InstancedMesh, three_d::ColorMaterial> {
+        use three_d::*;
+
+        // Few segments, point clouds can have millions of points.
+        Gm::new(
+            InstancedMesh::new(
+                three_d,
+                &Self::point_instances(cpu_meshes, size),
+                &CpuMesh::sphere(4),
+            ),
+            ColorMaterial::default(),
+        )
+    }
+
+    /// `size` is the radius of the points as a fraction of the diagonal of the mesh bounds.
+    fn point_instances(cpu_meshes: &[three_d::CpuMesh], size: f32) -> three_d::Instances {
+        use three_d::*;
+
+        let radius = size * Self::meshes_aabb(cpu_meshes).size().magnitude();
+        Instances {
+            transformations: cpu_meshes
+                .iter()
+                .flat_map(|cpu_mesh| cpu_mesh.positions.to_f32())
+                .map(|position| Mat4::from_translation(position) * Mat4::from_scale(radius))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn point_size(&self) -> f32 {
+        self.point_size
+    }
+
+    /// Changes the radius of the points, as a fraction of the diagonal of the model bounds.
+    pub fn set_point_size(&mut self, size: f32) {
+        if size != self.point_size {
+            self.point_size = size;
+            self.points
+                .geometry
+                .set_instances(&Self::point_instances(&self.placed_meshes(), size));
+        }
+    }
+
+    /// The line between the measured points, set once both are picked.
+    fn measurement_line_model(three_d: &three_d::Context) -> LineModel {
+        use three_d::*;
+
+        lines::line_model(
+            three_d,
+            &[],
+            Srgba::new(255, 60, 200, 255),
+            Self::DEFAULT_LINE_WIDTH,
+        )
+    }
+
+    /// Lines drawn over the scene, with their segments set every frame.
+    fn frustum_model(three_d: &three_d::Context, color: three_d::Srgba) -> LineModel {
+        let mut model = lines::line_model(three_d, &[], color, Self::DEFAULT_LINE_WIDTH);
+        model.material.depth_test = three_d::DepthTest::Always;
+        model
+    }
+
+    /// The edges of the box, moved to the bounds of the transformed models before each render.
+    fn bounding_box_model(
+        three_d: &three_d::Context,
+        aabb: three_d::AxisAlignedBoundingBox,
+    ) -> LineModel {
+        use three_d::*;
+
+        lines::line_model(
+            three_d,
+            &lines::box_segments(&aabb),
+            Srgba::new(0, 220, 255, 255),
+            Self::DEFAULT_LINE_WIDTH,
+        )
+    }
+
+    /// The models must have been created with the given `three_d` context, which is then used for all rendering.
+    fn with_objects(three_d: three_d::Context, objects: Vec<SceneObject>) -> Self {
+        use three_d::*;
+
+        let cpu_meshes = &objects
+            .iter()
+            .map(SceneObject::placed_mesh)
+            .collect::<Vec<_>>();
+        let wireframe = Self::wireframe_model(&three_d, cpu_meshes);
+        let normal_length = 0.05;
+        let normals = Self::normals_model(&three_d, cpu_meshes, normal_length);
+        let point_size = 0.005;
+        let points = Self::points_model(&three_d, cpu_meshes, point_size);
+        let model_aabb = Self::meshes_aabb(cpu_meshes);
+        let bounding_box = Self::bounding_box_model(&three_d, model_aabb);
+        let srgb_support = SrgbSupport::query(&three_d);
+        log::info!("{srgb_support}");
+        let texture_settings = TextureSettings {
+            // Keep the squares of the checkerboard sharp when magnified.
+            mag_filter: Interpolation::Nearest,
+            ..Default::default()
+        };
+        let mut triangle_cpu_texture = Self::checkerboard();
+        texture_settings.apply(&mut triangle_cpu_texture);
+        let triangle_texture = Texture2DRef::from_cpu_texture(&three_d, &triangle_cpu_texture);
+        let texture_plane = Self::texture_plane_model(&three_d, texture_settings);
+        let particles = ParticleFountain::new(&three_d);
+
+        let camera = Self::default_camera();
+        let orbit = crate::orbit::OrbitControl::new(&camera);
+        let light_direction = vec3(0.0, -0.5, -1.0);
+        let (grid_extent, grid_spacing) = (10.0, 1.0);
+        let instance_count = 1000;
+        // SAFETY: Querying a parameter has no side effects.
+        let max_msaa_samples =
+            unsafe { three_d.get_parameter_i32(context::MAX_SAMPLES) }.max(1) as u32;
+        let instanced_model = Gm::new(
+            InstancedMesh::new(
+                &three_d,
+                &instancing::random_instances(instance_count),
+                &Self::triangle_mesh(Some(Self::DEFAULT_VERTEX_COLORS), 0.0),
+            ),
+            PhysicalMaterial::default(),
+        );
+
+        let mut occluder = Gm::new(
+            Mesh::new(&three_d, &CpuMesh::sphere(32)),
+            PhysicalMaterial::new_opaque(
+                &three_d,
+                &CpuMaterial {
+                    albedo: Srgba::new(180, 180, 180, 255),
+                    roughness: 0.4,
+                    ..Default::default()
+                },
+            ),
+        );
+        occluder.set_transformation(
+            Mat4::from_translation(vec3(0.25, 0.0, -0.3)) * Mat4::from_scale(0.35),
+        );
+
+        let pane = |color: Srgba, translation: Vec3| {
+            let mut pane = Gm::new(
+                Mesh::new(&three_d, &CpuMesh::square()),
+                PhysicalMaterial::new_transparent(
+                    &three_d,
+                    &CpuMaterial {
+                        albedo: color,
+                        roughness: 0.6,
+                        ..Default::default()
+                    },
+                ),
+            );
+            pane.set_transformation(Mat4::from_translation(translation) * Mat4::from_scale(0.35));
+            pane
+        };
+        let panes = [
+            pane(Srgba::new(60, 140, 255, 255), vec3(0.2, 0.15, 0.25)),
+            pane(Srgba::new(255, 140, 40, 255), vec3(-0.2, -0.15, -0.25)),
+        ];
+
+        let ground = Gm::new(
+            Mesh::new(&three_d, &CpuMesh::square()),
+            PhysicalMaterial::new_opaque(
+                &three_d,
+                &CpuMaterial {
+                    albedo: Srgba::new(150, 150, 150, 255),
+                    roughness: 0.9,
+                    ..Default::default()
+                },
+            ),
+        );
+
+        Self {
+            show_occluder: false,
+            occluder,
+            show_panes: false,
+            pane_alpha: 0.5,
+            panes,
+            show_ground: false,
+            ground,
+            shadows: false,
+            shadow_map_size: 1024,
+            instancing: false,
+            instance_count,
+            instanced_model,
+            ambient: AmbientLight::new(&three_d, 0.3, Srgba::WHITE),
+            directional: DirectionalLight::new(&three_d, 2.0, Srgba::WHITE, &light_direction),
+            light_direction,
+            lights: Vec::new(),
+            light_markers: lights::marker_model(&three_d),
+            deferred: false,
+            msaa_samples: 4.min(max_msaa_samples),
+            max_msaa_samples,
+            targets: ViewTargets::default(),
+            split_view: false,
+            side_targets: ViewTargets::default(),
+            pixel_targets: Default::default(),
+            show_frustums: false,
+            main_frustum: Self::frustum_model(&three_d, Srgba::new(255, 220, 0, 255)),
+            side_frustum: Self::frustum_model(&three_d, Srgba::new(255, 0, 220, 255)),
+            post_processing: false,
+            depth_of_field: DepthOfField::default(),
+            ambient_occlusion: AmbientOcclusion::default(),
+            tone_mapping: ToneMapping::default(),
+            exposure: 1.0,
+            clear_color: Srgba::new(30, 30, 30, 255),
+            show_skybox: false,
+            sky: None,
+            builtin_sky: Some(BuiltinSky::default()),
+            show_grid: true,
+            grid_extent,
+            grid_spacing,
+            grid: Self::grid_model(&three_d, grid_extent, grid_spacing),
+            line_width: Self::DEFAULT_LINE_WIDTH,
+            gizmo_camera: Camera::new_orthographic(
+                Viewport::new_at_origo(1, 1),
+                vec3(0.0, 0.0, Self::GIZMO_DISTANCE),
+                vec3(0.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                2.5,
+                0.1,
+                10.0,
+            ),
+            axes: Axes::new(&three_d, 0.05, 1.0),
+            screenshot_request: None,
+            screenshot_result: None,
+            turntable: None,
+            turntable_result: None,
+            pick_ray: None,
+            focus_ray: None,
+            picked: None,
+            selected: None,
+            frustum_culling: true,
+            show_culled: false,
+            nudge_step: 0.01,
+            highlight_hovered: true,
+            hover_pos: None,
+            hover_ray: None,
+            hovered: None,
+            translate_gizmo: TranslateGizmo::new(&three_d),
+            outline_color: Srgba::new(255, 160, 0, 255),
+            outline_thickness: 3.0,
+            measuring: false,
+            measurement: Vec::new(),
+            measurement_line: Self::measurement_line_model(&three_d),
+            show_cursor_ray: false,
+            cursor_ray: None,
+            cursor_ray_line: lines::line_model(
+                &three_d,
+                &[],
+                Srgba::new(255, 255, 0, 255),
+                Self::DEFAULT_LINE_WIDTH,
+            ),
+            cursor_hit: None,
+            cursor_hit_marker: Gm::new(
+                Mesh::new(&three_d, &CpuMesh::sphere(8)),
+                ColorMaterial {
+                    color: Srgba::new(255, 255, 0, 255),
+                    ..Default::default()
+                },
+            ),
+            debug_draw: DebugDraw::default(),
+            debug_draw_models: DebugDrawModels::new(&three_d, Self::DEFAULT_LINE_WIDTH),
+            #[cfg(not(target_arch = "wasm32"))]
+            cursor: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            probe_depth: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            cursor_depth: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            depth_probe: Default::default(),
+            transformation: Mat4::identity(),
+            three_d,
+            field_of_view: Self::FIELD_OF_VIEW_DEGREES,
+            home_camera: camera.clone(),
+            camera,
+            projection_mode: ProjectionMode::Perspective,
+            orbit,
+            navigation: Navigation::Orbit,
+            fly: crate::fly::FlyControl::new(1.0),
+            animator: None,
+            objects,
+            objects_dirty: false,
+            primitive: Some(Primitive::Triangle),
+            shading: Shading::Smooth,
+            #[cfg(not(target_arch = "wasm32"))]
+            missing_objects: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            import_stats: None,
+            level_of_detail: LevelOfDetail::default(),
+            draw_calls: DrawCalls::default(),
+            vertex_colors: None,
+            textured: false,
+            calibration: false,
+            compare_interpolation: false,
+            auto_clip_planes: false,
+            auto_near_factor: 0.5,
+            raw_triangle: None,
+            framebuffer_srgb: FramebufferSrgb::Unchanged,
+            srgb_support,
+            debug_view: DebugView::default(),
+            cull: Cull::None,
+            triangle_texture,
+            triangle_cpu_texture,
+            texture_settings,
+            show_texture_plane: false,
+            texture_plane,
+            show_particles: false,
+            pulse: false,
+            time: 0.0,
+            particles,
+            triangle_dirty: false,
+            morph: 0.0,
+            show_wireframe: false,
+            wireframe,
+            show_points: false,
+            point_color: Srgba::WHITE,
+            point_size,
+            points,
+            show_normals: false,
+            normal_length,
+            normals,
+            show_bounding_box: false,
+            bounding_box,
+            model_aabb,
+            world_aabb: model_aabb,
+        }
+    }
+
+    fn default_camera() -> three_d::Camera {
+        use three_d::*;
+
+        Camera::new_perspective(
+            Viewport {
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 0,
+            },
+            vec3(0.0, 0.0, 2.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            degrees(Self::FIELD_OF_VIEW_DEGREES),
+            0.1,
+            10.0,
+        )
+    }
+
+    pub fn vertex_colors(&self) -> Option<[three_d::Srgba; 3]> {
+        self.vertex_colors
+    }
+
+    /// Changes the corner colors of the built-in triangle, does nothing for a loaded model.
+    ///
+    /// The colors are sRGB encoded, like egui's. three-d converts them to linear before uploading them, so they are
+    /// interpolated across the triangle in linear light, see [`Self::compare_interpolation`].
+    pub fn set_vertex_colors(&mut self, colors: [three_d::Srgba; 3]) {
+        if self.vertex_colors.is_some() && self.vertex_colors != Some(colors) {
+            self.vertex_colors = Some(colors);
+            self.triangle_dirty = true;
+        }
+    }
+
+    pub fn morph(&self) -> f32 {
+        self.morph
+    }
+
+    /// Morphs the built-in triangle `morph` of the way into a flattened triangle, from 0 to 1.
+    /// The mesh is rebuilt on the next paint, does nothing for a loaded model.
+    pub fn set_morph(&mut self, morph: f32) {
+        let morph = morph.clamp(0.0, 1.0);
+        if self.vertex_colors.is_some() && self.morph != morph {
+            self.morph = morph;
+            self.triangle_dirty = true;
+        }
+    }
+
+    pub fn shading(&self) -> Shading {
+        self.shading
+    }
+
+    /// Switches the models between smooth and flat shading, uploading their meshes again.
+    pub fn set_shading(&mut self, shading: Shading) {
+        if self.shading != shading {
+            self.shading = shading;
+            self.upload_meshes();
+            // The instanced copies of the triangle are rebuilt with it.
+            self.triangle_dirty |= self.vertex_colors.is_some();
+        }
+    }
+
+    /// The mesh as uploaded for the shading, with vertices of its own for each triangle when flat.
+    fn shaded_mesh(&self, cpu_mesh: &three_d::CpuMesh) -> three_d::CpuMesh {
+        match self.shading {
+            Shading::Smooth => cpu_mesh.clone(),
+            Shading::Flat => primitives::flat_shaded(cpu_mesh),
+        }
+    }
+
+    /// Uploads the meshes of all objects again, after the shading changed.
+    fn upload_meshes(&mut self) {
+        for index in 0..self.objects.len() {
+            self.upload_mesh(index);
+        }
+        self.simplify_meshes();
+    }
+
+    /// Uploads the meshes of freshly added objects for the shading and simplifies them, they come uploaded smooth.
+    fn finish_objects(&mut self, indices: std::ops::Range<usize>) {
+        for index in indices {
+            if self.shading != Shading::Smooth {
+                self.upload_mesh(index);
+            }
+            self.simplify_object(index);
+        }
+    }
+
+    fn upload_mesh(&mut self, index: usize) {
+        let mesh = self.shaded_mesh(&self.objects[index].cpu_mesh);
+        self.objects[index].model.geometry = three_d::Mesh::new(&self.three_d, &mesh);
+    }
+
+    pub fn level_of_detail(&self) -> LevelOfDetail {
+        self.level_of_detail
+    }
+
+    /// Changes the level of detail, simplifying the meshes again if it's enabled or the quality changed.
+    pub fn set_level_of_detail(&mut self, level_of_detail: LevelOfDetail) {
+        let previous = std::mem::replace(&mut self.level_of_detail, level_of_detail);
+        if previous.enabled != level_of_detail.enabled
+            || previous.quality != level_of_detail.quality
+        {
+            self.simplify_meshes();
+        }
+    }
+
+    /// Triangles of the visible simplified models and of their full meshes, `None` while nothing is simplified.
+    pub fn simplified_triangles(&self) -> Option<(usize, usize)> {
+        self.objects
+            .iter()
+            .filter(|object| object.visible)
+            .filter_map(|object| {
+                let simplified = object.simplified.as_ref()?;
+                Some((simplified.triangles, object.cpu_mesh.triangle_count()))
+            })
+            .reduce(|a, b| (a.0 + b.0, a.1 + b.1))
+    }
+
+    /// Builds the simplified meshes of all objects when the level of detail is enabled, or drops them.
+    fn simplify_meshes(&mut self) {
+        for index in 0..self.objects.len() {
+            self.simplify_object(index);
+        }
+    }
+
+    fn simplify_object(&mut self, index: usize) {
+        let simplified = self
+            .level_of_detail
+            .enabled
+            .then(|| {
+                simplify::simplify(&self.objects[index].cpu_mesh, self.level_of_detail.quality)
+            })
+            .flatten()
+            .map(|cpu_mesh| SimplifiedMesh {
+                triangles: cpu_mesh.triangle_count(),
+                mesh: three_d::Mesh::new(&self.three_d, &self.shaded_mesh(&cpu_mesh)),
+                shown: false,
+            });
+        self.objects[index].simplified = simplified;
+    }
+
+    pub fn textured(&self) -> bool {
+        self.textured
+    }
+
+    /// Switches the built-in triangle between the texture and the vertex colors, does nothing for a loaded model.
+    pub fn set_textured(&mut self, textured: bool) {
+        if self.vertex_colors.is_some() && self.textured != textured {
+            self.textured = textured;
+            self.triangle_dirty = true;
+        }
+    }
+
+    /// Replaces the checkerboard on the built-in triangle with an image file, and shows it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_texture(&mut self, path: &std::path::Path) -> three_d_asset::Result<()> {
+        let mut cpu_texture: three_d::CpuTexture = three_d_asset::io::load_and_deserialize(path)?;
+        self.texture_settings.apply(&mut cpu_texture);
+        self.triangle_texture =
+            three_d::Texture2DRef::from_cpu_texture(&self.three_d, &cpu_texture);
+        self.triangle_cpu_texture = cpu_texture;
+        if self.vertex_colors.is_some() {
+            self.textured = true;
+            // Rebuild even when already textured, to swap the texture in the materials.
+            self.triangle_dirty = true;
+        }
+        Ok(())
+    }
+
+    /// A gray checkerboard of 8 by 8 squares, so texturing works without any image files.
+    fn checkerboard() -> three_d::CpuTexture {
+        use three_d::*;
+
+        let (size, square) = (64, 8);
+        let data = (0..size * size)
+            .map(|i| {
+                let (x, y) = (i % size, i / size);
+                if (x / square + y / square) % 2 == 0 {
+                    [230, 230, 230, 255]
+                } else {
+                    [60, 60, 60, 255]
+                }
+            })
+            .collect();
+        CpuTexture {
+            name: "checkerboard".to_owned(),
+            data: TextureData::RgbaU8(data),
+            width: size,
+            height: size,
+            ..Default::default()
+        }
+    }
+
+    /// A square of 20 by 20 units lying flat below the triangle, with the checkerboard repeated 10 times along
+    /// each side.
+    fn texture_plane_model(
+        three_d: &three_d::Context,
+        settings: TextureSettings,
+    ) -> three_d::Gm<three_d::Mesh, three_d::ColorMaterial> {
+        use three_d::*;
+
+        let mut cpu_mesh = CpuMesh::square();
+        if let Some(uvs) = &mut cpu_mesh.uvs {
+            for uv in uvs {
+                *uv *= 10.0;
+            }
+        }
+        let mut cpu_texture = Self::checkerboard();
+        settings.apply(&mut cpu_texture);
+        let mut plane = Gm::new(
+            Mesh::new(three_d, &cpu_mesh),
+            ColorMaterial {
+                texture: Some(Texture2DRef::from_cpu_texture(three_d, &cpu_texture)),
+                ..Default::default()
+            },
+        );
+        plane.set_transformation(
+            Mat4::from_translation(vec3(0.0, -0.5, 0.0))
+                * Mat4::from_scale(10.0)
+                * Mat4::from_angle_x(degrees(-90.0)),
+        );
+        plane
+    }
+
+    pub fn texture_settings(&self) -> TextureSettings {
+        self.texture_settings
+    }
+
+    /// Uploads the triangle texture and the texture of the plane again with the new settings. Models keep the
+    /// settings they were loaded with.
+    pub fn set_texture_settings(&mut self, settings: TextureSettings) {
+        if settings == self.texture_settings {
+            return;
+        }
+        self.texture_settings = settings;
+        settings.apply(&mut self.triangle_cpu_texture);
+        self.triangle_texture =
+            three_d::Texture2DRef::from_cpu_texture(&self.three_d, &self.triangle_cpu_texture);
+        self.texture_plane = Self::texture_plane_model(&self.three_d, settings);
+        if self.textured {
+            // Swap the texture in the materials.
+            self.triangle_dirty = true;
+        }
+    }
+
+    /// Uploads the triangle with the new vertex colors or texture, replacing the single model and the instanced mesh.
+    fn rebuild_triangle(&mut self, colors: [three_d::Srgba; 3]) {
+        use three_d::*;
+
+        let cpu_mesh = Self::triangle_mesh((!self.textured).then_some(colors), self.morph);
+        let shaded_mesh = self.shaded_mesh(&cpu_mesh);
+        let albedo_texture = self.textured.then(|| self.triangle_texture.clone());
+        let triangle = &mut self.objects[0];
+        triangle.model.material.albedo_texture = albedo_texture.clone();
+        self.instanced_model.material.albedo_texture = albedo_texture;
+        triangle.model.geometry = Mesh::new(&self.three_d, &shaded_mesh);
+        self.instanced_model.geometry = InstancedMesh::new(
+            &self.three_d,
+            &instancing::random_instances(self.instance_count),
+            &shaded_mesh,
+        );
+        triangle.cpu_mesh = cpu_mesh;
+        self.raw_triangle = None;
+        self.simplify_object(0);
+        // The lines drawn from the mesh follow the morph.
+        self.rebuild_lines();
+    }
+
+    /// Replaces the sky with an equirectangular panorama loaded from an image file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_skybox(&mut self, path: &std::path::Path) -> three_d_asset::Result<()> {
+        let cpu_texture: three_d::CpuTexture = three_d_asset::io::load_and_deserialize(path)?;
+        // Dropping the previous sky deletes its textures.
+        self.sky = Some(Sky::new(&self.three_d, &cpu_texture));
+        self.builtin_sky = None;
+        self.show_skybox = true;
+        Ok(())
+    }
+
+    pub fn builtin_sky(&self) -> Option<BuiltinSky> {
+        self.builtin_sky
+    }
+
+    /// Replaces the sky with a built-in panorama, and shows it.
+    pub fn set_builtin_sky(&mut self, builtin: BuiltinSky) {
+        if self.builtin_sky != Some(builtin) {
+            self.builtin_sky = Some(builtin);
+            // Created on the next paint, dropping the previous sky deletes its textures.
+            self.sky = None;
+        }
+        self.show_skybox = true;
+    }
+
+    pub fn add_point_light(&mut self) {
+        self.lights.push(SceneLight::point(&self.three_d));
+    }
+
+    pub fn add_spot_light(&mut self) {
+        self.lights.push(SceneLight::spot(&self.three_d));
+    }
+
+    /// Adds `count` colored point lights in a ring around the models, for comparing the renderers with many lights.
+    pub fn add_light_ring(&mut self, count: usize) {
+        use three_d::*;
+
+        let (center, radius) = if self.world_aabb.is_empty() {
+            (vec3(0.0, 0.0, 0.0), 1.0)
+        } else {
+            (self.world_aabb.center(), self.world_aabb.size().magnitude())
+        };
+        self.lights
+            .extend(SceneLight::ring(&self.three_d, count, center, radius));
+    }
+
+    pub fn rebuild_instances(&mut self) {
+        self.instanced_model
+            .geometry
+            .set_instances(&instancing::random_instances(self.instance_count));
+    }
+
+    fn grid_model(three_d: &three_d::Context, extent: f32, spacing: f32) -> LineModel {
+        lines::line_model(
+            three_d,
+            &lines::grid_segments(extent, spacing),
+            three_d::Srgba::new(100, 100, 100, 255),
+            Self::DEFAULT_LINE_WIDTH,
+        )
+    }
+
+    pub fn rebuild_grid(&mut self) {
+        self.grid = Self::grid_model(&self.three_d, self.grid_extent, self.grid_spacing);
+    }
+
+    /// Looks at the center of the box from the front, close enough for the whole box to be visible,
+    /// and makes that the view to reset to.
+    fn frame_to_fit(&mut self, aabb: three_d::AxisAlignedBoundingBox) {
+        use three_d::*;
+
+        if aabb.is_empty() {
+            return;
+        }
+        let center = aabb.center();
+        let radius = (aabb.size().magnitude() * 0.5).max(0.001);
+        // The bounding sphere of the box touches the sides of the view frustum at this distance.
+        let half_fov = degrees(self.field_of_view) / 2.0;
+        let distance = radius / half_fov.sin();
+
+        let camera = Camera::new_perspective(
+            self.camera.viewport(),
+            center + vec3(0.0, 0.0, distance),
+            center,
+            vec3(0.0, 1.0, 0.0),
+            degrees(self.field_of_view),
+            distance * 0.05,
+            (distance + radius) * 4.0,
+        );
+        // Takes the zoom range from the new clipping planes, the animation keeps it.
+        self.orbit = crate::orbit::OrbitControl::new(&camera);
+        self.home_camera = camera.clone();
+        let start = std::mem::replace(&mut self.camera, camera);
+        self.set_projection_mode(self.projection_mode);
+
+        let end = std::mem::replace(&mut self.camera, start);
+        self.animate_to(end);
+    }
+
+    /// Starts turning the camera towards `target`, keeping its position.
+    pub fn look_at(&mut self, target: three_d::Vec3) {
+        let mut end = self.camera.clone();
+        let (position, up) = (*end.position(), *end.up());
+        if position != target {
+            end.set_view(position, target, up);
+            self.animate_to(end);
+        }
+    }
+
+    /// Starts moving the camera and its target together until the target is at `point`.
+    fn focus_on(&mut self, point: three_d::Vec3) {
+        let mut end = self.camera.clone();
+        let offset = end.position() - end.target();
+        let up = *end.up();
+        end.set_view(point + offset, point, up);
+        self.animate_to(end);
+    }
+
+    /// Starts moving the camera along its view direction until the selected object, or all models if none is selected,
+    /// fill the view. Widens the clipping planes and zoom range if the box wouldn't fit in them.
+    pub fn frame_selection(&mut self) {
+        use three_d::*;
+
+        let aabb = match self.selected.filter(|_| !self.instancing) {
+            Some(index) => self.objects[index].model.aabb(),
+            None => self.world_aabb,
+        };
+        if aabb.is_empty() {
+            return;
+        }
+        let center = aabb.center();
+        let radius = (aabb.size().magnitude() * 0.5).max(0.001);
+        // The bounding sphere of the box touches the sides of the view frustum at this distance, like `frame_to_fit`.
+        let half_fov = degrees(self.field_of_view) / 2.0;
+        let distance = radius / half_fov.sin();
+
+        let start = self.camera.clone();
+        let offset = start.position() - start.target();
+        let direction = if offset.magnitude2() > 0.0 {
+            offset.normalize()
+        } else {
+            vec3(0.0, 0.0, 1.0)
+        };
+        self.camera = Camera::new_perspective(
+            start.viewport(),
+            center + direction * distance,
+            center,
+            *start.up(),
+            degrees(self.field_of_view),
+            start.z_near().min(distance * 0.05),
+            start.z_far().max((distance + radius) * 4.0),
+        );
+        self.set_projection_mode(self.projection_mode);
+        self.orbit = crate::orbit::OrbitControl::new(&self.camera);
+
+        let end = std::mem::replace(&mut self.camera, start);
+        self.animate_to(end);
+    }
+
+    /// Starts moving the camera to look at all models along an axis from far enough for them to fit,
+    /// switching to an orthographic projection once it gets there.
+    pub fn snap_to_view(&mut self, preset: ViewPreset) {
+        use three_d::*;
+
+        let aabb = self.world_aabb;
+        if aabb.is_empty() {
+            return;
+        }
+        let center = aabb.center();
+        let radius = (aabb.size().magnitude() * 0.5).max(0.001);
+        // Far enough for the bounding sphere to fit, like `frame_selection`, which the orthographic projection
+        // keeps as it takes its height from the distance.
+        let half_fov = degrees(self.field_of_view) / 2.0;
+        let distance = radius / half_fov.sin();
+
+        let (direction, up) = preset.direction_and_up();
+        let start = self.camera.clone();
+        self.camera = Camera::new_perspective(
+            start.viewport(),
+            center + direction * distance,
+            center,
+            up,
+            degrees(self.field_of_view),
+            start.z_near().min(distance * 0.05),
+            start.z_far().max((distance + radius) * 4.0),
+        );
+        self.set_projection_mode(ProjectionMode::Orthographic);
+        self.orbit = crate::orbit::OrbitControl::new(&self.camera);
+
+        let end = std::mem::replace(&mut self.camera, start);
+        self.animate_to(end);
+    }
+
+    /// Moves the camera to the view of `end` over the next frames, advanced by [`Self::animate`].
+    fn animate_to(&mut self, end: three_d::Camera) {
+        self.animator = Some(CameraAnimator::new(
+            self.camera.clone(),
+            end,
+            Self::CAMERA_ANIMATION_SECONDS,
+        ));
+    }
+
+    /// Advances the time, the camera animation and the particles by `dt` seconds.
+    pub fn animate(&mut self, dt: f32) {
+        self.time += dt;
+        if self.show_particles {
+            self.particles.update(dt);
+        }
+        if let Some(animator) = &mut self.animator {
+            if !animator.update(&mut self.camera, dt) {
+                self.animator = None;
+            }
+            self.orbit.follow_camera(&self.camera);
+        }
+    }
+
+    /// Whether the camera moves by itself, animated to a view or turning for a turntable export.
+    pub fn is_animating(&self) -> bool {
+        self.animator.is_some() || self.turntable.is_some()
+    }
+
+    /// Seconds the scene was animated for, passed to animated shaders as [`TIME_UNIFORM`](crate::pulse::TIME_UNIFORM).
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Moves the camera back to where it started, keeping the projection and zoom range.
+    pub fn reset_view(&mut self) {
+        let start = self.camera.clone();
+        let mut camera = self.home_camera.clone();
+        camera.set_viewport(self.camera.viewport());
+        if let ProjectionType::Perspective { field_of_view_y } = *camera.projection_type() {
+            self.field_of_view = three_d::Deg::from(field_of_view_y).0;
+        }
+        self.camera = camera;
+        self.set_projection_mode(self.projection_mode);
+
+        let end = std::mem::replace(&mut self.camera, start);
+        self.animate_to(end);
+    }
+
+    /// Axis aligned bounds of the transformed models, as of the last paint.
+    pub fn bounding_box(&self) -> three_d::AxisAlignedBoundingBox {
+        self.world_aabb
+    }
+
+    /// Draw calls of the last frame, with the triangles and estimated buffer memory of the models.
+    pub fn render_stats(&self) -> RenderStats {
+        let mut stats = RenderStats {
+            draw_calls: self.draw_calls.last_frame(),
+            ..Default::default()
+        };
+        stats.add_meshes(self.objects.iter().map(|object| &object.cpu_mesh));
+        for object in self.objects.iter().filter(|object| object.visible) {
+            if object.culled {
+                stats.culled_objects += 1;
+            } else {
+                stats.rendered_objects += 1;
+            }
+        }
+        stats
+    }
+
+    pub fn max_msaa_samples(&self) -> u32 {
+        self.max_msaa_samples
+    }
+
+    /// Vertical field of view of the perspective projection, in degrees.
+    pub fn field_of_view(&self) -> f32 {
+        self.field_of_view
+    }
+
+    pub fn clip_planes(&self) -> (f32, f32) {
+        (self.camera.z_near(), self.camera.z_far())
+    }
+
+    /// Changes the field of view and clipping planes, moving the planes apart if they are too close or the near plane isn't in front of the camera.
+    /// The field of view is kept for when switching back to a perspective projection.
+    pub fn set_lens(&mut self, field_of_view: f32, z_near: f32, z_far: f32) {
+        use three_d::*;
+
+        self.field_of_view = field_of_view.clamp(1.0, 179.0);
+        let z_near = z_near.max(Self::MIN_Z_NEAR);
+        let z_far = z_far.max(z_near * 1.01);
+        match *self.camera.projection_type() {
+            ProjectionType::Perspective { .. } => {
+                self.camera
+                    .set_perspective_projection(degrees(self.field_of_view), z_near, z_far)
+            }
+            ProjectionType::Orthographic { height } => self
+                .camera
+                .set_orthographic_projection(height, z_near, z_far),
+        }
+    }
+
+    /// Places the near plane in front of the transformed models' `aabb` and the far plane behind it, also taking in
+    /// the grid and the ground when they're shown. Keeps the planes when there's nothing to fit them to.
+    fn fit_clip_planes(&mut self, mut aabb: three_d::AxisAlignedBoundingBox) {
+        use three_d::*;
+
+        if aabb.is_empty() {
+            return;
+        }
+        if self.show_ground {
+            // The ground reaches twice the diagonal of the models around their center, see `ground_transformation`.
+            let (center, reach) = (aabb.center(), aabb.size().magnitude().max(0.001) * 2.0);
+            let bottom = aabb.min().y;
+            aabb.expand(&[
+                vec3(center.x - reach, bottom, center.z - reach),
+                vec3(center.x + reach, bottom, center.z + reach),
+            ]);
+        }
+        if self.show_grid {
+            let extent = self.grid_extent;
+            aabb.expand(&[vec3(-extent, 0.0, -extent), vec3(extent, 0.0, extent)]);
+        }
+        let diagonal = aabb.size().magnitude();
+        // `AxisAlignedBoundingBox::distance` squares small distances, so measure to the closest point of the box.
+        let position = *self.camera.position();
+        let (min, max) = (aabb.min(), aabb.max());
+        let closest = vec3(
+            position.x.clamp(min.x, max.x),
+            position.y.clamp(min.y, max.y),
+            position.z.clamp(min.z, max.z),
+        );
+        let distance = position.distance(closest);
+        // From inside the box the distance is 0, the near plane stays a tiny fraction of it in front.
+        let z_near = (distance * self.auto_near_factor.clamp(0.0, 1.0)).max(diagonal * 1e-4);
+        self.set_lens(self.field_of_view, z_near, distance + diagonal);
+    }
+
+    /// Writes the current view and the model `angle` as JSON.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_state(&self, path: &std::path::Path, angle: f32) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(&self.camera_state(angle))?;
+        std::fs::write(path, json)
+    }
+
+    /// Restores a view written by [`Self::save_state`], returning the model angle.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_state(&mut self, path: &std::path::Path) -> std::io::Result<f32> {
+        let state: CameraState = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        Ok(self.set_camera_state(&state))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn camera_state(&self, angle: f32) -> CameraState {
+        let camera = &self.camera;
+        CameraState {
+            position: (*camera.position()).into(),
+            target: (*camera.target()).into(),
+            up: (*camera.up()).into(),
+            projection: self.projection_mode,
+            field_of_view: self.field_of_view,
+            z_near: camera.z_near(),
+            z_far: camera.z_far(),
+            angle,
+        }
+    }
+
+    /// Restores the view, returning the model angle.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn set_camera_state(&mut self, state: &CameraState) -> f32 {
+        self.camera
+            .set_view(state.position.into(), state.target.into(), state.up.into());
+        self.field_of_view = state.field_of_view;
+        // Derives the orthographic height from the view, like switching the projection in the UI.
+        self.set_projection_mode(state.projection);
+        self.set_lens(state.field_of_view, state.z_near, state.z_far);
+        self.orbit.follow_camera(&self.camera);
+        state.angle
+    }
+
+    /// Writes the scene as a project: the files and placement of the models, the lights, the view with the model
+    /// `angle` and the background. Models that weren't read from a file are left out.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_project(&self, path: &std::path::Path, angle: f32) -> std::io::Result<()> {
+        let objects = self
+            .objects
+            .iter()
+            .filter_map(|object| {
+                let source = match object.source.as_ref()? {
+                    ObjectSource::Primitive(primitive) => ProjectSource::Primitive {
+                        shape: primitive.name().to_owned(),
+                    },
+                    ObjectSource::File { path: file, mesh } => ProjectSource::File {
+                        path: project::relative_path(file, path),
+                        mesh: *mesh,
+                    },
+                };
+                let transform = object.transform;
+                Some(ProjectObject {
+                    name: object.name.clone(),
+                    source,
+                    translation: transform.translation.into(),
+                    rotation: transform.rotation.into(),
+                    scale: transform.scale,
+                    visible: object.visible,
+                })
+            })
+            // Saved again as they were, so they're back once their files are.
+            .chain(
+                self.missing_objects
+                    .iter()
+                    .map(|missing| missing.object.clone()),
+            )
+            .collect();
+        let lights = self
+            .lights
+            .iter()
+            .map(|light| {
+                let (kind, position, color, intensity) = match light {
+                    SceneLight::Point(light) => (
+                        ProjectLightKind::Point,
+                        light.position,
+                        light.color,
+                        light.intensity,
+                    ),
+                    SceneLight::Spot(light) => (
+                        ProjectLightKind::Spot,
+                        light.position,
+                        light.color,
+                        light.intensity,
+                    ),
+                };
+                ProjectLight {
+                    kind,
+                    position: position.into(),
+                    color: color.into(),
+                    intensity,
+                }
+            })
+            .collect();
+        let project = ProjectFile {
+            version: ProjectFile::VERSION,
+            objects,
+            lights,
+            camera: self.camera_state(angle),
+            clear_color: self.clear_color.into(),
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&project)?)
+    }
+
+    /// Replaces the scene with a project written by [`Self::save_project`], returning the model angle.
+    /// Models whose files can't be loaded are listed in [`Self::missing_objects`] instead, with the rest of the
+    /// scene restored all the same.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_project(&mut self, path: &std::path::Path) -> std::io::Result<f32> {
+        use std::collections::HashMap;
+
+        let project: ProjectFile = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        if project.version > ProjectFile::VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "the project has version {}, newer than version {} this demo reads",
+                    project.version,
+                    ProjectFile::VERSION
+                ),
+            ));
+        }
+
+        // Nothing fails from here on, the project replaces the scene with whatever it could load.
+        self.drop_objects();
+        // Each file is read once, however many of its meshes the project uses.
+        let mut files = HashMap::new();
+        let mut objects = Vec::new();
+        let mut missing_objects = Vec::new();
+        for saved in project.objects {
+            let object = match &saved.source {
+                ProjectSource::Primitive { shape } => Primitive::ALL
+                    .into_iter()
+                    .find(|primitive| primitive.name() == shape)
+                    .map(|primitive| self.primitive_object(primitive))
+                    .ok_or(format!("Unknown shape {shape}")),
+                ProjectSource::File { path: file, mesh } => {
+                    let file = project::resolve_path(file, path);
+                    let loaded = files.entry(file.clone()).or_insert_with(|| {
+                        Self::read_model(&file)
+                            .and_then(|mut cpu_model| {
+                                self.prepare_model(&mut cpu_model)?;
+                                Ok(self.model_objects(cpu_model, Some(&file)))
+                            })
+                            .map(|objects| objects.into_iter().map(Some).collect::<Vec<_>>())
+                            .map_err(|err| format!("Failed to load {}: {err}", file.display()))
+                    });
+                    match loaded {
+                        Ok(objects) => objects
+                            .get_mut(*mesh)
+                            .and_then(Option::take)
+                            .ok_or(format!("{} has no mesh {mesh}", file.display())),
+                        Err(err) => Err(err.clone()),
+                    }
+                }
+            };
+            match object {
+                Ok(mut object) => {
+                    object.name = saved.name;
+                    object.transform = Transform {
+                        translation: saved.translation.into(),
+                        rotation: saved.rotation.into(),
+                        scale: saved.scale,
+                    };
+                    object.visible = saved.visible;
+                    objects.push(object);
+                }
+                Err(error) => missing_objects.push(MissingObject {
+                    object: saved,
+                    error,
+                }),
+            }
+        }
+
+        let primitive = match objects.as_slice() {
+            [object] => match object.source {
+                Some(ObjectSource::Primitive(primitive)) => Some(primitive),
+                _ => None,
+            },
+            _ => None,
+        };
+        let triangle = objects.first().is_some_and(|object| {
+            object.source == Some(ObjectSource::Primitive(Primitive::Triangle))
+        });
+        // The meshes of the files the project doesn't use go first.
+        drop(files);
+        self.set_objects(objects);
+        self.missing_objects = missing_objects;
+        self.primitive = primitive;
+        if triangle {
+            self.vertex_colors = Some(Self::DEFAULT_VERTEX_COLORS);
+        }
+
+        self.lights = project
+            .lights
+            .iter()
+            .map(|saved| {
+                let mut light = match saved.kind {
+                    ProjectLightKind::Point => SceneLight::point(&self.three_d),
+                    ProjectLightKind::Spot => SceneLight::spot(&self.three_d),
+                };
+                let (position, color, intensity) = light.common_mut();
+                *position = saved.position.into();
+                *color = saved.color.into();
+                *intensity = saved.intensity;
+                light
+            })
+            .collect();
+        self.clear_color = project.clear_color.into();
+        Ok(self.set_camera_state(&project.camera))
+    }
+
+    /// What the GL context supports of encoding to sRGB, as queried when the scene was set up.
+    pub fn srgb_support(&self) -> SrgbSupport {
+        self.srgb_support
+    }
+
+    /// Counts and bounds of the meshes of the model file loaded last by [`Self::set_model`] or [`Self::add_model`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn import_stats(&self) -> Option<&ImportStats> {
+        self.import_stats.as_ref()
+    }
+
+    /// Models of the opened project whose files couldn't be loaded.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn missing_objects(&self) -> &[MissingObject] {
+        &self.missing_objects
+    }
+
+    /// Rebuilds the camera with the given projection, keeping its view and clipping planes.
+    pub fn set_projection_mode(&mut self, mode: ProjectionMode) {
+        use three_d::*;
+
+        let viewport = self.camera.viewport();
+        let position = *self.camera.position();
+        let target = *self.camera.target();
+        let up = *self.camera.up();
+        let (z_near, z_far) = (self.camera.z_near(), self.camera.z_far());
+        let field_of_view = degrees(self.field_of_view);
+
+        self.camera = match mode {
+            ProjectionMode::Perspective => Camera::new_perspective(
+                viewport,
+                position,
+                target,
+                up,
+                field_of_view,
+                z_near,
+                z_far,
+            ),
+            ProjectionMode::Orthographic => {
+                // Use the height of the perspective frustum at the target, so the target keeps its size on screen.
+                let height = 2.0 * position.distance(target) * (field_of_view / 2.0).tan();
+                Camera::new_orthographic(viewport, position, target, up, height, z_near, z_far)
+            }
+        };
+        self.projection_mode = mode;
+    }
+
+    /// Degrees the camera is rolled by counterclockwise around its view direction, 0 when level with the horizon.
+    pub fn roll(&self) -> f32 {
+        self.orbit.roll().to_degrees()
+    }
+
+    /// Rolls the camera to `roll` degrees counterclockwise around its view direction, keeping where it looks.
+    pub fn set_roll(&mut self, roll: f32) {
+        self.animator = None;
+        self.orbit.set_roll(&mut self.camera, roll.to_radians());
+    }
+
+    /// Rolls the camera back level with the horizon, so +y points up on the screen again.
+    pub fn level_horizon(&mut self) {
+        self.set_roll(0.0);
+    }
+
+    /// Moves the camera, done before painting so overlays drawn by egui see the same camera as the paint callback.
+    /// Ignores the input while a turntable export moves the camera.
+    pub fn handle_input(&mut self, input: &OrbitInput) {
+        if self.turntable.is_some() {
+            return;
+        }
+        if input.is_active() {
+            // Hand the camera back to the user instead of fighting them.
+            self.animator = None;
+        }
+        match self.navigation {
+            Navigation::Orbit => self.orbit.handle_input(&mut self.camera, input),
+            Navigation::Fly => {
+                self.fly.handle_input(&mut self.camera, input);
+                self.orbit.follow_camera(&self.camera);
+            }
+        }
+    }
+
+    /// Picks the model under `pos` in the canvas `rect` when painting next.
+    pub fn request_pick(&mut self, rect: egui::Rect, pos: egui::Pos2) {
+        self.pick_ray = Ray::from_canvas(&self.camera, rect, pos);
+        if self.pick_ray.is_none() {
+            self.picked = None;
+        }
+    }
+
+    /// Follows the cursor at `pos` in the canvas `rect` with the cursor ray, `None` while the cursor is elsewhere.
+    pub fn set_cursor(&mut self, rect: egui::Rect, pos: Option<egui::Pos2>) {
+        if let Some(pos) = pos {
+            self.cursor_ray = Ray::from_canvas(&self.camera, rect, pos);
+        }
+        if pos != self.hover_pos {
+            self.hover_pos = pos;
+            self.hover_ray = pos.and_then(|pos| Ray::from_canvas(&self.camera, rect, pos));
+            if self.hover_ray.is_none() {
+                self.hovered = None;
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.cursor = pos.map(|pos| (pos - rect.min) / rect.size());
+        }
+    }
+
+    /// The surface under the cursor as of the last paint, `None` if there's nothing under it, the cursor is
+    /// elsewhere or `probe_depth` is off.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn cursor_depth(&self) -> Option<DepthSample> {
+        self.cursor_depth
+    }
+
+    /// Renders the depth of the scene into a depth texture the size of the viewport, kept for the next call, only
+    /// for the pixel at `position`, a fraction of the viewport size with y down. Its depth is unprojected back to
+    /// world space. WebGL can't read depth textures, hence native only.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn sample_depth(&self, position: egui::Vec2) -> Option<DepthSample> {
+        use three_d::*;
+
+        let viewport = self.camera.viewport();
+        let mut camera = self.camera.clone();
+        camera.set_viewport(Viewport::new_at_origo(viewport.width, viewport.height));
+        let (width, height) = (viewport.width as f32, viewport.height as f32);
+        let x = (position.x * width).clamp(0.0, width - 1.0).floor();
+        let y = ((1.0 - position.y) * height)
+            .clamp(0.0, height - 1.0)
+            .floor();
+        let pixel = ScissorBox {
+            x: x as i32,
+            y: y as i32,
+            width: 1,
+            height: 1,
+        };
+
+        let mut probe = OffscreenTarget::reuse::<[u8; 4]>(
+            self.depth_probe.take(),
+            &self.three_d,
+            viewport.width,
+            viewport.height,
+        );
+        let depth = {
+            let target = probe.render_target();
+            self.with_scene(|_, objects, lights| {
+                target
+                    .clear_partially(pixel, ClearState::depth(1.0))
+                    .render_partially(pixel, &camera, objects, lights);
+            });
+            target.read_depth_partially(pixel).first().copied()
+        };
+        self.depth_probe.set(Some(probe));
+        let depth = depth?;
+        // Nothing was drawn there, or only the sky at the far plane.
+        if depth >= 1.0 {
+            return None;
+        }
+
+        // The depth buffer holds the window space depth of the pixel center, from 0 at the near plane to 1 at the far plane.
+        let ndc = vec4(
+            (x + 0.5) / width * 2.0 - 1.0,
+            (y + 0.5) / height * 2.0 - 1.0,
+            depth * 2.0 - 1.0,
+            1.0,
+        );
+        let world = (camera.projection() * camera.view()).invert()? * ndc;
+        let position = world.truncate() / world.w;
+        Some(DepthSample {
+            distance: position.distance(*camera.position()),
+            position,
+        })
+    }
+
+    /// Moves the camera target to the point of the models under `pos` in the canvas `rect` when painting next,
+    /// keeping the distance to it. Nothing happens if `pos` misses the models.
+    pub fn request_focus(&mut self, rect: egui::Rect, pos: egui::Pos2) {
+        self.focus_ray = Ray::from_canvas(&self.camera, rect, pos);
+    }
+
+    /// Finds the closest model along the ray by rendering the depth of each model, like `three_d::pick`.
+    fn pick(&self, ray: &Ray) -> Option<Pick> {
+        use three_d::*;
+
+        self.objects
+            .iter()
+            .enumerate()
+            .filter(|(_, object)| object.visible)
+            .filter_map(|(model, object)| {
+                let position = ray_intersect(
+                    &self.three_d,
+                    ray.origin,
+                    ray.direction,
+                    ray.length,
+                    &object.model.geometry,
+                )?;
+                Some(Pick { model, position })
+            })
+            .min_by(|a, b| {
+                let distance = |pick: &Pick| pick.position.distance2(ray.origin);
+                distance(a).total_cmp(&distance(b))
+            })
+    }
+
+    /// Adds a world space point to the measurement, starting a new one if it already had two points.
+    fn add_measurement_point(&mut self, position: three_d::Vec3) {
+        use three_d::*;
+
+        let Some(inverse) = self.transformation.invert() else {
+            return;
+        };
+        if self.measurement.len() == 2 {
+            self.measurement.clear();
+        }
+        self.measurement
+            .push((inverse * position.extend(1.0)).truncate());
+        let segments = match self.measurement[..] {
+            [start, end] => vec![[start, end]],
+            _ => Vec::new(),
+        };
+        lines::set_segments(&mut self.measurement_line, &segments);
+    }
+
+    /// World space distance between the two measured points, `None` until both are picked.
+    pub fn measured_distance(&self) -> Option<f32> {
+        use three_d::*;
+
+        match self.measurement[..] {
+            [start, end] => {
+                let world = |point: Vec3| (self.transformation * point.extend(1.0)).truncate();
+                Some(world(start).distance(world(end)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Number of points picked for the measurement so far, at most two.
+    pub fn measurement_points(&self) -> usize {
+        self.measurement.len()
+    }
+
+    pub fn clear_measurement(&mut self) {
+        self.measurement.clear();
+        lines::set_segments(&mut self.measurement_line, &[]);
+    }
+
+    /// Screen positions and colors of the triangle corners labelled "A", "B" and "C", leaving out corners behind
+    /// the camera. Empty when a model was loaded, the triangle is hidden or instancing is on.
+    pub fn vertex_labels(
+        &self,
+        transformation: three_d::Mat4,
+        rect: egui::Rect,
+    ) -> Vec<(&'static str, three_d::Srgba, egui::Pos2)> {
+        let Some(colors) = self.vertex_colors.filter(|_| !self.instancing) else {
+            return Vec::new();
+        };
+        let triangle = &self.objects[0];
+        if !triangle.visible {
+            return Vec::new();
+        }
+        let view_projection = self.camera.projection()
+            * self.camera.view()
+            * transformation
+            * triangle.transform.matrix();
+        ["A", "B", "C"]
+            .into_iter()
+            .zip(colors)
+            .zip(Self::triangle_positions(self.morph))
+            .filter_map(|((label, color), position)| {
+                let clip = view_projection * position.extend(1.0);
+                if clip.w <= 0.0 {
+                    return None;
+                }
+                let ndc = clip.truncate() / clip.w;
+                let pos = egui::pos2(
+                    rect.left() + (ndc.x + 1.0) * 0.5 * rect.width(),
+                    rect.top() + (1.0 - ndc.y) * 0.5 * rect.height(),
+                );
+                // The camera has no aspect ratio before the first paint.
+                (pos.x.is_finite() && pos.y.is_finite()).then_some((label, color, pos))
+            })
+            .collect()
+    }
+
+    /// `transformation` is the transformation of the models, set before rendering them.
+    pub fn paint(&mut self, info: &egui::PaintCallbackInfo, transformation: three_d::Mat4) {
+        use three_d::*;
+
+        let viewport = canvas::canvas_viewport(info);
+        // A collapsed canvas has no aspect ratio, so keep the camera as it was until it has a size again.
+        if viewport.width == 0 || viewport.height == 0 {
+            return;
+        }
+
+        self.prepare_frame(viewport, info.pixels_per_point, transformation);
+
+        // Only clear and draw inside the egui rect, the rest of the window belongs to egui.
+        let [width, height] = info.screen_size_px;
+        let screen = RenderTarget::screen(&self.three_d, width, height);
+        let srgb = self
+            .framebuffer_srgb
+            .apply(&self.three_d, &self.srgb_support);
+        let mut targets = std::mem::take(&mut self.targets);
+        self.render_to_screen(&screen, &self.camera, &mut targets, true);
+        self.targets = targets;
+        if self.split_view {
+            self.render_frustum(&screen, &self.camera, &self.side_frustum);
+        }
+
+        self.paint_axes_gizmo(&screen, viewport, info.pixels_per_point);
+        FramebufferSrgb::restore(&self.three_d, srgb);
+    }
+
+    /// Renders the scene at `width` by `height` pixels into an offscreen texture instead of the screen, returning
+    /// its pixels from top to bottom. Prepares the frame like [`Self::paint`], but leaves out the axes gizmo.
+    /// Returns no pixels if either side is zero.
+    pub fn paint_to_pixels(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels_per_point: f32,
+        transformation: three_d::Mat4,
+    ) -> Vec<[u8; 4]> {
+        if width == 0 || height == 0 {
+            return Vec::new();
+        }
+        self.prepare_frame(
+            three_d::Viewport::new_at_origo(width, height),
+            pixels_per_point,
+            transformation,
+        );
+        self.render_offscreen(width, height, true)
+    }
+
+    /// Updates everything that changes from frame to frame, for rendering into `viewport` next.
+    fn prepare_frame(
+        &mut self,
+        viewport: three_d::Viewport,
+        pixels_per_point: f32,
+        transformation: three_d::Mat4,
+    ) {
+        use three_d::*;
+
+        //We need to update the viewport each frame to ensure three-d is actually rendering inside the Canvas each time.
+        self.camera.set_viewport(viewport);
+        self.draw_calls.start_frame();
+
+        self.directional.direction = self.light_direction;
+        for pane in &mut self.panes {
+            pane.material.albedo.a = (self.pane_alpha.clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+
+        for object in &mut self.objects {
+            object.model.material.render_states.cull = self.cull;
+        }
+        self.instanced_model.material.render_states.cull = self.cull;
+
+        if std::mem::take(&mut self.triangle_dirty) {
+            if let Some(colors) = self.vertex_colors {
+                self.rebuild_triangle(colors);
+            }
+        }
+
+        if self.objects_dirty {
+            self.rebuild_lines();
+        }
+
+        self.transformation = transformation;
+        for object in &mut self.objects {
+            // Set the current transformation of the model, placing it in the scene before transforming the whole scene
+            object
+                .model
+                .set_transformation(transformation * object.transform.matrix());
+            if let Some(simplified) = &mut object.simplified {
+                simplified
+                    .mesh
+                    .set_transformation(transformation * object.transform.matrix());
+                // Simplified beyond the distance, measured in sizes of the model.
+                let aabb = object.model.aabb();
+                let distance = aabb.center().distance(*self.camera.position());
+                simplified.shown = !self.level_of_detail.automatic
+                    || distance > self.level_of_detail.distance * aabb.size().magnitude();
+            }
+        }
+        if self.auto_clip_planes {
+            let mut aabb = self.model_aabb;
+            aabb.transform(&transformation);
+            self.fit_clip_planes(aabb);
+        }
+        let frustum = Frustum::new(&self.camera);
+        for object in &mut self.objects {
+            object.culled = self.frustum_culling && !frustum.intersects(&object.model.aabb());
+        }
+        if let Some(colors) = self.vertex_colors.filter(|_| self.compare_interpolation) {
+            let three_d = &self.three_d;
+            let morph = self.morph;
+            let raw_triangle = self.raw_triangle.get_or_insert_with(|| {
+                let cpu_mesh =
+                    Self::triangle_mesh(Some(color_interpolation::raw_colors(colors)), morph);
+                Gm::new(Mesh::new(three_d, &cpu_mesh), RawInterpolationMaterial)
+            });
+            raw_triangle.set_transformation(
+                transformation
+                    * self.objects[0].transform.matrix()
+                    * Mat4::from_translation(vec3(1.2, 0.0, 0.0)),
+            );
+        }
+        if let Some((_, origin, length)) = self.translate_handles() {
+            self.translate_gizmo.update(origin, length);
+        }
+        self.measurement_line.set_transformation(transformation);
+        let line_width = self.line_width * pixels_per_point;
+        for lines in [
+            &mut self.grid,
+            &mut self.wireframe,
+            &mut self.normals,
+            &mut self.bounding_box,
+            &mut self.measurement_line,
+            &mut self.cursor_ray_line,
+            &mut self.main_frustum,
+            &mut self.side_frustum,
+        ] {
+            lines.geometry.width = line_width;
+        }
+        self.instanced_model.set_transformation(transformation);
+        self.wireframe.set_transformation(transformation);
+        self.normals.set_transformation(transformation);
+        self.points.set_transformation(transformation);
+        self.points.material.color = self.point_color;
+        self.world_aabb = self.model_aabb;
+        self.world_aabb.transform(&transformation);
+        if self.show_bounding_box {
+            lines::set_segments(
+                &mut self.bounding_box,
+                &lines::box_segments(&self.world_aabb),
+            );
+        }
+        let ground_transformation = self.ground_transformation();
+        self.ground.set_transformation(ground_transformation);
+        self.update_shadow_map();
+        let center = self.world_aabb.center();
+        for light in &mut self.lights {
+            light.aim_at(center);
+        }
+        self.light_markers
+            .geometry
+            .set_instances(&lights::marker_instances(&self.lights));
+        if self.show_particles {
+            self.particles.update_instances(&self.camera);
+        }
+
+        if self.show_skybox && self.sky.is_none() {
+            let builtin = self.builtin_sky.unwrap_or_default();
+            self.sky = Some(Sky::builtin(&self.three_d, builtin));
+        }
+
+        if let Some(ray) = self.pick_ray.take() {
+            // Only the models can be picked, not the instances.
+            self.picked = (!self.instancing).then(|| self.pick(&ray)).flatten();
+            self.selected = self.picked.map(|pick| pick.model);
+            if let Some(pick) = self.picked.filter(|_| self.measuring) {
+                self.add_measurement_point(pick.position);
+            }
+        }
+
+        if let Some(ray) = self.hover_ray.take() {
+            self.hovered = (self.highlight_hovered && !self.instancing)
+                .then(|| self.pick(&ray))
+                .flatten()
+                .map(|pick| pick.model);
+        }
+
+        let marker_radius = (self.world_aabb.size().magnitude() * 0.01).max(0.001);
+        if let Some(ray) = self.cursor_ray.filter(|_| self.show_cursor_ray) {
+            let end = ray.origin + ray.direction * ray.length;
+            lines::set_segments(&mut self.cursor_ray_line, &[[ray.origin, end]]);
+            // Picks every frame, which is slow but only needed while debugging.
+            self.cursor_hit = (!self.instancing)
+                .then(|| self.pick(&ray))
+                .flatten()
+                .map(|pick| pick.position);
+            if let Some(hit) = self.cursor_hit {
+                self.cursor_hit_marker.set_transformation(
+                    Mat4::from_translation(hit) * Mat4::from_scale(marker_radius),
+                );
+            }
+        }
+
+        self.debug_draw_models
+            .update(&self.debug_draw, line_width, marker_radius);
+        self.debug_draw.clear();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.cursor_depth = self
+                .cursor
+                .filter(|_| self.probe_depth)
+                .and_then(|position| self.sample_depth(position));
+            let dof = self.depth_of_field;
+            if dof.enabled && dof.auto_focus {
+                let position = self.cursor.unwrap_or(egui::vec2(0.5, 0.5));
+                if let Some(sample) = self.sample_depth(position) {
+                    self.depth_of_field.focus_distance = sample.distance;
+                }
+            }
+        }
+
+        if let Some(ray) = self.focus_ray.take().filter(|_| !self.instancing) {
+            if let Some(pick) = self.pick(&ray) {
+                self.focus_on(pick.position);
+            }
+        }
+
+        if let Some(request) = self.screenshot_request.take() {
+            // A screenshot of another aspect ratio sees more than the view the objects were culled for.
+            for object in &mut self.objects {
+                object.culled = false;
+            }
+            self.screenshot_result = Some(
+                self.capture(&request)
+                    .map(|_| request.path)
+                    .map_err(|err| format!("Failed to save screenshot: {err}")),
+            );
+        }
+
+        self.save_turntable_frame();
+    }
+
+    /// Starts exporting a turntable of the scene, one frame saved on each paint, with the camera orbiting a full
+    /// turn from where it is. Fails if the directory can't be created.
+    pub fn start_turntable(&mut self, mut request: TurntableRequest) -> std::io::Result<()> {
+        std::fs::create_dir_all(&request.directory)?;
+        request.frames = request.frames.max(1);
+        self.animator = None;
+        self.turntable = Some(Turntable {
+            request,
+            saved: 0,
+            camera: self.camera.clone(),
+        });
+        self.turntable_result = None;
+        Ok(())
+    }
+
+    /// Frames saved and frames in total of the turntable export in progress.
+    pub fn turntable_progress(&self) -> Option<(u32, u32)> {
+        self.turntable
+            .as_ref()
+            .map(|turntable| (turntable.saved, turntable.request.frames))
+    }
+
+    /// Stops the turntable export, keeping the frames saved so far, and puts the camera back.
+    pub fn cancel_turntable(&mut self) {
+        self.finish_turntable(None);
+    }
+
+    fn finish_turntable(&mut self, result: Option<Result<PathBuf, String>>) {
+        if let Some(turntable) = self.turntable.take() {
+            let viewport = self.camera.viewport();
+            self.camera = turntable.camera;
+            self.camera.set_viewport(viewport);
+            self.orbit.follow_camera(&self.camera);
+            self.turntable_result = result;
+        }
+    }
+
+    /// Turns the camera to the next frame of the turntable export and saves it.
+    fn save_turntable_frame(&mut self) {
+        let Some(turntable) = &self.turntable else {
+            return;
+        };
+        let request = turntable.request.clone();
+        let index = turntable.saved;
+        if index > 0 {
+            let step = std::f32::consts::TAU / request.frames as f32;
+            self.orbit.turn(&mut self.camera, step);
+        }
+        // The frames may have another aspect ratio than the view the objects were culled for.
+        for object in &mut self.objects {
+            object.culled = false;
+        }
+        let path = request.frame_path(index);
+        let pixels = self.render_to_pixels(request.width, request.height);
+        match screenshot::save_png(&path, request.width, request.height, pixels) {
+            Err(err) => self.finish_turntable(Some(Err(format!(
+                "Failed to save {}: {err}",
+                path.display()
+            )))),
+            Ok(()) if index + 1 == request.frames => {
+                self.finish_turntable(Some(Ok(request.directory)));
+            }
+            Ok(()) => {
+                if let Some(turntable) = &mut self.turntable {
+                    turntable.saved += 1;
+                }
+            }
+        }
+    }
+
+    /// Lays the ground plane flat below the models, reaching well past them so their shadows stay on it.
+    fn ground_transformation(&self) -> three_d::Mat4 {
+        use three_d::*;
+
+        let (center, size) = if self.world_aabb.is_empty() {
+            (vec3(0.0, 0.0, 0.0), vec3(1.0, 1.0, 1.0))
+        } else {
+            (self.world_aabb.center(), self.world_aabb.size())
+        };
+        let bottom = center.y - size.y * 0.5;
+        Mat4::from_translation(vec3(center.x, bottom, center.z))
+            * Mat4::from_scale(size.magnitude().max(0.001) * 2.0)
+            * Mat4::from_angle_x(degrees(-90.0))
+    }
+
+    /// Renders the depth of the shadow casters as seen from the directional light, so the shadows follow the models as they move.
+    fn update_shadow_map(&mut self) {
+        use three_d::*;
+
+        if !self.shadows {
+            self.directional.clear_shadow_map();
+            return;
+        }
+        // The ground doesn't cast a visible shadow, but including it fits the shadow map around all of it.
+        let casters = self
+            .objects
+            .iter()
+            .filter(|object| object.visible && !self.instancing)
+            .map(|object| object.shown_geometry() as &dyn Geometry)
+            .chain(
+                self.instancing
+                    .then_some(&self.instanced_model.geometry as &dyn Geometry),
+            )
+            .chain(
+                self.show_occluder
+                    .then_some(&self.occluder.geometry as &dyn Geometry),
+            )
+            .chain(
+                self.show_ground
+                    .then_some(&self.ground.geometry as &dyn Geometry),
+            )
+            .collect::<Vec<_>>();
+        self.draw_calls.add(casters.len());
+        self.directional
+            .generate_shadow_map(self.shadow_map_size, casters);
+    }
+
+    /// Paints the top-down view of the split view, after [`Self::paint`] prepared the scene for the frame.
+    pub fn paint_side_view(&mut self, info: &egui::PaintCallbackInfo) {
+        use three_d::*;
+
+        let viewport = canvas::canvas_viewport(info);
+        if viewport.width == 0 || viewport.height == 0 {
+            return;
+        }
+        let camera = self.top_down_camera(viewport);
+        if self.show_frustums {
+            lines::set_segments(
+                &mut self.main_frustum,
+                &lines::frustum_segments(&self.camera),
+            );
+            lines::set_segments(&mut self.side_frustum, &lines::frustum_segments(&camera));
+        }
+        let [width, height] = info.screen_size_px;
+        let screen = RenderTarget::screen(&self.three_d, width, height);
+        let srgb = self
+            .framebuffer_srgb
+            .apply(&self.three_d, &self.srgb_support);
+        // The models culled for the main camera are still in the top-down view, as usual unless shown in magenta.
+        let culled = if self.show_culled {
+            Vec::new()
+        } else {
+            self.objects
+                .iter_mut()
+                .map(|object| std::mem::take(&mut object.culled))
+                .collect()
+        };
+        let mut targets = std::mem::take(&mut self.side_targets);
+        // The top-down view shows the whole scene, which the focus of the main view would blur.
+        self.render_to_screen(&screen, &camera, &mut targets, false);
+        self.side_targets = targets;
+        for (object, culled) in self.objects.iter_mut().zip(culled) {
+            object.culled = culled;
+        }
+        self.render_frustum(&screen, &camera, &self.main_frustum);
+        FramebufferSrgb::restore(&self.three_d, srgb);
+    }
+
+    /// An orthographic camera looking down on the models, with their bounds filling most of the viewport.
+    fn top_down_camera(&self, viewport: three_d::Viewport) -> three_d::Camera {
+        use three_d::*;
+
+        let (center, size) = if self.world_aabb.is_empty() {
+            (vec3(0.0, 0.0, 0.0), vec3(1.0, 1.0, 1.0))
+        } else {
+            (self.world_aabb.center(), self.world_aabb.size())
+        };
+        let radius = (size.magnitude() * 0.5).max(0.001);
+        let aspect = viewport.width as f32 / viewport.height.max(1) as f32;
+        let height = size.z.max(size.x / aspect).max(0.001) * 1.2;
+        Camera::new_orthographic(
+            viewport,
+            center + vec3(0.0, radius * 2.0, 0.0),
+            center,
+            vec3(0.0, 0.0, -1.0),
+            height,
+            radius * 0.1,
+            radius * 4.0,
+        )
+    }
+
+    /// Renders the scene into the viewport of the camera, antialiased and post-processed when enabled,
+    /// and blurred by the depth of field if it's enabled too with `depth_of_field`.
+    /// Reuses the offscreen targets of the view from the last frame.
+    fn render_to_screen(
+        &self,
+        screen: &three_d::RenderTarget,
+        camera: &three_d::Camera,
+        targets: &mut ViewTargets,
+        depth_of_field: bool,
+    ) {
+        let depth_of_field = depth_of_field && self.depth_of_field.enabled;
+        if self.post_processing {
+            targets.msaa = None;
+            let hdr = targets.hdr.take();
+            targets.hdr = Some(self.render_multisampled(screen, camera, hdr, depth_of_field));
+        } else if self.msaa_samples > 1 || depth_of_field || self.ambient_occlusion.enabled {
+            targets.hdr = None;
+            let msaa = targets.msaa.take();
+            targets.msaa = Some(self.render_multisampled(screen, camera, msaa, depth_of_field));
+        } else {
+            targets.msaa = None;
+            targets.hdr = None;
+            self.render_scene(screen, camera, three_d::ScissorBox::from(camera.viewport()));
+        }
+        self.render_outline(screen, camera, &mut targets.outline);
+        self.render_translate_handles(screen, camera);
+    }
+
+    /// Draws the frustum of the other camera of the split view over everything else in the viewport of `camera`.
+    fn render_frustum(
+        &self,
+        target: &three_d::RenderTarget,
+        camera: &three_d::Camera,
+        frustum: &LineModel,
+    ) {
+        if !self.show_frustums {
+            return;
+        }
+        self.draw_calls.add(1);
+        target.render_partially(
+            three_d::ScissorBox::from(camera.viewport()),
+            camera,
+            frustum,
+            &[],
+        );
+    }
+
+    /// Draws the translate handles of the selected object over everything else in the viewport of the camera.
+    fn render_translate_handles(&self, target: &three_d::RenderTarget, camera: &three_d::Camera) {
+        if self.translate_handles().is_none() {
+            return;
+        }
+        let handles = self.translate_gizmo.handles();
+        self.draw_calls.add(handles.len());
+        target.render_partially(
+            three_d::ScissorBox::from(camera.viewport()),
+            camera,
+            handles,
+            &[],
+        );
+    }
+
+    /// Outlines the selected object on top of the rendered scene in the viewport of the camera.
+    /// The model is rendered into a mask first, so the outline follows its silhouette even where it's hidden. The
+    /// mask is kept in `mask` for the next frame.
+    fn render_outline(
+        &self,
+        target: &three_d::RenderTarget,
+        camera: &three_d::Camera,
+        mask: &mut Option<OffscreenTarget>,
+    ) {
+        use three_d::*;
+
+        let Some(object) = self
+            .selected
+            .filter(|_| !self.instancing)
+            .map(|index| &self.objects[index])
+            .filter(|object| object.visible)
+        else {
+            return;
+        };
+        let viewport = camera.viewport();
+        let kept = mask.take();
+        let mask = mask.insert(OffscreenTarget::reuse::<[u8; 4]>(
+            kept,
+            &self.three_d,
+            viewport.width,
+            viewport.height,
+        ));
+        let mut mask_camera = camera.clone();
+        mask_camera.set_viewport(Viewport::new_at_origo(viewport.width, viewport.height));
+        let model = Gm::new(
+            object.shown_geometry(),
+            ColorMaterial {
+                color: Srgba::WHITE,
+                ..Default::default()
+            },
+        );
+        mask.render_target()
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+            .render(&mask_camera, [&model], &[]);
+
+        // The mask and the effect drawing the outline from it.
+        self.draw_calls.add(2);
+        target.apply_screen_effect_partially(
+            ScissorBox::from(viewport),
+            &OutlineEffect {
+                color: self.outline_color,
+                thickness: self.outline_thickness,
+            },
+            camera,
+            &[],
+            Some(ColorTexture::Single(&mask.color)),
+            None,
+        );
+    }
+
+    fn exposure_effect(&self) -> ExposureEffect {
+        ExposureEffect {
+            exposure: self.exposure,
+        }
+    }
+
+    /// Clears the scissor box of the target and renders the scene into it.
+    fn render_scene(
+        &self,
+        target: &three_d::RenderTarget,
+        camera: &three_d::Camera,
+        scissor_box: three_d::ScissorBox,
+    ) {
+        self.with_scene(|clear_state, objects, lights| {
+            target
+                .clear_partially(scissor_box, clear_state)
+                .render_partially(scissor_box, camera, objects, lights);
+        });
+    }
+
+    /// Passes the background clear state, the visible objects and the lights to `render`,
+    /// so the scene can be rendered into targets that aren't a `RenderTarget`.
+    fn with_scene(
+        &self,
+        render: impl FnOnce(three_d::ClearState, &[&dyn three_d::Object], &[&dyn three_d::Light]),
+    ) {
+        use three_d::*;
+
+        let grid = self.show_grid.then_some(&self.grid);
+        let instanced = self.instancing.then_some(&self.instanced_model);
+        let wireframe = (self.show_wireframe && !self.instancing).then_some(&self.wireframe);
+        let normals = (self.show_normals && !self.instancing).then_some(&self.normals);
+        let points = (self.show_points && !self.instancing).then_some(&self.points);
+        // The wireframe and the points replace the surfaces of the models.
+        let surfaces = !self.instancing && !self.show_wireframe && !self.show_points;
+        let calibrating =
+            self.calibration && surfaces && self.vertex_colors.is_some() && !self.textured;
+        let debugging = self.debug_view != DebugView::Shaded && surfaces && !calibrating;
+        let comparing = self.compare_interpolation
+            && surfaces
+            && !calibrating
+            && !debugging
+            && self.vertex_colors.is_some()
+            && !self.textured
+            && self.objects[0].is_drawn();
+        let pulsing = self.pulse
+            && surfaces
+            && !calibrating
+            && !comparing
+            && !debugging
+            && self.vertex_colors.is_some()
+            && !self.textured
+            && self.objects[0].is_drawn();
+        let tinted = self.hovered_material();
+        let models = self
+            .visible_models(tinted.as_ref())
+            .filter(|_| surfaces && !calibrating && !debugging)
+            // The visible triangle comes first, replaced by the pulsing one or the compared ones.
+            .skip((pulsing || comparing) as usize)
+            .collect::<Vec<_>>();
+        let pulsing_triangle = pulsing.then(|| {
+            let material = PulseMaterial {
+                time: self.time,
+                ..Default::default()
+            };
+            Gm::new(&self.objects[0].model.geometry, material)
+        });
+        let debug_models = self
+            .objects
+            .iter()
+            .filter(|object| object.is_drawn() && debugging)
+            .map(|object| {
+                let material = AttributeMaterial {
+                    view: self.debug_view,
+                    has_uvs: object.cpu_mesh.uvs.is_some(),
+                    bounds: self.world_aabb,
+                };
+                Gm::new(object.shown_geometry(), material)
+            })
+            .collect::<Vec<_>>();
+        let unlit = (calibrating || comparing)
+            .then(|| &self.objects[0])
+            .filter(|triangle| triangle.is_drawn())
+            .map(|triangle| Gm::new(&triangle.model.geometry, ColorMaterial::default()));
+        let culled_models = self
+            .objects
+            .iter()
+            .filter(|object| object.visible && object.culled && self.show_culled && surfaces)
+            .map(|object| {
+                let material = ColorMaterial {
+                    color: Srgba::new(255, 0, 255, 255),
+                    ..Default::default()
+                };
+                Gm::new(object.shown_geometry(), material)
+            })
+            .collect::<Vec<_>>();
+        let sky = self.sky.as_ref().filter(|_| self.show_skybox);
+        let occluder = self.show_occluder.then_some(&self.occluder);
+        let ground = self.show_ground.then_some(&self.ground);
+        let texture_plane = self.show_texture_plane.then_some(&self.texture_plane);
+        let particles = self
+            .show_particles
+            .then(|| self.particles.model())
+            .flatten();
+        let markers = (!self.lights.is_empty()).then_some(&self.light_markers);
+        let panes = if self.show_panes {
+            &self.panes[..]
+        } else {
+            &[]
+        };
+        let bounding_box =
+            (self.show_bounding_box && !self.instancing).then_some(&self.bounding_box);
+        let measurement_line = (self.measurement.len() == 2).then_some(&self.measurement_line);
+        let cursor_ray = self.cursor_ray.filter(|_| self.show_cursor_ray);
+        let cursor_ray_line = cursor_ray.map(|_| &self.cursor_ray_line);
+        let cursor_hit_marker = cursor_ray
+            .and(self.cursor_hit)
+            .map(|_| &self.cursor_hit_marker);
+        let models = models.into_iter();
+        // `render` draws the objects with a deferred material through the G-buffer and the rest forward,
+        // so swapping the materials is all the deferred renderer needs.
+        let deferred = |material: &PhysicalMaterial| {
+            DeferredPhysicalMaterial::from_physical_material(material)
+        };
+        let (forward_models, deferred_models) = if self.deferred {
+            let models = models
+                .map(|model| Gm::new(model.geometry, deferred(model.material)))
+                .collect();
+            (Vec::new(), models)
+        } else {
+            (models.collect(), Vec::new())
+        };
+        let deferred_instanced = instanced
+            .filter(|_| self.deferred)
+            .map(|model| Gm::new(&model.geometry, deferred(&model.material)));
+        let instanced = instanced.filter(|_| !self.deferred);
+        let objects = sky
+            .map(|sky| &sky.skybox as &dyn Object)
+            .into_iter()
+            .chain(forward_models.iter().map(|model| model as &dyn Object))
+            .chain(deferred_models.iter().map(|model| model as &dyn Object))
+            .chain(unlit.as_ref().map(|model| model as &dyn Object))
+            .chain(
+                self.raw_triangle
+                    .as_ref()
+                    .filter(|_| comparing)
+                    .map(|model| model as &dyn Object),
+            )
+            .chain(pulsing_triangle.as_ref().map(|model| model as &dyn Object))
+            .chain(debug_models.iter().map(|model| model as &dyn Object))
+            .chain(culled_models.iter().map(|model| model as &dyn Object))
+            .chain(instanced.map(|model| model as &dyn Object))
+            .chain(
+                deferred_instanced
+                    .as_ref()
+                    .map(|model| model as &dyn Object),
+            )
+            .chain(wireframe.map(|wireframe| wireframe as &dyn Object))
+            .chain(normals.map(|normals| normals as &dyn Object))
+            .chain(points.map(|points| points as &dyn Object))
+            .chain(occluder.map(|occluder| occluder as &dyn Object))
+            .chain(ground.map(|ground| ground as &dyn Object))
+            .chain(texture_plane.map(|plane| plane as &dyn Object))
+            .chain(particles.map(|particles| particles as &dyn Object))
+            .chain(panes.iter().map(|pane| pane as &dyn Object))
+            .chain(bounding_box.map(|bounding_box| bounding_box as &dyn Object))
+            .chain(measurement_line.map(|line| line as &dyn Object))
+            .chain(cursor_ray_line.map(|line| line as &dyn Object))
+            .chain(cursor_hit_marker.map(|marker| marker as &dyn Object))
+            .chain(self.debug_draw_models.objects())
+            .chain(grid.map(|grid| grid as &dyn Object))
+            .chain(markers.map(|markers| markers as &dyn Object))
+            .collect::<Vec<_>>();
+
+        let ambient = sky.map_or(&self.ambient, |sky| &sky.ambient);
+
+        let c = self.clear_color;
+        let c = if self.post_processing {
+            // The clear color isn't tone mapped without post-processing either, so undo the mapping the effect applies.
+            tone_mapping::unmapped_color(c, self.tone_mapping, self.exposure)
+        } else {
+            vec4(c.r as f32, c.g as f32, c.b as f32, c.a as f32) / 255.0
+        };
+        let clear_state = ClearState::color_and_depth(c.x, c.y, c.z, c.w, 1.0);
+        // Render the models lit by the ambient (or sky), directional and added lights, together with the grid.
+        // `render` draws the opaque objects first, then the transparent ones from back to front,
+        // which the panes rely on as they don't write depth.
+        let lights = [ambient, &self.directional as &dyn Light]
+            .into_iter()
+            .chain(self.lights.iter().map(SceneLight::light))
+            .collect::<Vec<_>>();
+        // One call for each object, plus the lighting pass over the G-buffer of the deferred objects.
+        let lighting_pass = !deferred_models.is_empty() || deferred_instanced.is_some();
+        self.draw_calls.add(objects.len() + lighting_pass as usize);
+        render(clear_state, &objects, &lights);
+    }
+
+    /// Renders the scene into a multisampled target the size of the view, then draws the resolved colors into the view with the effect.
+    /// Without antialiasing the target has a single sample.
+    fn render_multisampled<T: three_d::TextureDataType>(
+        &self,
+        screen: &three_d::RenderTarget,
+        camera: &three_d::Camera,
+        msaa: Option<MsaaTarget<T>>,
+        depth_of_field: bool,
+    ) -> MsaaTarget<T> {
+        use three_d::*;
+
+        let viewport = camera.viewport();
+        let mut msaa = msaa
+            .filter(|msaa| {
+                (
+                    msaa.target.width(),
+                    msaa.target.height(),
+                    msaa.target.number_of_samples(),
+                ) == (viewport.width, viewport.height, self.msaa_samples)
+            })
+            .unwrap_or_else(|| MsaaTarget {
+                target: RenderTargetMultisample::new(
+                    &self.three_d,
+                    viewport.width,
+                    viewport.height,
+                    self.msaa_samples,
+                ),
+                color: Texture2D::new_empty::<T>(
+                    &self.three_d,
+                    viewport.width,
+                    viewport.height,
+                    Interpolation::Nearest,
+                    Interpolation::Nearest,
+                    None,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                ),
+                depth: DepthTexture2D::new::<f32>(
+                    &self.three_d,
+                    viewport.width,
+                    viewport.height,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                ),
+            });
+
+        let mut offscreen_camera = camera.clone();
+        offscreen_camera.set_viewport(Viewport::new_at_origo(viewport.width, viewport.height));
+        if self.post_processing {
+            // The effect maps the colors once they're resolved.
+            offscreen_camera.disable_tone_and_color_mapping();
+        }
+        self.with_scene(|clear_state, objects, lights| {
+            msaa.target
+                .clear(clear_state)
+                .render(&offscreen_camera, objects, lights);
+        });
+        msaa.target
+            .resolve_color_to(&msaa.color.as_color_target(None));
+        if depth_of_field || self.ambient_occlusion.enabled {
+            msaa.target.resolve_depth_to(&msaa.depth.as_depth_target());
+        }
+        self.apply_ambient_occlusion(&mut msaa.color, &msaa.depth, &offscreen_camera);
+
+        // The effect draws a quad over the camera viewport, which is the view in the canvas.
+        self.apply_screen_effect(
+            screen,
+            camera,
+            &msaa.color,
+            depth_of_field.then_some(&msaa.depth),
+        );
+        msaa
+    }
+
+    /// Darkens the offscreen colors by the ambient occlusion computed from their depth, if it's enabled.
+    fn apply_ambient_occlusion(
+        &self,
+        color: &mut three_d::Texture2D,
+        depth: &three_d::DepthTexture2D,
+        camera: &three_d::Camera,
+    ) {
+        use three_d::*;
+
+        let settings = self.ambient_occlusion;
+        if !settings.enabled {
+            return;
+        }
+        self.draw_calls.add(1);
+        let mut camera = camera.clone();
+        camera.set_viewport(Viewport::new_at_origo(color.width(), color.height()));
+        let effect = AmbientOcclusionEffect {
+            radius: settings.radius,
+            intensity: settings.intensity,
+            show_only: settings.show_only,
+        };
+        color.as_color_target(None).write(|| {
+            apply_screen_effect(
+                &self.three_d,
+                effect,
+                &camera,
+                &[],
+                None,
+                Some(DepthTexture::Single(depth)),
+            )
+        });
+    }
+
+    /// Draws the offscreen colors into the viewport of the camera, with the exposure and tone mapping when
+    /// post-processing, blurred by the depth of field when given the depth.
+    fn apply_screen_effect(
+        &self,
+        target: &three_d::RenderTarget,
+        camera: &three_d::Camera,
+        color: &three_d::Texture2D,
+        depth: Option<&three_d::DepthTexture2D>,
+    ) {
+        use three_d::*;
+
+        self.draw_calls.add(1);
+        let mut camera = camera.clone();
+        camera.tone_mapping = self.tone_mapping;
+        let scissor_box = ScissorBox::from(camera.viewport());
+        let color = Some(ColorTexture::Single(color));
+        match depth {
+            Some(depth) => target.apply_screen_effect_partially(
+                scissor_box,
+                &DepthOfFieldEffect {
+                    focus_distance: self.depth_of_field.focus_distance,
+                    aperture: self.depth_of_field.aperture,
+                    exposure: self.post_processing.then_some(self.exposure),
+                },
+                &camera,
+                &[],
+                color,
+                Some(DepthTexture::Single(depth)),
+            ),
+            None if self.post_processing => target.apply_screen_effect_partially(
+                scissor_box,
+                &self.exposure_effect(),
+                &camera,
+                &[],
+                color,
+                None,
+            ),
+            None => target.apply_screen_effect_partially(
+                scissor_box,
+                &CopyEffect::default(),
+                &camera,
+                &[],
+                color,
+                None,
+            ),
+        };
+    }
+
+    /// Renders the scene offscreen at the requested resolution and saves it as a PNG.
+    fn capture(&self, request: &ScreenshotRequest) -> Result<(), image::ImageError> {
+        let (width, height) = (request.width, request.height);
+        match request.format {
+            ScreenshotFormat::Png => {
+                let pixels = self.render_to_pixels(width, height);
+                screenshot::save_png(&request.path, width, height, pixels)
+            }
+            ScreenshotFormat::Hdr => {
+                let pixels = self.render_to_linear_pixels(width, height);
+                screenshot::save_hdr(&request.path, width, height, pixels)
+            }
+        }
+    }
+
+    /// Renders the scene like [`Self::render_to_pixels`], but into a floating point texture without the exposure,
+    /// tone mapping and sRGB encoding, returning the linear colors. The outline of the picked model is left out.
+    pub fn render_to_linear_pixels(&self, width: u32, height: u32) -> Vec<[f32; 4]> {
+        use three_d::*;
+
+        if width == 0 || height == 0 {
+            return Vec::new();
+        }
+        let mut camera = self.camera.clone();
+        camera.set_viewport(Viewport::new_at_origo(width, height));
+        camera.disable_tone_and_color_mapping();
+
+        let mut color = Texture2D::new_empty::<[f32; 4]>(
+            &self.three_d,
+            width,
+            height,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        // The depth texture doesn't depend on the color format, so depth testing works as for the PNG.
+        let mut depth = DepthTexture2D::new::<f32>(
+            &self.three_d,
+            width,
+            height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let target = RenderTarget::new(color.as_color_target(None), depth.as_depth_target());
+        self.with_scene(|_, objects, lights| {
+            // The background as the linear color behind the clear color, instead of its sRGB encoded value.
+            let c = self.clear_color.to_linear_srgb();
+            target
+                .clear(ClearState::color_and_depth(c.x, c.y, c.z, c.w, 1.0))
+                .render(&camera, objects, lights);
+        });
+        target.read_color::<[f32; 4]>()
+    }
+
+    /// Renders the scene from the current camera into a texture of the given size, returning its pixels from top to bottom.
+    /// Returns no pixels if either side is zero. The textures are kept for the next call of the same size.
+    pub fn render_to_pixels(&self, width: u32, height: u32) -> Vec<[u8; 4]> {
+        self.render_offscreen(width, height, false)
+    }
+
+    /// Like [`Self::render_to_pixels`], drawing the translate handles on top with `handles`.
+    fn render_offscreen(&self, width: u32, height: u32, handles: bool) -> Vec<[u8; 4]> {
+        use three_d::*;
+
+        if width == 0 || height == 0 {
+            return Vec::new();
+        }
+        let mut camera = self.camera.clone();
+        camera.set_viewport(Viewport::new_at_origo(width, height));
+
+        let mut targets = self.pixel_targets.take();
+        let mut result =
+            OffscreenTarget::reuse::<[u8; 4]>(targets.target.take(), &self.three_d, width, height);
+        let scene = if self.post_processing
+            || self.depth_of_field.enabled
+            || self.ambient_occlusion.enabled
+        {
+            // Without post-processing the colors are mapped already and only darkened, blurred or copied.
+            // The depth is sampled by the depth of field while the result is written, so it's apart from the
+            // depth of the result.
+            let mut scene = OffscreenTarget::reuse::<[f16; 4]>(
+                targets.scene.take(),
+                &self.three_d,
+                width,
+                height,
+            );
+            let mut scene_camera = camera.clone();
+            if self.post_processing {
+                scene_camera.disable_tone_and_color_mapping();
+            }
+            {
+                let target = scene.render_target();
+                self.render_scene(&target, &scene_camera, target.scissor_box());
+            }
+            self.apply_ambient_occlusion(&mut scene.color, &scene.depth, &scene_camera);
+            self.apply_screen_effect(
+                &result.render_target(),
+                &camera,
+                &scene.color,
+                self.depth_of_field.enabled.then_some(&scene.depth),
+            );
+            Some(scene)
+        } else {
+            let target = result.render_target();
+            self.render_scene(&target, &camera, target.scissor_box());
+            None
+        };
+        let pixels = {
+            let target = result.render_target();
+            self.render_outline(&target, &camera, &mut targets.outline);
+            if handles {
+                self.render_translate_handles(&target, &camera);
+            }
+
+            // The camera color mapping already outputs sRGB, which the non-sRGB texture stores as is,
+            // so the bytes are the same as on screen.
+            target.read_color::<[u8; 4]>()
+        };
+        targets.target = Some(result);
+        // Dropped with the effects, like the targets of the views.
+        targets.scene = scene;
+        self.pixel_targets.set(targets);
+        pixels
+    }
+
+    fn paint_axes_gizmo(
+        &mut self,
+        screen: &three_d::RenderTarget,
+        viewport: three_d::Viewport,
+        pixels_per_point: f32,
+    ) {
+        use three_d::*;
+
+        let size = ((Self::GIZMO_SIZE * pixels_per_point).round() as u32)
+            .min(viewport.width)
+            .min(viewport.height);
+        let gizmo_viewport = Viewport {
+            x: viewport.x,
+            y: viewport.y,
+            width: size,
+            height: size,
+        };
+
+        // Look at the origin from the same direction as the main camera, ignoring its position.
+        let position = -self.camera.view_direction() * Self::GIZMO_DISTANCE;
+        self.gizmo_camera.set_viewport(gizmo_viewport);
+        self.gizmo_camera
+            .set_view(position, vec3(0.0, 0.0, 0.0), *self.camera.up());
+
+        self.draw_calls.add(1);
+        // Clear the depth so the axes are drawn on top of the scene.
+        let scissor_box = ScissorBox::from(gizmo_viewport);
+        screen
+            .clear_partially(scissor_box, ClearState::depth(1.0))
+            .render_partially(scissor_box, &self.gizmo_camera, &self.axes, &[]);
+    }
+}
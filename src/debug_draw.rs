@@ -0,0 +1,96 @@
+use three_d::*;
+
+use crate::lines::{self, LineModel};
+
+/// Lines, boxes and points drawn for one frame, for debugging code that runs along with the demo.
+///
+/// Draw into [`Custom3d::debug_draw`](crate::Custom3d::debug_draw) while building the frame, every frame they
+/// should be seen. The next paint renders them and starts over with nothing drawn. Positions are in the scene,
+/// after the transformation of the models.
+#[derive(Clone, Debug, Default)]
+pub struct DebugDraw {
+    segments: Vec<[Vec3; 2]>,
+    segment_colors: Vec<Srgba>,
+    points: Vec<Vec3>,
+    point_colors: Vec<Srgba>,
+}
+
+impl DebugDraw {
+    /// A line from `a` to `b`, as wide as the other lines of the view.
+    pub fn line(&mut self, a: Vec3, b: Vec3, color: Srgba) {
+        self.segments.push([a, b]);
+        self.segment_colors.push(color);
+    }
+
+    /// The edges of a box.
+    pub fn aabb(&mut self, aabb: &AxisAlignedBoundingBox, color: Srgba) {
+        for [a, b] in lines::box_segments(aabb) {
+            self.line(a, b, color);
+        }
+    }
+
+    /// A dot at `p`, sized like the marker of the cursor ray.
+    pub fn point(&mut self, p: Vec3, color: Srgba) {
+        self.points.push(p);
+        self.point_colors.push(color);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty() && self.points.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// What [`DebugDraw`] drew in the last paint, all the lines in one draw call and all the points in another.
+pub(crate) struct DebugDrawModels {
+    lines: LineModel,
+    points: Gm<InstancedMesh, ColorMaterial>,
+    line_count: usize,
+    point_count: usize,
+}
+
+impl DebugDrawModels {
+    pub fn new(context: &Context, width: f32) -> Self {
+        Self {
+            lines: lines::colored_line_model(context, &[], &[], width),
+            points: Gm::new(
+                InstancedMesh::new(context, &Instances::default(), &CpuMesh::sphere(8)),
+                // White keeps the colors of the instances as they are.
+                ColorMaterial::default(),
+            ),
+            line_count: 0,
+            point_count: 0,
+        }
+    }
+
+    /// Uploads what's drawn, with lines `width` pixels wide and points of `radius`. Skips the upload while
+    /// nothing is drawn, as in most frames.
+    pub fn update(&mut self, drawn: &DebugDraw, width: f32, radius: f32) {
+        self.lines.geometry.width = width;
+        if drawn.segments.len() + self.line_count > 0 {
+            lines::set_colored_segments(&mut self.lines, &drawn.segments, &drawn.segment_colors);
+            self.line_count = drawn.segments.len();
+        }
+        if drawn.points.len() + self.point_count > 0 {
+            self.points.geometry.set_instances(&Instances {
+                transformations: drawn
+                    .points
+                    .iter()
+                    .map(|p| Mat4::from_translation(*p) * Mat4::from_scale(radius))
+                    .collect(),
+                colors: Some(drawn.point_colors.clone()),
+                ..Default::default()
+            });
+            self.point_count = drawn.points.len();
+        }
+    }
+
+    pub fn objects(&self) -> impl Iterator<Item = &dyn Object> {
+        let lines = (self.line_count > 0).then_some(&self.lines as &dyn Object);
+        let points = (self.point_count > 0).then_some(&self.points as &dyn Object);
+        lines.into_iter().chain(points)
+    }
+}
@@ -0,0 +1,126 @@
+use three_d::*;
+
+/// What the models are colored by, for diagnosing problems with imported meshes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DebugView {
+    /// Lit by the materials as usual.
+    #[default]
+    Shaded,
+    /// The normals in world space, with x, y and z mapped from -1..1 to red, green and blue.
+    WorldNormals,
+    /// The normals relative to the camera, facing it in blue.
+    ViewNormals,
+    /// The texture coordinates as red and green, black without any.
+    Uvs,
+    /// The distance from the camera across the bounds of the models, from black up close to white far away.
+    Depth,
+}
+
+impl DebugView {
+    pub const ALL: [Self; 5] = [
+        Self::Shaded,
+        Self::WorldNormals,
+        Self::ViewNormals,
+        Self::Uvs,
+        Self::Depth,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Shaded => "Shaded",
+            Self::WorldNormals => "World normals",
+            Self::ViewNormals => "View normals",
+            Self::Uvs => "UVs",
+            Self::Depth => "Depth",
+        }
+    }
+}
+
+/// Colors a model unlit by the attribute a [`DebugView`] other than `Shaded` shows.
+pub(crate) struct AttributeMaterial {
+    pub view: DebugView,
+    /// Whether the mesh has texture coordinates, three-d panics drawing a material that needs them otherwise.
+    pub has_uvs: bool,
+    /// Bounds of the models in the scene, spanning the distances of the depth view.
+    pub bounds: AxisAlignedBoundingBox,
+}
+
+impl Material for AttributeMaterial {
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        // The normals aren't turned towards the camera on back faces like `NormalMaterial` does, so flipped
+        // normals show up as the wrong color. three-d flips v when uploading the texture coordinates, so it's
+        // flipped back to show them as the mesh has them.
+        let color = match self.view {
+            DebugView::WorldNormals => "0.5 + 0.5 * normalize(nor)",
+            DebugView::ViewNormals => "0.5 + 0.5 * normalize(viewRotation * nor)",
+            DebugView::Uvs if self.has_uvs => "vec3(uvs.x, 1.0 - uvs.y, 0.0)",
+            DebugView::Depth => "vec3(clamp((distance(pos, eye) - minDistance) / (maxDistance - minDistance), 0.0, 1.0))",
+            DebugView::Uvs | DebugView::Shaded => "vec3(0.0)",
+        };
+        format!(
+            "
+            uniform mat3 viewRotation;
+            uniform vec3 eye;
+            uniform float minDistance;
+            uniform float maxDistance;
+
+            in vec3 pos;
+            in vec3 nor;
+            in vec2 uvs;
+
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                outColor = vec4({color}, 1.0);
+            }}
+            "
+        )
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: self.view == DebugView::Depth,
+            normal: matches!(self.view, DebugView::WorldNormals | DebugView::ViewNormals),
+            uv: self.view == DebugView::Uvs && self.has_uvs,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        match self.view {
+            DebugView::ViewNormals => {
+                let view = camera.view();
+                program.use_uniform(
+                    "viewRotation",
+                    Mat3::from_cols(view.x.truncate(), view.y.truncate(), view.z.truncate()),
+                );
+            }
+            DebugView::Depth => {
+                let eye = *camera.position();
+                let radius = self.bounds.size().magnitude() * 0.5;
+                let distance = self.bounds.center().distance(eye);
+                program.use_uniform("eye", eye);
+                program.use_uniform("minDistance", (distance - radius).max(camera.z_near()));
+                program.use_uniform(
+                    "maxDistance",
+                    (distance + radius).max(camera.z_near() * 2.0),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates::default()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+
+    fn id(&self) -> u16 {
+        // Next to the lines, with the view and whether there are uvs in the lowest bits.
+        0b1u16 << 15 | 0b1u16 << 10 | (self.view as u16) << 1 | self.has_uvs as u16
+    }
+}
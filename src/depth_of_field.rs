@@ -0,0 +1,172 @@
+use three_d::*;
+
+use crate::effect;
+
+/// Settings of the depth of field, which blurs what's in front of or behind the focal plane.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DepthOfField {
+    pub enabled: bool,
+    /// Distance from the camera that's in focus.
+    pub focus_distance: f32,
+    /// Focuses on what's under the cursor, or in the center of the view while the cursor is elsewhere.
+    /// Needs reading back the depth buffer, hence native only.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub auto_focus: bool,
+    /// Blur radius in pixels of what's infinitely far behind the focal plane.
+    pub aperture: f32,
+}
+
+/// Off, focused on the built-in triangle.
+impl Default for DepthOfField {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            focus_distance: 2.0,
+            #[cfg(not(target_arch = "wasm32"))]
+            auto_focus: false,
+            aperture: 4.0,
+        }
+    }
+}
+
+/// Blurs a color texture by the distance of each pixel from the focal plane, taken from a depth texture.
+/// Gathers a fixed number of samples in a spiral around each pixel, so the cost doesn't grow with the blur.
+pub struct DepthOfFieldEffect {
+    pub focus_distance: f32,
+    pub aperture: f32,
+    /// Scales the colors by the exposure before the tone and color mapping of the camera, for the linear colors
+    /// of the post-processing. `None` copies colors that are already mapped as they are, like `CopyEffect`.
+    pub exposure: Option<f32>,
+}
+
+impl DepthOfFieldEffect {
+    /// The blur radius is capped, as the samples get too sparse to hide the spiral beyond it.
+    pub const MAX_RADIUS: f32 = 16.0;
+    const SAMPLES: u32 = 24;
+}
+
+impl Effect for DepthOfFieldEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        let color_texture = color_texture.expect("the depth of field needs a color texture");
+        let depth_texture = depth_texture.expect("the depth of field needs a depth texture");
+        let mapping = if self.exposure.is_some() {
+            "outColor.rgb = color_mapping(tone_mapping(outColor.rgb * exposure));"
+        } else {
+            ""
+        };
+        format!(
+            "{}{}{}{}{}
+            uniform mat4 viewProjectionInverse;
+            uniform vec3 eyePosition;
+            uniform float focusDistance;
+            uniform float aperture;
+            uniform float exposure;
+            uniform vec2 texelSize;
+
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            float distanceAt(vec2 uv)
+            {{
+                vec4 position = viewProjectionInverse * vec4(uv * 2.0 - 1.0, sample_depth(uv) * 2.0 - 1.0, 1.0);
+                return distance(position.xyz / position.w, eyePosition);
+            }}
+
+            // Zero in the focal plane, approaching the aperture far behind it and growing quickly in front of it.
+            float blurRadius(float d)
+            {{
+                return min(aperture * abs(d - focusDistance) / max(d, 1e-4), {max_radius:.1});
+            }}
+
+            void main()
+            {{
+                float centerDistance = distanceAt(uvs);
+                float radius = blurRadius(centerDistance);
+                outColor = sample_color(uvs);
+                if (radius >= 0.5) {{
+                    float total = 1.0;
+                    for (int i = 0; i < {samples}; i++) {{
+                        float r = sqrt((float(i) + 0.5) / {samples}.0) * radius;
+                        // The golden angle spreads the samples evenly over the disc.
+                        float angle = float(i) * 2.39996323;
+                        vec2 uv = uvs + vec2(cos(angle), sin(angle)) * r * texelSize;
+                        float d = distanceAt(uv);
+                        // Something sharper in front only covers the blurred background as far as its own blur reaches.
+                        float weight = d < centerDistance ? clamp(blurRadius(d) - r + 1.0, 0.0, 1.0) : 1.0;
+                        outColor += sample_color(uv) * weight;
+                        total += weight;
+                    }}
+                    outColor /= total;
+                }}
+                {mapping}
+            }}
+            ",
+            effect::HIGHP_SAMPLERS,
+            color_texture.fragment_shader_source(),
+            depth_texture.fragment_shader_source(),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            max_radius = Self::MAX_RADIUS,
+            samples = Self::SAMPLES,
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        // Next to the outline effect, with another bit for whether the colors are mapped.
+        0b1u16 << 15
+            | 0b1u16 << 9
+            | (self.exposure.is_some() as u16) << 8
+            | color_texture.map_or(0, |t| t.id())
+            | depth_texture.map_or(0, |t| t.id())
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        if let Some(exposure) = self.exposure {
+            camera.tone_mapping.use_uniforms(program);
+            camera.color_mapping.use_uniforms(program);
+            program.use_uniform("exposure", exposure);
+        }
+        // Always succeeds for a valid camera, its view and projection are invertible.
+        if let Some(inverse) = (camera.projection() * camera.view()).invert() {
+            program.use_uniform("viewProjectionInverse", inverse);
+        }
+        program.use_uniform("eyePosition", camera.position());
+        program.use_uniform("focusDistance", self.focus_distance);
+        program.use_uniform("aperture", self.aperture.min(Self::MAX_RADIUS));
+        if let Some(color_texture) = color_texture {
+            let (width, height) = (color_texture.width(), color_texture.height());
+            program.use_uniform("texelSize", vec2(1.0 / width as f32, 1.0 / height as f32));
+            color_texture.use_uniforms(program);
+        }
+        if let Some(depth_texture) = depth_texture {
+            depth_texture.use_uniforms(program);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,3 @@
+/// Goes first in the fragment shaders of effects sampling the depth. Samplers default to low precision on OpenGL ES,
+/// too coarse for the depth to tell distances or normals apart.
+pub(crate) const HIGHP_SAMPLERS: &str = "precision highp sampler2D;\n";
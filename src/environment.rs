@@ -0,0 +1,141 @@
+use three_d::*;
+
+/// A skybox together with the image based lighting derived from it.
+pub struct Sky {
+    pub skybox: Skybox,
+    pub ambient: AmbientLight,
+}
+
+impl Sky {
+    /// `cpu_texture` is an equirectangular panorama, either an LDR image in sRGB or an HDR image.
+    pub fn new(context: &Context, cpu_texture: &CpuTexture) -> Self {
+        let skybox = Skybox::new_from_equirectangular(context, cpu_texture);
+        let ambient =
+            AmbientLight::new_with_environment(context, 1.0, Srgba::WHITE, skybox.texture());
+        Self { skybox, ambient }
+    }
+
+    pub fn builtin(context: &Context, builtin: BuiltinSky) -> Self {
+        Self::new(context, &builtin.cpu_texture())
+    }
+}
+
+/// The panoramas that come with the demo, generated instead of loaded so they work on the web too.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BuiltinSky {
+    /// A blue sky fading to a bright horizon, in LDR like a photo.
+    #[default]
+    Daylight,
+    /// A low sun far brighter than white, in HDR.
+    Sunset,
+    /// Two bright soft boxes in a dark room, in HDR, for showing off reflections.
+    Studio,
+}
+
+impl BuiltinSky {
+    pub const ALL: [Self; 3] = [Self::Daylight, Self::Sunset, Self::Studio];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Daylight => "Daylight",
+            Self::Sunset => "Sunset",
+            Self::Studio => "Studio",
+        }
+    }
+
+    fn cpu_texture(self) -> CpuTexture {
+        match self {
+            Self::Daylight => gradient_sky(),
+            Self::Sunset => hdr_panorama("sunset", |direction| {
+                let elevation = direction.y;
+                let sky = if elevation > 0.0 {
+                    vec3(1.0, 0.45, 0.2).lerp(vec3(0.1, 0.15, 0.35), elevation.sqrt())
+                } else {
+                    vec3(0.6, 0.3, 0.15).lerp(vec3(0.05, 0.04, 0.04), (-elevation * 8.0).min(1.0))
+                };
+                let sun = vec3(0.0, 0.08, -1.0).normalize();
+                // A sharp disc with a glow around it.
+                let closeness = direction.dot(sun);
+                let glow = closeness.max(0.0).powf(64.0) * vec3(4.0, 2.0, 0.8);
+                let disc = if closeness > 0.9995 {
+                    vec3(40.0, 30.0, 18.0)
+                } else {
+                    Vec3::zero()
+                };
+                sky + glow + disc
+            }),
+            Self::Studio => hdr_panorama("studio", |direction| {
+                let walls = vec3(0.08, 0.08, 0.09) * (1.0 + direction.y * 0.5);
+                // Rectangular lights to the upper left and right of the default camera, facing the models.
+                let soft_box = |center: Vec3| {
+                    let side = vec3(center.z, 0.0, -center.x);
+                    let inside = direction.dot(center) > 0.0
+                        && (direction.y - 0.5).abs() < 0.25
+                        && direction.dot(side).abs() < 0.2;
+                    if inside {
+                        vec3(8.0, 8.0, 8.0)
+                    } else {
+                        Vec3::zero()
+                    }
+                };
+                walls + soft_box(vec3(-0.7, 0.0, 0.7)) + soft_box(vec3(0.7, 0.0, 0.7))
+            }),
+        }
+    }
+}
+
+/// A procedural panorama of a blue sky fading to a bright horizon above a dark ground.
+fn gradient_sky() -> CpuTexture {
+    let (width, height) = (256, 128);
+    let zenith = vec3(70.0, 120.0, 200.0);
+    let horizon = vec3(210.0, 225.0, 240.0);
+    let ground = vec3(70.0, 65.0, 60.0);
+
+    let row = |y: u32| {
+        // The elevation goes from 1 at the top row to -1 at the bottom row.
+        let elevation = 1.0 - 2.0 * (y as f32 + 0.5) / height as f32;
+        let color = if elevation > 0.0 {
+            horizon.lerp(zenith, elevation.sqrt())
+        } else {
+            horizon.lerp(ground, (-elevation * 8.0).min(1.0))
+        };
+        [color.x as u8, color.y as u8, color.z as u8, 255]
+    };
+    let data = (0..height)
+        .flat_map(|y| std::iter::repeat_n(row(y), width as usize))
+        .collect();
+
+    CpuTexture {
+        name: "sky".to_owned(),
+        data: TextureData::RgbaU8(data),
+        width,
+        height,
+        ..Default::default()
+    }
+}
+
+/// An equirectangular HDR panorama with the color `color` returns for each direction, the top row looking up.
+fn hdr_panorama(name: &str, color: impl Fn(Vec3) -> Vec3) -> CpuTexture {
+    let (width, height) = (256, 128);
+    let data = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            // Around the y-axis the way three-d maps the panorama onto the cube map, from -x through -z.
+            let azimuth = ((x as f32 + 0.5) / width as f32 - 0.5) * std::f32::consts::TAU;
+            let polar = (y as f32 + 0.5) / height as f32 * std::f32::consts::PI;
+            let direction = vec3(
+                polar.sin() * azimuth.cos(),
+                polar.cos(),
+                polar.sin() * azimuth.sin(),
+            );
+            color(direction).into()
+        })
+        .collect();
+    CpuTexture {
+        name: name.to_owned(),
+        data: TextureData::RgbF32(data),
+        width,
+        height,
+        ..Default::default()
+    }
+}
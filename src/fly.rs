@@ -0,0 +1,52 @@
+use three_d::{vec3, Camera, InnerSpace};
+
+use crate::orbit::{self, OrbitInput};
+
+/// First person navigation, looking around by dragging and moving with WASD, Q and E.
+pub struct FlyControl {
+    /// Movement speed in units per second.
+    pub speed: f32,
+}
+
+impl FlyControl {
+    const LOOK_SPEED: f32 = 0.005;
+    const SPRINT_FACTOR: f32 = 4.0;
+    const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+    pub fn new(speed: f32) -> Self {
+        Self { speed }
+    }
+
+    /// Moves the target along with the camera, so switching back to orbiting continues from the new view.
+    pub fn handle_input(&self, camera: &mut Camera, input: &OrbitInput) {
+        let up = vec3(0.0, 1.0, 0.0);
+        let direction = camera.view_direction();
+        let distance = (camera.target() - camera.position()).magnitude();
+
+        let yaw = direction.x.atan2(direction.z) - input.orbit.x * Self::LOOK_SPEED;
+        let pitch = (direction.y.asin() - input.orbit.y * Self::LOOK_SPEED)
+            .clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+        let direction = vec3(
+            pitch.cos() * yaw.sin(),
+            pitch.sin(),
+            pitch.cos() * yaw.cos(),
+        );
+        let right = direction.cross(up).normalize();
+        // Moving up and down stays vertical, only the view rolls.
+        let roll = orbit::roll_of(camera) + input.roll;
+
+        let speed = if input.sprint {
+            self.speed * Self::SPRINT_FACTOR
+        } else {
+            self.speed
+        };
+        let m = input.movement;
+        let position =
+            camera.position() + (right * m.x + up * m.y + direction * m.z) * speed * input.dt;
+        camera.set_view(
+            position,
+            position + direction * distance,
+            orbit::rolled_up(direction, roll),
+        );
+    }
+}
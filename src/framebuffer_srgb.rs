@@ -0,0 +1,101 @@
+use eframe::egui_glow::glow::{self, HasContext};
+
+/// Overrides `GL_FRAMEBUFFER_SRGB` while the scene is painted into the canvas, for tracking down colors that come
+/// out too dark or banded on some drivers.
+///
+/// three-d's materials encode their output to sRGB themselves, so the colors are right with the GPU encoding
+/// disabled. Enabling it encodes them a second time on framebuffers storing sRGB, brightening them.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum FramebufferSrgb {
+    /// Leaves it as egui_glow set it, which is disabled.
+    #[default]
+    Unchanged,
+    Enabled,
+    Disabled,
+}
+
+impl FramebufferSrgb {
+    pub const ALL: [Self; 3] = [Self::Unchanged, Self::Enabled, Self::Disabled];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Unchanged => "Unchanged",
+            Self::Enabled => "Enabled",
+            Self::Disabled => "Disabled",
+        }
+    }
+
+    /// Sets `GL_FRAMEBUFFER_SRGB` for painting, returning the previous state for [`Self::restore`].
+    /// Does nothing where it can't be switched.
+    pub fn apply(self, gl: &glow::Context, support: &SrgbSupport) -> Option<bool> {
+        let enable = match self {
+            Self::Unchanged => return None,
+            Self::Enabled => true,
+            Self::Disabled => false,
+        };
+        if !support.write_control {
+            return None;
+        }
+        unsafe {
+            let previous = gl.is_enabled(glow::FRAMEBUFFER_SRGB);
+            set_enabled(gl, enable);
+            Some(previous)
+        }
+    }
+
+    /// Restores the state returned by [`Self::apply`], egui draws with what it set itself.
+    pub fn restore(gl: &glow::Context, previous: Option<bool>) {
+        if let Some(enabled) = previous {
+            unsafe { set_enabled(gl, enabled) };
+        }
+    }
+}
+
+unsafe fn set_enabled(gl: &glow::Context, enabled: bool) {
+    if enabled {
+        gl.enable(glow::FRAMEBUFFER_SRGB);
+    } else {
+        gl.disable(glow::FRAMEBUFFER_SRGB);
+    }
+}
+
+/// What the GL context supports of encoding the colors written to sRGB framebuffers, queried when the scene is
+/// set up. glow can't query the encoding of the framebuffer the canvas is painted into, only the switch.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct SrgbSupport {
+    /// Whether `GL_FRAMEBUFFER_SRGB` can be switched, which WebGL can't.
+    pub write_control: bool,
+    /// Whether the GPU encoded the colors written to sRGB framebuffers at the time.
+    pub enabled: bool,
+}
+
+impl SrgbSupport {
+    pub fn query(gl: &glow::Context) -> Self {
+        let version = gl.version();
+        let extensions = gl.supported_extensions();
+        // Core since desktop GL 3.0, and an extension on GLES, which encodes unless it's switched off.
+        let write_control = !cfg!(target_arch = "wasm32")
+            && ((!version.is_embedded && version.major >= 3)
+                || extensions.contains("GL_ARB_framebuffer_sRGB")
+                || extensions.contains("GL_EXT_framebuffer_sRGB")
+                || extensions.contains("GL_EXT_sRGB_write_control"));
+        let enabled = write_control && unsafe { gl.is_enabled(glow::FRAMEBUFFER_SRGB) };
+        Self {
+            write_control,
+            enabled,
+        }
+    }
+}
+
+impl std::fmt::Display for SrgbSupport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.write_control, self.enabled) {
+            (false, _) => write!(f, "GL_FRAMEBUFFER_SRGB isn't supported"),
+            (true, enabled) => write!(
+                f,
+                "GL_FRAMEBUFFER_SRGB is {}",
+                if enabled { "enabled" } else { "disabled" }
+            ),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use eframe::egui_glow::glow;
+
+#[cfg(not(target_arch = "wasm32"))]
+use khronos_egl as egl;
+
+use crate::custom3d::Custom3d;
+
+/// Renders the built-in triangle from the default camera without a window, returning `width * height` RGBA pixels
+/// from top to bottom.
+///
+/// The glow context can come from any offscreen context, like a surfaceless EGL context on a software rasterizer,
+/// and has to be current on the calling thread.
+pub fn render_triangle(
+    gl: &Arc<glow::Context>,
+    width: u32,
+    height: u32,
+) -> Result<Vec<[u8; 4]>, String> {
+    Ok(headless_scene(gl)?.render_to_pixels(width, height))
+}
+
+/// The built-in triangle without the grid, which would otherwise cut through the middle of it, for rendering
+/// without a window like [`render_triangle`].
+pub fn headless_scene(gl: &Arc<glow::Context>) -> Result<Custom3d, String> {
+    let mut custom_3d = Custom3d::new(gl)?;
+    custom_3d.show_grid = false;
+    Ok(custom_3d)
+}
+
+/// An OpenGL ES 3 context without any surface, current on the thread that created it.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct HeadlessGl {
+    pub gl: Arc<glow::Context>,
+    // Keeps libEGL loaded for as long as the function pointers in `gl` are used.
+    _egl: egl::DynamicInstance<egl::EGL1_5>,
+}
+
+/// Creates a context on the surfaceless Mesa platform, which works without a display, e.g. with llvmpipe in CI.
+/// Returns `None` when EGL or the platform isn't available, so tests can be skipped instead of failing and
+/// `--bench` opens a window instead.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn headless_gl() -> Option<HeadlessGl> {
+    const PLATFORM_SURFACELESS_MESA: egl::Enum = 0x31DD;
+
+    let lib = unsafe { libloading::Library::new("libEGL.so.1") }.ok()?;
+    let egl = unsafe { egl::DynamicInstance::<egl::EGL1_5>::load_required_from(lib) }.ok()?;
+    let display = unsafe {
+        egl.get_platform_display(
+            PLATFORM_SURFACELESS_MESA,
+            egl::DEFAULT_DISPLAY,
+            &[egl::ATTRIB_NONE],
+        )
+    }
+    .ok()?;
+    egl.initialize(display).ok()?;
+    egl.bind_api(egl::OPENGL_ES_API).ok()?;
+    let config = egl
+        .choose_first_config(
+            display,
+            &[
+                egl::SURFACE_TYPE,
+                0,
+                egl::RENDERABLE_TYPE,
+                egl::OPENGL_ES3_BIT,
+                egl::NONE,
+            ],
+        )
+        .ok()??;
+    let context = egl
+        .create_context(
+            display,
+            config,
+            None,
+            &[egl::CONTEXT_MAJOR_VERSION, 3, egl::NONE],
+        )
+        .ok()?;
+    egl.make_current(display, None, None, Some(context)).ok()?;
+
+    let gl = unsafe {
+        glow::Context::from_loader_function(|name| {
+            egl.get_proc_address(name)
+                .map_or(std::ptr::null(), |f| f as *const _)
+        })
+    };
+    Some(HeadlessGl {
+        gl: Arc::new(gl),
+        _egl: egl,
+    })
+}
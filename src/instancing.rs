@@ -0,0 +1,46 @@
+use three_d::*;
+
+/// Half the side length of the cube the instances are scattered in.
+const SPREAD: f32 = 3.0;
+
+/// Scatters `count` copies at reproducible pseudo random positions and orientations.
+/// The first instance stays at the origin, so a count of one shows the plain model.
+pub fn random_instances(count: u32) -> Instances {
+    // Shrink the instances as their number grows, so they fill the same volume without becoming a solid blob.
+    let scale = (SPREAD / (count as f32).cbrt()).min(1.0);
+    let transformations = (0..count)
+        .map(|i| {
+            if i == 0 {
+                return Mat4::from_scale(scale);
+            }
+            let mut random = (0..7).map(|j| hash_to_unit(i * 7 + j) * 2.0 - 1.0);
+            let mut next = || random.next().unwrap();
+            let position = vec3(next(), next(), next());
+            let axis = vec3(next(), next(), next());
+            let axis = if axis.magnitude() > 0.001 {
+                axis.normalize()
+            } else {
+                vec3(0.0, 1.0, 0.0)
+            };
+            let angle = radians(next() * std::f32::consts::PI);
+            Mat4::from_translation(position * SPREAD)
+                * Mat4::from_axis_angle(axis, angle)
+                * Mat4::from_scale(scale)
+        })
+        .collect();
+    Instances {
+        transformations,
+        ..Default::default()
+    }
+}
+
+/// Maps an integer to a pseudo random number in `0.0..1.0`, avoiding a dependency on `rand` (which needs extra setup on the web).
+pub(crate) fn hash_to_unit(mut x: u32) -> f32 {
+    // The integer hash from https://nullprogram.com/blog/2018/07/31/
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846ca68b);
+    x ^= x >> 16;
+    (x >> 8) as f32 / (1 << 24) as f32
+}
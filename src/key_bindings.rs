@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use eframe::egui;
+use egui::Key;
+use serde::{Deserialize, Serialize};
+
+/// What a key does in the 3D view.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    MoveDown,
+    MoveUp,
+    FrameSelection,
+    ResetView,
+    /// Snaps to the front view, or the back view with ctrl held.
+    FrontView,
+    /// Snaps to the right view, or the left view with ctrl held.
+    SideView,
+    /// Snaps to the top view, or the bottom view with ctrl held.
+    TopView,
+    /// Nudges the selected object along the screen by its nudge step, ten steps with shift held.
+    NudgeLeft,
+    NudgeRight,
+    NudgeDown,
+    NudgeUp,
+    /// Nudges the selected object away from the camera.
+    NudgeAway,
+    /// Nudges the selected object towards the camera.
+    NudgeCloser,
+    /// Rolls the camera counterclockwise around its view direction while held.
+    RollLeft,
+    /// Rolls the camera clockwise around its view direction while held.
+    RollRight,
+}
+
+impl Action {
+    pub const ALL: [Action; 19] = [
+        Action::MoveForward,
+        Action::MoveBack,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveDown,
+        Action::MoveUp,
+        Action::FrameSelection,
+        Action::ResetView,
+        Action::FrontView,
+        Action::SideView,
+        Action::TopView,
+        Action::NudgeLeft,
+        Action::NudgeRight,
+        Action::NudgeDown,
+        Action::NudgeUp,
+        Action::NudgeAway,
+        Action::NudgeCloser,
+        Action::RollLeft,
+        Action::RollRight,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveForward => "Move forward",
+            Action::MoveBack => "Move back",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::MoveDown => "Move down",
+            Action::MoveUp => "Move up",
+            Action::FrameSelection => "Frame selection",
+            Action::ResetView => "Reset view",
+            Action::FrontView => "Front view",
+            Action::SideView => "Side view",
+            Action::TopView => "Top view",
+            Action::NudgeLeft => "Nudge left",
+            Action::NudgeRight => "Nudge right",
+            Action::NudgeDown => "Nudge down",
+            Action::NudgeUp => "Nudge up",
+            Action::NudgeAway => "Nudge away",
+            Action::NudgeCloser => "Nudge closer",
+            Action::RollLeft => "Roll left",
+            Action::RollRight => "Roll right",
+        }
+    }
+
+    fn default_key(self) -> Key {
+        match self {
+            Action::MoveForward => Key::W,
+            Action::MoveBack => Key::S,
+            Action::MoveLeft => Key::A,
+            Action::MoveRight => Key::D,
+            Action::MoveDown => Key::Q,
+            Action::MoveUp => Key::E,
+            Action::FrameSelection => Key::F,
+            Action::ResetView => Key::Home,
+            // The numpad keys of Blender, egui doesn't tell them apart from the number row.
+            Action::FrontView => Key::Num1,
+            Action::SideView => Key::Num3,
+            Action::TopView => Key::Num7,
+            Action::NudgeLeft => Key::ArrowLeft,
+            Action::NudgeRight => Key::ArrowRight,
+            Action::NudgeDown => Key::ArrowDown,
+            Action::NudgeUp => Key::ArrowUp,
+            Action::NudgeAway => Key::PageUp,
+            Action::NudgeCloser => Key::PageDown,
+            Action::RollLeft => Key::Z,
+            Action::RollRight => Key::C,
+        }
+    }
+}
+
+/// The key of each action, saved as JSON with the keys by name.
+#[derive(Clone, PartialEq, Debug)]
+pub struct KeyBindings {
+    keys: HashMap<Action, Key>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_key()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// The keys an action can be bound to. egui can't look keys up by name, so saved bindings are matched against
+    /// these, and keys that depend on the keyboard layout are left out like egui does.
+    pub const BINDABLE_KEYS: [Key; 58] = [
+        Key::A,
+        Key::B,
+        Key::C,
+        Key::D,
+        Key::E,
+        Key::F,
+        Key::G,
+        Key::H,
+        Key::I,
+        Key::J,
+        Key::K,
+        Key::L,
+        Key::M,
+        Key::N,
+        Key::O,
+        Key::P,
+        Key::Q,
+        Key::R,
+        Key::S,
+        Key::T,
+        Key::U,
+        Key::V,
+        Key::W,
+        Key::X,
+        Key::Y,
+        Key::Z,
+        Key::Num0,
+        Key::Num1,
+        Key::Num2,
+        Key::Num3,
+        Key::Num4,
+        Key::Num5,
+        Key::Num6,
+        Key::Num7,
+        Key::Num8,
+        Key::Num9,
+        Key::F1,
+        Key::F2,
+        Key::F3,
+        Key::F4,
+        Key::F5,
+        Key::F6,
+        Key::F7,
+        Key::F8,
+        Key::F9,
+        Key::F10,
+        Key::F11,
+        Key::F12,
+        Key::ArrowUp,
+        Key::ArrowDown,
+        Key::ArrowLeft,
+        Key::ArrowRight,
+        Key::Space,
+        Key::Home,
+        Key::End,
+        Key::PageUp,
+        Key::PageDown,
+        Key::Insert,
+    ];
+
+    pub fn key(&self, action: Action) -> Key {
+        self.keys
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_key())
+    }
+
+    /// Binds `key` to `action`. Another action bound to the same key gets the previous key of `action`,
+    /// so no two actions end up sharing a key.
+    pub fn set(&mut self, action: Action, key: Key) {
+        let previous = self.key(action);
+        for other in self.keys.values_mut() {
+            if *other == key {
+                *other = previous;
+            }
+        }
+        self.keys.insert(action, key);
+    }
+
+    /// Whether the key of `action` is held down.
+    pub fn down(&self, input: &egui::InputState, action: Action) -> bool {
+        input.key_down(self.key(action))
+    }
+
+    /// Whether the key of `action` was pressed this frame.
+    pub fn pressed(&self, input: &egui::InputState, action: Action) -> bool {
+        input.key_pressed(self.key(action))
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        let names: HashMap<Action, &str> = self
+            .keys
+            .iter()
+            .map(|(action, key)| (*action, key.name()))
+            .collect();
+        serde_json::to_string_pretty(&names)
+    }
+
+    /// Reads bindings written by [`Self::to_json`]. Actions that are missing or bound to an unknown key keep
+    /// their default key.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let names: HashMap<Action, String> = serde_json::from_str(json)?;
+        let mut bindings = Self::default();
+        for (action, name) in names {
+            if let Some(key) = Self::BINDABLE_KEYS
+                .into_iter()
+                .find(|key| key.name() == name)
+            {
+                bindings.set(action, key);
+            }
+        }
+        Ok(bindings)
+    }
+}
@@ -0,0 +1,90 @@
+mod ambient_occlusion;
+mod app;
+#[cfg(not(target_arch = "wasm32"))]
+mod benchmark;
+mod camera_animator;
+#[cfg(not(target_arch = "wasm32"))]
+mod camera_state;
+mod canvas;
+mod color_interpolation;
+mod culling;
+mod custom3d;
+mod debug_draw;
+mod debug_view;
+mod depth_of_field;
+mod effect;
+mod environment;
+mod fly;
+mod framebuffer_srgb;
+mod headless;
+mod instancing;
+mod key_bindings;
+mod lights;
+mod lines;
+mod orbit;
+mod outline;
+#[cfg(target_arch = "wasm32")]
+mod page_scroll;
+mod particles;
+mod picking;
+mod primitives;
+#[cfg(not(target_arch = "wasm32"))]
+mod project;
+mod pulse;
+mod rotation;
+mod scene_object;
+mod screenshot;
+mod simplify;
+mod stats;
+mod texture_settings;
+mod tone_mapping;
+mod translate_gizmo;
+mod view;
+
+pub use ambient_occlusion::AmbientOcclusion;
+pub use app::MyApp;
+#[cfg(not(target_arch = "wasm32"))]
+pub use benchmark::{Benchmark, BenchmarkReport};
+pub use canvas::ThreeDCanvas;
+pub use culling::Frustum;
+pub use custom3d::{Custom3d, Navigation, ProjectionMode, Shading, ViewPreset};
+pub use debug_draw::DebugDraw;
+pub use debug_view::DebugView;
+pub use depth_of_field::DepthOfField;
+pub use environment::BuiltinSky;
+pub use framebuffer_srgb::{FramebufferSrgb, SrgbSupport};
+#[cfg(not(target_arch = "wasm32"))]
+pub use headless::{headless_gl, HeadlessGl};
+pub use headless::{headless_scene, render_triangle};
+pub use key_bindings::{Action, KeyBindings};
+pub use lights::SceneLight;
+pub use particles::ParticleFountain;
+pub use picking::DepthSample;
+pub use primitives::Primitive;
+#[cfg(not(target_arch = "wasm32"))]
+pub use project::MissingObject;
+pub use pulse::{PulseMaterial, TIME_UNIFORM};
+pub use rotation::advance_angle;
+pub use scene_object::{ObjectSource, SceneObject, Transform};
+pub use screenshot::TurntableRequest;
+pub use simplify::LevelOfDetail;
+pub use stats::{ImportStats, MeshStats, RenderStats};
+pub use texture_settings::TextureSettings;
+pub use view::ThreeDView;
+
+/// Runs the demo in the canvas with the given id, three-d needs the WebGL2 context eframe creates.
+#[cfg(target_arch = "wasm32")]
+pub async fn start(canvas_id: &str) -> Result<(), wasm_bindgen::JsValue> {
+    eframe::WebLogger::init(log::LevelFilter::Debug).ok();
+    let web_options = eframe::WebOptions {
+        webgl_context_option: eframe::WebGlContextOption::WebGl2,
+        ..Default::default()
+    };
+    eframe::WebRunner::new()
+        .start(
+            canvas_id,
+            web_options,
+            Box::new(|cc| Box::new(MyApp::new(cc))),
+        )
+        .await
+}
@@ -0,0 +1,112 @@
+use three_d::*;
+
+/// A light added in the UI, on top of the ambient and directional light.
+pub enum SceneLight {
+    Point(PointLight),
+    /// Shines at the models from its position.
+    Spot(SpotLight),
+}
+
+impl SceneLight {
+    /// Falls off quickly enough to see the attenuation across the triangle.
+    const ATTENUATION: Attenuation = Attenuation {
+        constant: 1.0,
+        linear: 0.5,
+        quadratic: 1.0,
+    };
+    /// Radius of the spheres marking the lights.
+    const MARKER_RADIUS: f32 = 0.03;
+
+    pub fn point(context: &Context) -> Self {
+        Self::Point(PointLight::new(
+            context,
+            2.0,
+            Srgba::new(255, 200, 150, 255),
+            &vec3(0.6, 0.4, 0.6),
+            Self::ATTENUATION,
+        ))
+    }
+
+    pub fn spot(context: &Context) -> Self {
+        Self::Spot(SpotLight::new(
+            context,
+            4.0,
+            Srgba::WHITE,
+            &vec3(0.0, 0.8, 0.8),
+            &vec3(0.0, -1.0, -1.0),
+            degrees(20.0),
+            Self::ATTENUATION,
+        ))
+    }
+
+    /// `count` dim point lights evenly spaced on a horizontal circle, going around the hues.
+    pub fn ring(context: &Context, count: usize, center: Vec3, radius: f32) -> Vec<Self> {
+        (0..count)
+            .map(|i| {
+                let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+                let channel =
+                    |offset: f32| ((0.5 + 0.5 * (angle - offset).cos()) * 255.0).round() as u8;
+                let third = std::f32::consts::TAU / 3.0;
+                Self::Point(PointLight::new(
+                    context,
+                    0.5,
+                    Srgba::new(channel(0.0), channel(third), channel(2.0 * third), 255),
+                    &(center + vec3(angle.cos(), 0.0, angle.sin()) * radius),
+                    Self::ATTENUATION,
+                ))
+            })
+            .collect()
+    }
+
+    pub fn light(&self) -> &dyn Light {
+        match self {
+            Self::Point(light) => light,
+            Self::Spot(light) => light,
+        }
+    }
+
+    /// The position, color and intensity, which all lights have.
+    pub fn common_mut(&mut self) -> (&mut Vec3, &mut Srgba, &mut f32) {
+        match self {
+            Self::Point(light) => (&mut light.position, &mut light.color, &mut light.intensity),
+            Self::Spot(light) => (&mut light.position, &mut light.color, &mut light.intensity),
+        }
+    }
+
+    /// Turns a spot light towards `target`, point lights shine everywhere anyway.
+    pub fn aim_at(&mut self, target: Vec3) {
+        if let Self::Spot(light) = self {
+            if light.position != target {
+                light.direction = target - light.position;
+            }
+        }
+    }
+}
+
+/// Small spheres at the positions of the lights, tinted by their colors.
+pub fn marker_model(context: &Context) -> Gm<InstancedMesh, ColorMaterial> {
+    Gm::new(
+        InstancedMesh::new(context, &Instances::default(), &CpuMesh::sphere(8)),
+        ColorMaterial::default(),
+    )
+}
+
+pub fn marker_instances(lights: &[SceneLight]) -> Instances {
+    let (transformations, colors) = lights
+        .iter()
+        .map(|light| match light {
+            SceneLight::Point(light) => (light.position, light.color),
+            SceneLight::Spot(light) => (light.position, light.color),
+        })
+        .map(|(position, color)| {
+            let transformation =
+                Mat4::from_translation(position) * Mat4::from_scale(SceneLight::MARKER_RADIUS);
+            (transformation, color)
+        })
+        .unzip();
+    Instances {
+        transformations,
+        colors: Some(colors),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,442 @@
+use three_d::*;
+
+/// Line segments with a width in pixels, drawn as camera-facing quads with smooth edges.
+pub type LineModel = Gm<Lines, LineMaterial>;
+
+pub fn line_model(
+    context: &Context,
+    segments: &[[Vec3; 2]],
+    color: Srgba,
+    width: f32,
+) -> LineModel {
+    Gm::new(
+        Lines::new(context, segments, None, width),
+        LineMaterial {
+            color,
+            depth_test: DepthTest::Less,
+        },
+    )
+}
+
+/// Lines like [`line_model`], but with a color for each segment.
+pub fn colored_line_model(
+    context: &Context,
+    segments: &[[Vec3; 2]],
+    colors: &[Srgba],
+    width: f32,
+) -> LineModel {
+    Gm::new(
+        Lines::new(context, segments, Some(colors), width),
+        LineMaterial {
+            color: Srgba::WHITE,
+            depth_test: DepthTest::Less,
+        },
+    )
+}
+
+/// Moves the lines of the model to new segments, keeping the color and width.
+pub fn set_segments(model: &mut LineModel, segments: &[[Vec3; 2]]) {
+    model.geometry.set_segments(segments, None);
+}
+
+/// Moves the lines of a [`colored_line_model`] to new segments with new colors, keeping the width.
+pub fn set_colored_segments(model: &mut LineModel, segments: &[[Vec3; 2]], colors: &[Srgba]) {
+    model.geometry.set_segments(segments, Some(colors));
+}
+
+/// The geometry of [`LineModel`]: one instance of a quad for each segment, stretched between the projected
+/// end points and widened in screen space so the lines keep their width at any distance.
+pub struct Lines {
+    context: Context,
+    corners: VertexBuffer,
+    starts: InstanceBuffer,
+    ends: InstanceBuffer,
+    colors: Option<InstanceBuffer>,
+    count: u32,
+    aabb: AxisAlignedBoundingBox,
+    transformation: Mat4,
+    /// Width of the lines in pixels.
+    pub width: f32,
+}
+
+impl Lines {
+    fn new(
+        context: &Context,
+        segments: &[[Vec3; 2]],
+        colors: Option<&[Srgba]>,
+        width: f32,
+    ) -> Self {
+        // Along the segment from 0 at the start to 1 at the end, and across it from -1 to 1.
+        let corners = [
+            vec2(0.0, -1.0),
+            vec2(1.0, -1.0),
+            vec2(1.0, 1.0),
+            vec2(1.0, 1.0),
+            vec2(0.0, 1.0),
+            vec2(0.0, -1.0),
+        ];
+        let mut lines = Self {
+            context: context.clone(),
+            corners: VertexBuffer::new_with_data(context, &corners),
+            starts: InstanceBuffer::new(context),
+            ends: InstanceBuffer::new(context),
+            colors: None,
+            count: 0,
+            aabb: AxisAlignedBoundingBox::EMPTY,
+            transformation: Mat4::identity(),
+            width,
+        };
+        lines.set_segments(segments, colors);
+        lines
+    }
+
+    fn set_segments(&mut self, segments: &[[Vec3; 2]], colors: Option<&[Srgba]>) {
+        // Keep the colors paired with their segments while leaving out the empty ones.
+        let visible: Vec<_> = (0..segments.len())
+            .filter(|i| segments[*i][0] != segments[*i][1])
+            .collect();
+        let starts: Vec<_> = visible.iter().map(|i| segments[*i][0]).collect();
+        let ends: Vec<_> = visible.iter().map(|i| segments[*i][1]).collect();
+        self.starts.fill(&starts);
+        self.ends.fill(&ends);
+        self.colors = colors.map(|colors| {
+            let colors: Vec<_> = visible
+                .iter()
+                .map(|i| colors[*i].to_linear_srgb())
+                .collect();
+            InstanceBuffer::new_with_data(&self.context, &colors)
+        });
+        self.count = visible.len() as u32;
+        self.aabb = AxisAlignedBoundingBox::new_with_positions(&[starts, ends].concat());
+    }
+
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+    }
+}
+
+impl Geometry for Lines {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        if self.count == 0 {
+            return;
+        }
+        let viewport = camera.viewport();
+        program.use_uniform("view", camera.view());
+        program.use_uniform("projection", camera.projection());
+        program.use_uniform("modelMatrix", self.transformation);
+        program.use_uniform("zNear", camera.z_near());
+        program.use_uniform(
+            "viewportSize",
+            vec2(viewport.width as f32, viewport.height as f32),
+        );
+        program.use_uniform("lineWidth", self.width);
+        program.use_vertex_attribute("corner", &self.corners);
+        program.use_instance_attribute("start", &self.starts);
+        program.use_instance_attribute("end", &self.ends);
+        if let Some(colors) = self.colors.as_ref().filter(|_| attributes.color) {
+            program.use_instance_attribute("instanceColor", colors);
+        }
+        program.draw_arrays_instanced(
+            render_states,
+            viewport,
+            self.corners.vertex_count(),
+            self.count,
+        );
+    }
+
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        let mut source = String::new();
+        if required_attributes.position {
+            source.push_str("#define USE_POSITION\n");
+        }
+        if required_attributes.color {
+            source.push_str("#define USE_COLOR\n");
+            if self.colors.is_some() {
+                source.push_str("#define USE_INSTANCE_COLOR\n");
+            }
+        }
+        source.push_str(
+            "
+            uniform mat4 view;
+            uniform mat4 projection;
+            uniform mat4 modelMatrix;
+            uniform float zNear;
+            uniform vec2 viewportSize;
+            uniform float lineWidth;
+
+            in vec2 corner;
+            in vec3 start;
+            in vec3 end;
+
+            #ifdef USE_POSITION
+            out vec3 pos;
+            #endif
+            #ifdef USE_COLOR
+            out vec4 col;
+            #endif
+            #ifdef USE_INSTANCE_COLOR
+            in vec4 instanceColor;
+            #endif
+            // Distance from the middle of the line and half its width, in pixels.
+            out vec2 linePixels;
+
+            void main()
+            {
+                vec4 worldStart = modelMatrix * vec4(start, 1.0);
+                vec4 worldEnd = modelMatrix * vec4(end, 1.0);
+                vec4 a = view * worldStart;
+                vec4 b = view * worldEnd;
+
+                // Cut the segment at the near plane, the projection of points behind the camera is mirrored.
+                float near = -zNear;
+                if (a.z > near && b.z > near) {
+                    gl_Position = vec4(0.0, 0.0, 2.0, 1.0);
+                    return;
+                }
+                if (a.z > near) {
+                    a = mix(a, b, (a.z - near) / (a.z - b.z));
+                }
+                if (b.z > near) {
+                    b = mix(b, a, (b.z - near) / (b.z - a.z));
+                }
+
+                vec4 clipStart = projection * a;
+                vec4 clipEnd = projection * b;
+                vec2 screenStart = clipStart.xy / clipStart.w * 0.5 * viewportSize;
+                vec2 screenEnd = clipEnd.xy / clipEnd.w * 0.5 * viewportSize;
+                vec2 direction = screenEnd - screenStart;
+                direction = length(direction) > 0.0 ? normalize(direction) : vec2(1.0, 0.0);
+                vec2 normal = vec2(-direction.y, direction.x);
+
+                // Half a pixel more on each side for the smoothed edge, and square caps closing the gaps at corners.
+                float halfWidth = 0.5 * lineWidth + 1.0;
+                vec2 offset = normal * corner.y * halfWidth + direction * (corner.x * 2.0 - 1.0) * 0.5 * lineWidth;
+                vec4 clip = corner.x < 0.5 ? clipStart : clipEnd;
+                clip.xy += offset / viewportSize * 2.0 * clip.w;
+                gl_Position = clip;
+                linePixels = vec2(corner.y * halfWidth, 0.5 * lineWidth);
+
+                #ifdef USE_POSITION
+                pos = mix(worldStart.xyz, worldEnd.xyz, corner.x);
+                #endif
+                #ifdef USE_COLOR
+                #ifdef USE_INSTANCE_COLOR
+                col = instanceColor;
+                #else
+                col = vec4(1.0);
+                #endif
+                #endif
+            }
+            ",
+        );
+        source
+    }
+
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 10;
+        if required_attributes.position {
+            id |= 0b1u16;
+        }
+        if required_attributes.color {
+            id |= 0b1u16 << 1;
+            if self.colors.is_some() {
+                id |= 0b1u16 << 2;
+            }
+        }
+        id
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, self, material, lights)
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let mut aabb = self.aabb;
+        aabb.transform(&self.transformation);
+        aabb
+    }
+}
+
+/// Colors [`Lines`], fading out the outermost pixel on each side so the edges are smooth.
+#[derive(Clone)]
+pub struct LineMaterial {
+    pub color: Srgba,
+    /// `DepthTest::Always` draws the lines over everything else.
+    pub depth_test: DepthTest,
+}
+
+impl Material for LineMaterial {
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        format!(
+            "{}
+            uniform vec4 lineColor;
+
+            in vec4 col;
+            in vec2 linePixels;
+
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                float coverage = clamp(linePixels.y + 0.5 - abs(linePixels.x), 0.0, 1.0);
+                if (coverage <= 0.0) {{
+                    discard;
+                }}
+                outColor = lineColor * col;
+                outColor.a *= coverage;
+                outColor.rgb = color_mapping(outColor.rgb);
+            }}
+            ",
+            ColorMapping::fragment_shader_source(),
+        )
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            color: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.color_mapping.use_uniforms(program);
+        program.use_uniform("lineColor", self.color.to_linear_srgb());
+    }
+
+    fn render_states(&self) -> RenderStates {
+        // The quads face either way depending on the direction of their segment.
+        RenderStates {
+            depth_test: self.depth_test,
+            cull: Cull::None,
+            blend: Blend::TRANSPARENCY,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1u16 << 9
+    }
+}
+
+/// The unique triangle edges of a mesh, for rendering it as a wireframe.
+pub fn edge_segments(mesh: &CpuMesh) -> Vec<[Vec3; 2]> {
+    let positions = mesh.positions.to_f32();
+    let mut edges = std::collections::HashSet::new();
+    mesh.for_each_triangle(|a, b, c| {
+        for (start, end) in [(a, b), (b, c), (c, a)] {
+            // Triangles sharing an edge list it in opposite directions, so store it in one order only.
+            edges.insert((start.min(end), start.max(end)));
+        }
+    });
+    edges
+        .into_iter()
+        .map(|(start, end)| [positions[start], positions[end]])
+        .collect()
+}
+
+/// Segments `length` long from each vertex of a mesh along its normal, each colored by its direction with
+/// the x, y and z components mapped from -1..1 to red, green and blue. Empty if the mesh has no normals.
+pub fn normal_segments(mesh: &CpuMesh, length: f32) -> (Vec<[Vec3; 2]>, Vec<Srgba>) {
+    let Some(normals) = &mesh.normals else {
+        return (Vec::new(), Vec::new());
+    };
+    let channel = |value: f32| ((value * 0.5 + 0.5).clamp(0.0, 1.0) * 255.0).round() as u8;
+    mesh.positions
+        .to_f32()
+        .into_iter()
+        .zip(normals)
+        .filter(|(_, normal)| normal.magnitude2() > 0.0)
+        .map(|(position, normal)| {
+            let normal = normal.normalize();
+            (
+                [position, position + normal * length],
+                Srgba::new(channel(normal.x), channel(normal.y), channel(normal.z), 255),
+            )
+        })
+        .unzip()
+}
+
+/// The 12 edges of a box.
+pub fn box_segments(aabb: &AxisAlignedBoundingBox) -> Vec<[Vec3; 2]> {
+    let (min, max) = (aabb.min(), aabb.max());
+    let corner = |i: usize| {
+        vec3(
+            if i & 1 == 0 { min.x } else { max.x },
+            if i & 2 == 0 { min.y } else { max.y },
+            if i & 4 == 0 { min.z } else { max.z },
+        )
+    };
+    cube_segments(corner)
+}
+
+/// The 12 edges of what `camera` sees, from the near plane to the far plane.
+pub fn frustum_segments(camera: &Camera) -> Vec<[Vec3; 2]> {
+    // Always succeeds for a valid camera, its view and projection are invertible.
+    let inverse = (camera.projection() * camera.view())
+        .invert()
+        .unwrap_or(Mat4::identity());
+    let corner = |i: usize| {
+        let sign = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+        let position = inverse * vec4(sign(1), sign(2), sign(4), 1.0);
+        position.truncate() / position.w
+    };
+    cube_segments(corner)
+}
+
+/// The edges between the corners of a cube, with the bits of the index of a corner choosing its side on each axis.
+fn cube_segments(corner: impl Fn(usize) -> Vec3) -> Vec<[Vec3; 2]> {
+    // Corners whose indices differ in exactly one bit share an edge.
+    (0..8)
+        .flat_map(|i| [1, 2, 4].map(move |bit| (i, i | bit)))
+        .filter(|(i, j)| i != j)
+        .map(|(i, j)| [corner(i), corner(j)])
+        .collect()
+}
+
+/// Segments of a grid on the XZ plane covering `-extent..=extent` in both directions.
+pub fn grid_segments(extent: f32, spacing: f32) -> Vec<[Vec3; 2]> {
+    let steps = (extent / spacing).floor() as i32;
+    (-steps..=steps)
+        .flat_map(|i| {
+            let offset = i as f32 * spacing;
+            [
+                [vec3(offset, 0.0, -extent), vec3(offset, 0.0, extent)],
+                [vec3(-extent, 0.0, offset), vec3(extent, 0.0, offset)],
+            ]
+        })
+        .collect()
+}
@@ -0,0 +1,263 @@
+use std::f32::consts::{PI, TAU};
+
+use eframe::egui;
+use three_d::{vec3, Camera, InnerSpace, Vec3};
+use three_d_asset::ProjectionType;
+
+use crate::key_bindings::{Action, KeyBindings};
+
+/// Camera input gathered from egui for a single frame.
+#[derive(Clone, Copy)]
+pub struct OrbitInput {
+    /// Drag delta of the primary button, in points.
+    pub orbit: egui::Vec2,
+    /// Drag delta of the secondary or middle button, or the primary button with shift held,
+    /// as a fraction of the canvas height.
+    pub pan: egui::Vec2,
+    /// Vertical scroll delta, in points.
+    pub zoom: f32,
+    /// Factor a pinch on a touchscreen or trackpad, or ctrl-scrolling, zooms in by, 1 when not zooming.
+    pub pinch: f32,
+    /// Keyboard movement relative to the camera, x to the right, y up and z forward, each -1, 0 or 1.
+    pub movement: Vec3,
+    /// Radians to roll the camera by counterclockwise around its view direction.
+    pub roll: f32,
+    /// Whether shift is held to move faster.
+    pub sprint: bool,
+    /// Seconds the movement lasts for.
+    pub dt: f32,
+}
+
+impl Default for OrbitInput {
+    fn default() -> Self {
+        Self {
+            orbit: egui::Vec2::ZERO,
+            pan: egui::Vec2::ZERO,
+            zoom: 0.0,
+            pinch: 1.0,
+            movement: vec3(0.0, 0.0, 0.0),
+            roll: 0.0,
+            sprint: false,
+            dt: 0.0,
+        }
+    }
+}
+
+impl OrbitInput {
+    /// With `page_scroll`, scrolling is left to the web page embedding the view and only zooms with ctrl held.
+    pub fn from_response(
+        ui: &egui::Ui,
+        response: &egui::Response,
+        key_bindings: &KeyBindings,
+        page_scroll: bool,
+    ) -> Self {
+        let mut input = Self::default();
+        let panning = response.dragged()
+            && ui.input(|i| {
+                i.pointer.secondary_down()
+                    || i.pointer.middle_down()
+                    || (i.pointer.primary_down() && i.modifiers.shift)
+            });
+        let height = response.rect.height().max(1.0);
+        let multi_touch = ui.input(|i| i.multi_touch()).filter(|_| response.hovered());
+        if let Some(touch) = multi_touch {
+            // Two fingers twist to orbit around the vertical axis and move together to pan. egui drags the pointer
+            // with the first finger as well, which mustn't orbit on top.
+            input.orbit = egui::vec2(touch.rotation_delta / OrbitControl::ORBIT_SPEED, 0.0);
+            input.pan = touch.translation_delta / height;
+        } else if panning {
+            input.pan = response.drag_delta() / height;
+        } else if response.dragged_by(egui::PointerButton::Primary) {
+            input.orbit = response.drag_delta();
+        }
+        if response.hovered() {
+            // With the page scrolling instead, zooming takes ctrl, which egui reports as zooming below.
+            if !page_scroll {
+                input.zoom = ui.input(|i| i.scroll_delta.y);
+            }
+            // Pinching with two fingers, also on trackpads, which egui reports as zooming like ctrl-scrolling.
+            input.pinch = ui.input(|i| i.zoom_delta());
+            // Only move while hovering the canvas, so typing into widgets doesn't move the camera.
+            ui.input(|i| {
+                let axis = |negative, positive| {
+                    key_bindings.down(i, positive) as i32 as f32
+                        - key_bindings.down(i, negative) as i32 as f32
+                };
+                input.movement = vec3(
+                    axis(Action::MoveLeft, Action::MoveRight),
+                    axis(Action::MoveDown, Action::MoveUp),
+                    axis(Action::MoveBack, Action::MoveForward),
+                );
+                input.sprint = i.modifiers.shift;
+                input.dt = i.stable_dt;
+                input.roll = axis(Action::RollRight, Action::RollLeft) * ROLL_SPEED * input.dt;
+            });
+        }
+        input
+    }
+
+    /// Whether there's any input that moves the camera.
+    pub fn is_active(&self) -> bool {
+        self.orbit != egui::Vec2::ZERO
+            || self.pan != egui::Vec2::ZERO
+            || self.zoom != 0.0
+            || self.pinch != 1.0
+            || self.movement != vec3(0.0, 0.0, 0.0)
+            || self.roll != 0.0
+    }
+}
+
+/// Radians per second the camera rolls by while a roll key is held.
+const ROLL_SPEED: f32 = PI / 2.0;
+
+/// The right and up directions of a camera looking along the normalized `direction` level with the horizon.
+fn level_axes(direction: Vec3) -> (Vec3, Vec3) {
+    let right = direction.cross(vec3(0.0, 1.0, 0.0));
+    if right.magnitude2() < 1e-8 {
+        // Looking straight up or down there's no horizon, so the top of the view faces away like in the top view.
+        let up = vec3(0.0, 0.0, direction.y.signum());
+        return (direction.cross(up), up);
+    }
+    let right = right.normalize();
+    (right, right.cross(direction))
+}
+
+/// The up direction of a camera looking along `direction`, rolled counterclockwise by `roll` radians from level with
+/// the horizon.
+pub(crate) fn rolled_up(direction: Vec3, roll: f32) -> Vec3 {
+    let (right, up) = level_axes(direction.normalize());
+    up * roll.cos() - right * roll.sin()
+}
+
+/// Radians the camera is rolled by counterclockwise from level with the horizon, the inverse of [`rolled_up`].
+pub(crate) fn roll_of(camera: &Camera) -> f32 {
+    let direction = camera.view_direction().normalize();
+    let (right, level_up) = level_axes(direction);
+    let up = camera.right_direction().cross(direction);
+    (-up.dot(right)).atan2(up.dot(level_up))
+}
+
+/// Orbits the camera around a target point, storing the view as yaw/pitch/distance
+/// so pitch can be clamped before it flips over the poles.
+pub struct OrbitControl {
+    pub target: Vec3,
+    /// Closest the camera can zoom towards the target.
+    pub min_distance: f32,
+    /// Furthest the camera can zoom away from the target.
+    pub max_distance: f32,
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+    /// Radians the camera is rolled by counterclockwise around its view direction.
+    roll: f32,
+}
+
+impl OrbitControl {
+    pub(crate) const ORBIT_SPEED: f32 = 0.01;
+    const ZOOM_SPEED: f32 = 0.002;
+    const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+    pub fn new(camera: &Camera) -> Self {
+        let target = *camera.target();
+        let offset = camera.position() - target;
+        let distance = offset.magnitude();
+        let pitch = (offset.y / distance).clamp(-1.0, 1.0).asin();
+        let (yaw, roll) = if pitch.abs() > Self::MAX_PITCH {
+            // Looking straight up or down, like the top and bottom views, the up direction tells the yaw. Tipped
+            // back from the pole towards it, the camera looks the same level.
+            let up = camera.up() * -pitch.signum();
+            (up.x.atan2(up.z), 0.0)
+        } else {
+            (offset.x.atan2(offset.z), roll_of(camera))
+        };
+        Self {
+            target,
+            // Stay well inside the clipping range so the target never gets clipped away.
+            min_distance: camera.z_near() * 2.0,
+            max_distance: camera.z_far() * 0.5,
+            yaw,
+            // Clamped like dragging does, the view is undefined right at the poles.
+            pitch: pitch.clamp(-Self::MAX_PITCH, Self::MAX_PITCH),
+            distance,
+            roll,
+        }
+    }
+
+    /// Takes over the view of a camera moved by something else, keeping the zoom range.
+    pub fn follow_camera(&mut self, camera: &Camera) {
+        *self = Self {
+            min_distance: self.min_distance,
+            max_distance: self.max_distance,
+            ..Self::new(camera)
+        };
+    }
+
+    pub fn roll(&self) -> f32 {
+        self.roll
+    }
+
+    /// Rolls the camera to `roll` radians counterclockwise from level with the horizon, 0 levels it.
+    pub fn set_roll(&mut self, camera: &mut Camera, roll: f32) {
+        self.roll = roll;
+        self.update_camera(camera);
+    }
+
+    /// Orbits the camera by `angle` radians around the vertical axis through the target.
+    pub fn turn(&mut self, camera: &mut Camera, angle: f32) {
+        self.yaw += angle;
+        self.update_camera(camera);
+    }
+
+    pub fn handle_input(&mut self, camera: &mut Camera, input: &OrbitInput) {
+        // Drags follow the rolled screen, turning them back level makes dragging right always orbit the same way.
+        let (sin, cos) = self.roll.sin_cos();
+        let orbit = egui::vec2(
+            input.orbit.x * cos + input.orbit.y * sin,
+            input.orbit.y * cos - input.orbit.x * sin,
+        );
+        self.yaw -= orbit.x * Self::ORBIT_SPEED;
+        self.pitch =
+            (self.pitch + orbit.y * Self::ORBIT_SPEED).clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+        // Kept within half a turn either way, like `roll_of`.
+        self.roll = (self.roll + input.roll + PI).rem_euclid(TAU) - PI;
+
+        // Move by the height of the view at the target, so the point under the cursor follows it.
+        let right = camera.right_direction().normalize();
+        let up = right.cross(camera.view_direction()).normalize();
+        let view_height = match *camera.projection_type() {
+            ProjectionType::Perspective { field_of_view_y } => {
+                2.0 * self.distance * (field_of_view_y.0 / 2.0).tan()
+            }
+            ProjectionType::Orthographic { height } => height,
+        };
+        self.target += (-input.pan.x * right + input.pan.y * up) * view_height;
+
+        // The scroll delta is the amount scrolled since the last frame, so zooming exponentially by it
+        // covers the same distance no matter how many frames the scrolling is spread over.
+        let old_distance = self.distance;
+        self.distance *= (-input.zoom * Self::ZOOM_SPEED).exp() / input.pinch.max(f32::EPSILON);
+        self.distance = self.distance.clamp(self.min_distance, self.max_distance);
+
+        if let ProjectionType::Orthographic { height } = *camera.projection_type() {
+            // The distance has no visible effect with an orthographic projection, so zoom by scaling the view height.
+            let height = height * self.distance / old_distance;
+            let (z_near, z_far) = (camera.z_near(), camera.z_far());
+            camera.set_orthographic_projection(height, z_near, z_far);
+        }
+
+        self.update_camera(camera);
+    }
+
+    fn update_camera(&self, camera: &mut Camera) {
+        let offset = vec3(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        ) * self.distance;
+        camera.set_view(
+            self.target + offset,
+            self.target,
+            rolled_up(-offset, self.roll),
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use three_d::*;
+
+/// Draws an outline around the shapes in a mask texture, in pixels outside the mask but within `thickness`
+/// pixels of it. The mask stands in for a stencil buffer, which three-d's render states don't expose.
+pub struct OutlineEffect {
+    pub color: Srgba,
+    /// Width of the outline in pixels.
+    pub thickness: f32,
+}
+
+impl OutlineEffect {
+    /// The thickness is capped, every pixel samples the whole square around it.
+    pub const MAX_THICKNESS: f32 = 10.0;
+}
+
+impl Effect for OutlineEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        let color_texture = color_texture.expect("the outline effect needs a mask texture");
+        format!(
+            "{}
+            uniform vec4 outlineColor;
+            uniform float thickness;
+            uniform vec2 texelSize;
+
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                if (sample_color(uvs).a > 0.5) {{
+                    discard;
+                }}
+                int radius = int(ceil(thickness));
+                for (int x = -radius; x <= radius; x++) {{
+                    for (int y = -radius; y <= radius; y++) {{
+                        vec2 offset = vec2(float(x), float(y));
+                        if (dot(offset, offset) <= thickness * thickness
+                            && sample_color(uvs + offset * texelSize).a > 0.5) {{
+                            outColor = outlineColor;
+                            return;
+                        }}
+                    }}
+                }}
+                discard;
+            }}
+            ",
+            color_texture.fragment_shader_source(),
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        // Next to the exposure effect, which also sets the highest bit three-d's effects leave unset.
+        0b1u16 << 15 | 0b1u16 << 10 | color_texture.map_or(0, |t| t.id())
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        // Written to the target as is, like the egui colors, so the outline has exactly the chosen color.
+        let c = self.color;
+        program.use_uniform(
+            "outlineColor",
+            vec4(c.r as f32, c.g as f32, c.b as f32, c.a as f32) / 255.0,
+        );
+        program.use_uniform("thickness", self.thickness.clamp(0.0, Self::MAX_THICKNESS));
+        if let Some(color_texture) = color_texture {
+            let (width, height) = (color_texture.width(), color_texture.height());
+            program.use_uniform("texelSize", vec2(1.0 / width as f32, 1.0 / height as f32));
+            color_texture.use_uniforms(program);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: DepthTest::Always,
+            write_mask: WriteMask::COLOR,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Whether the pointer was over a 3D view leaving scrolling to the page in the last frame. Read by the wheel
+/// listener of [`install`], which runs outside of egui, so it's kept here instead of on the views.
+static SCROLL_PAGE: AtomicBool = AtomicBool::new(false);
+/// The egui frame [`SCROLL_PAGE`] was last updated in, the first view shown in a frame starts over from false.
+static FRAME: AtomicU64 = AtomicU64::new(u64::MAX);
+/// The listener is installed by the first view leaving scrolling to the page, and then listens for good.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Called by every view while it's shown, with whether [`ThreeDView::page_scroll`](crate::ThreeDView::page_scroll)
+/// is set and the pointer is over it.
+pub(crate) fn update(frame_nr: u64, scroll_page: bool) {
+    if FRAME.swap(frame_nr, Ordering::Relaxed) != frame_nr {
+        SCROLL_PAGE.store(false, Ordering::Relaxed);
+    }
+    if scroll_page {
+        SCROLL_PAGE.store(true, Ordering::Relaxed);
+        if !INSTALLED.swap(true, Ordering::Relaxed) {
+            if let Err(err) = install() {
+                log::error!("Failed to leave scrolling to the page: {err:?}");
+            }
+        }
+    }
+}
+
+/// Stops the wheel events over the 3D views leaving scrolling to the page from reaching the canvas below them.
+///
+/// eframe prevents the default scrolling of all wheel events over its canvas, so they're stopped on the way down
+/// to it instead, in a listener capturing them on the window. The rest of the app still scrolls as usual.
+fn install() -> Result<(), wasm_bindgen::JsValue> {
+    use wasm_bindgen::{closure::Closure, JsCast};
+
+    let window = web_sys::window().ok_or("no window")?;
+    let listener =
+        Closure::<dyn FnMut(web_sys::WheelEvent)>::new(move |event: web_sys::WheelEvent| {
+            let on_canvas = event
+                .target()
+                .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+                .is_some_and(|element| element.tag_name().eq_ignore_ascii_case("canvas"));
+            let zooming = event.ctrl_key() || event.meta_key();
+            if on_canvas && SCROLL_PAGE.load(Ordering::Relaxed) && !zooming {
+                event.stop_propagation();
+            }
+        });
+    let mut options = web_sys::AddEventListenerOptions::new();
+    options.capture(true);
+    window.add_event_listener_with_callback_and_add_event_listener_options(
+        "wheel",
+        listener.as_ref().unchecked_ref(),
+        &options,
+    )?;
+    // Listens for as long as the page is open.
+    listener.forget();
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use three_d::*;
+
+use crate::instancing::hash_to_unit;
+
+/// A fountain of particles shooting up from the origin and falling back under gravity, simulated on the CPU and
+/// drawn as camera facing quads in a single instanced draw call.
+pub struct ParticleFountain {
+    /// Most particles alive at once, no new ones spawn while there are this many.
+    pub max_count: u32,
+    /// Particles spawned per second.
+    pub spawn_rate: f32,
+    /// Downward acceleration, in units per second squared.
+    pub gravity: f32,
+    particles: Vec<Particle>,
+    /// Fraction of a particle left over from the last update, so low spawn rates still spawn now and then.
+    pending: f32,
+    /// Number of particles spawned so far, seeding the random velocity of the next one.
+    spawned: u32,
+    model: Gm<InstancedMesh, ColorMaterial>,
+}
+
+struct Particle {
+    position: Vec3,
+    velocity: Vec3,
+    /// Seconds since it spawned.
+    age: f32,
+}
+
+impl ParticleFountain {
+    /// Seconds a particle lives, fading out towards the end.
+    const LIFETIME: f32 = 3.0;
+    /// Half the side length of the quads.
+    const SIZE: f32 = 0.03;
+    const UPWARD_SPEED: f32 = 2.0;
+    const SIDEWAYS_SPEED: f32 = 0.5;
+    const COLOR: Srgba = Srgba::new(255, 180, 80, 255);
+
+    pub fn new(context: &Context) -> Self {
+        let material = ColorMaterial {
+            texture: Some(Texture2DRef::from_cpu_texture(
+                context,
+                &Self::soft_circle(),
+            )),
+            // Blended without writing depth, so overlapping particles don't cut each other off whatever order
+            // they're drawn in, while the models still hide the ones behind them.
+            render_states: RenderStates {
+                write_mask: WriteMask::COLOR,
+                blend: Blend::TRANSPARENCY,
+                ..Default::default()
+            },
+            is_transparent: true,
+            ..Default::default()
+        };
+        Self {
+            max_count: 2000,
+            spawn_rate: 300.0,
+            gravity: 2.0,
+            particles: Vec::new(),
+            pending: 0.0,
+            spawned: 0,
+            model: Gm::new(
+                InstancedMesh::new(context, &Instances::default(), &CpuMesh::square()),
+                material,
+            ),
+        }
+    }
+
+    /// White, with the alpha falling off smoothly from the center to the edge.
+    fn soft_circle() -> CpuTexture {
+        let size = 32;
+        let data = (0..size * size)
+            .map(|i| {
+                let (x, y) = (i % size, i / size);
+                let offset =
+                    vec2(x as f32 + 0.5, y as f32 + 0.5) / size as f32 * 2.0 - vec2(1.0, 1.0);
+                let falloff = (1.0 - offset.magnitude()).max(0.0);
+                [255, 255, 255, (falloff * falloff * 255.0).round() as u8]
+            })
+            .collect();
+        CpuTexture {
+            name: "soft circle".to_owned(),
+            data: TextureData::RgbaU8(data),
+            width: size,
+            height: size,
+            ..Default::default()
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.pending = 0.0;
+    }
+
+    /// Moves the particles `dt` seconds on, removing the expired ones and spawning new ones.
+    pub fn update(&mut self, dt: f32) {
+        for particle in &mut self.particles {
+            particle.velocity.y -= self.gravity * dt;
+            particle.position += particle.velocity * dt;
+            particle.age += dt;
+        }
+        self.particles
+            .retain(|particle| particle.age < Self::LIFETIME);
+        self.particles.truncate(self.max_count as usize);
+
+        self.pending += self.spawn_rate * dt;
+        let room = (self.max_count as usize).saturating_sub(self.particles.len());
+        let spawning = (self.pending.floor() as usize).min(room);
+        self.pending = self.pending.fract();
+        for _ in 0..spawning {
+            let random = |j: u32| hash_to_unit(self.spawned * 3 + j) * 2.0 - 1.0;
+            let velocity = vec3(
+                random(0) * Self::SIDEWAYS_SPEED,
+                Self::UPWARD_SPEED * (1.0 + 0.2 * random(1)),
+                random(2) * Self::SIDEWAYS_SPEED,
+            );
+            self.spawned = self.spawned.wrapping_add(1);
+            self.particles.push(Particle {
+                position: vec3(0.0, 0.0, 0.0),
+                velocity,
+                age: 0.0,
+            });
+        }
+    }
+
+    /// Turns the quads towards `camera` and fades them by their age.
+    pub fn update_instances(&mut self, camera: &Camera) {
+        let view = camera.view_direction().normalize();
+        let right = camera.right_direction().normalize();
+        let (up, back) = (right.cross(view) * Self::SIZE, -view * Self::SIZE);
+        let right = right * Self::SIZE;
+        let (transformations, colors) = self
+            .particles
+            .iter()
+            .map(|particle| {
+                let transformation = Mat4::from_cols(
+                    right.extend(0.0),
+                    up.extend(0.0),
+                    back.extend(0.0),
+                    particle.position.extend(1.0),
+                );
+                let life = 1.0 - particle.age / Self::LIFETIME;
+                let color = Srgba {
+                    a: (life * 255.0).round() as u8,
+                    ..Self::COLOR
+                };
+                (transformation, color)
+            })
+            .unzip();
+        self.model.geometry.set_instances(&Instances {
+            transformations,
+            colors: Some(colors),
+            ..Default::default()
+        });
+    }
+
+    /// The instanced quads, `None` while there are no particles to draw.
+    pub fn model(&self) -> Option<&Gm<InstancedMesh, ColorMaterial>> {
+        (!self.particles.is_empty()).then_some(&self.model)
+    }
+}
@@ -0,0 +1,50 @@
+use eframe::egui;
+use three_d::*;
+
+/// A model hit by a click on the canvas.
+#[derive(Clone, Copy)]
+pub struct Pick {
+    /// Index of the hit model.
+    pub model: usize,
+    /// World space position of the hit.
+    pub position: Vec3,
+}
+
+/// The surface under the cursor, read back from the depth buffer.
+#[derive(Clone, Copy)]
+pub struct DepthSample {
+    /// World space distance from the camera to the surface.
+    pub distance: f32,
+    /// World space position of the surface.
+    pub position: Vec3,
+}
+
+/// A ray through the clipping range of a camera.
+#[derive(Clone, Copy)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+    pub length: f32,
+}
+
+impl Ray {
+    /// The ray through the point `pos` of the canvas `rect`, from the near to the far plane of the camera.
+    /// Unprojecting the clip space corners works for both projections and doesn't need the camera viewport,
+    /// which is only known while painting.
+    pub fn from_canvas(camera: &Camera, rect: egui::Rect, pos: egui::Pos2) -> Option<Self> {
+        let inverse = (camera.projection() * camera.view()).invert()?;
+        let x = (pos.x - rect.left()) / rect.width() * 2.0 - 1.0;
+        let y = 1.0 - (pos.y - rect.top()) / rect.height() * 2.0;
+        let unproject = |z: f32| {
+            let world = inverse * vec4(x, y, z, 1.0);
+            world.truncate() / world.w
+        };
+        let (near, far) = (unproject(-1.0), unproject(1.0));
+        let length = near.distance(far);
+        (length.is_finite() && length > 0.0).then(|| Self {
+            origin: near,
+            direction: (far - near) / length,
+            length,
+        })
+    }
+}
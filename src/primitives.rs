@@ -0,0 +1,135 @@
+use three_d::*;
+
+/// The built-in shapes that can be shown instead of a loaded model.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Primitive {
+    /// The triangle with a color at each corner.
+    Triangle,
+    Cube,
+    Sphere,
+    Cylinder,
+    Torus,
+}
+
+impl Primitive {
+    pub const ALL: [Self; 5] = [
+        Self::Triangle,
+        Self::Cube,
+        Self::Sphere,
+        Self::Cylinder,
+        Self::Torus,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Triangle => "Triangle",
+            Self::Cube => "Cube",
+            Self::Sphere => "Sphere",
+            Self::Cylinder => "Cylinder",
+            Self::Torus => "Torus",
+        }
+    }
+
+    /// The mesh of a shape other than the triangle, centered on the origin and about as large as the triangle.
+    /// `None` for the triangle, which is built with its vertex colors instead.
+    pub fn cpu_mesh(self) -> Option<CpuMesh> {
+        let (mut cpu_mesh, transformation) = match self {
+            Self::Triangle => return None,
+            Self::Cube => (CpuMesh::cube(), Mat4::from_scale(0.4)),
+            Self::Sphere => (CpuMesh::sphere(32), Mat4::from_scale(0.5)),
+            // The cylinder runs from 0 to 1 along the x-axis, stand it up around the origin instead.
+            Self::Cylinder => (
+                CpuMesh::cylinder(32),
+                Mat4::from_angle_z(degrees(90.0))
+                    * Mat4::from_nonuniform_scale(1.0, 0.4, 0.4)
+                    * Mat4::from_translation(vec3(-0.5, 0.0, 0.0)),
+            ),
+            Self::Torus => (torus(0.35, 0.15, 48, 24), Mat4::from_angle_x(degrees(90.0))),
+        };
+        cpu_mesh.transform(&transformation).unwrap();
+        if cpu_mesh.normals.is_none() {
+            cpu_mesh.compute_normals();
+        }
+        Some(cpu_mesh)
+    }
+}
+
+/// The triangles of a mesh with vertices of their own, each taking the normal of its face and the color of its
+/// last vertex, like the provoking vertex of flat interpolation in OpenGL.
+pub(crate) fn flat_shaded(cpu_mesh: &CpuMesh) -> CpuMesh {
+    let positions = cpu_mesh.positions.to_f32();
+    let mut flat = CpuMesh {
+        positions: Positions::F32(Vec::new()),
+        normals: Some(Vec::new()),
+        uvs: cpu_mesh.uvs.as_ref().map(|_| Vec::new()),
+        colors: cpu_mesh.colors.as_ref().map(|_| Vec::new()),
+        ..Default::default()
+    };
+    let mut flat_positions = Vec::new();
+    cpu_mesh.for_each_triangle(|a, b, c| {
+        let corners = [positions[a], positions[b], positions[c]];
+        let normal = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+        let normal = if normal.magnitude2() > 0.0 {
+            normal.normalize()
+        } else {
+            normal
+        };
+        flat_positions.extend(corners);
+        flat.normals.as_mut().unwrap().extend([normal; 3]);
+        if let (Some(uvs), Some(flat_uvs)) = (&cpu_mesh.uvs, &mut flat.uvs) {
+            flat_uvs.extend([uvs[a], uvs[b], uvs[c]]);
+        }
+        if let (Some(colors), Some(flat_colors)) = (&cpu_mesh.colors, &mut flat.colors) {
+            flat_colors.extend([colors[c]; 3]);
+        }
+    });
+    flat.positions = Positions::F32(flat_positions);
+    if cpu_mesh.tangents.is_some() {
+        flat.compute_tangents();
+    }
+    flat
+}
+
+/// A torus around the z-axis, `major_radius` from its center to the middle of the tube.
+fn torus(
+    major_radius: f32,
+    minor_radius: f32,
+    major_segments: u32,
+    minor_segments: u32,
+) -> CpuMesh {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    // One more ring and vertex per ring than segments, so the texture coordinates can wrap around.
+    for i in 0..=major_segments {
+        let u = i as f32 / major_segments as f32;
+        let (sin_u, cos_u) = (u * std::f32::consts::TAU).sin_cos();
+        for j in 0..=minor_segments {
+            let v = j as f32 / minor_segments as f32;
+            let (sin_v, cos_v) = (v * std::f32::consts::TAU).sin_cos();
+            let normal = vec3(cos_u * cos_v, sin_u * cos_v, sin_v);
+            let center = vec3(cos_u, sin_u, 0.0) * major_radius;
+            positions.push(center + normal * minor_radius);
+            normals.push(normal);
+            uvs.push(vec2(u, v));
+        }
+    }
+
+    let ring = minor_segments + 1;
+    let mut indices = Vec::new();
+    for i in 0..major_segments {
+        for j in 0..minor_segments {
+            let a = i * ring + j;
+            let b = (i + 1) * ring + j;
+            indices.extend([a, b, a + 1, a + 1, b, b + 1]);
+        }
+    }
+
+    CpuMesh {
+        positions: Positions::F32(positions),
+        indices: Indices::U32(indices),
+        normals: Some(normals),
+        uvs: Some(uvs),
+        ..Default::default()
+    }
+}
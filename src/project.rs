@@ -0,0 +1,76 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::camera_state::CameraState;
+
+/// A whole scene saved to disk: where its models come from and how they're placed, the lights, the view and the
+/// background. The models are loaded again from their files when it's opened.
+#[derive(Serialize, Deserialize)]
+pub struct ProjectFile {
+    /// Bumped whenever the format changes in a way older versions can't read.
+    pub version: u32,
+    pub objects: Vec<ProjectObject>,
+    pub lights: Vec<ProjectLight>,
+    pub camera: CameraState,
+    pub clear_color: [u8; 4],
+}
+
+impl ProjectFile {
+    pub const VERSION: u32 = 1;
+}
+
+/// A model of the scene, also kept for saving again when its file couldn't be loaded.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ProjectObject {
+    pub name: String,
+    pub source: ProjectSource,
+    pub translation: [f32; 3],
+    /// Rotation around the x, y and z axes in degrees, like `Transform::rotation`.
+    pub rotation: [f32; 3],
+    pub scale: f32,
+    pub visible: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProjectSource {
+    /// One of the built-in shapes, by name.
+    Primitive { shape: String },
+    /// The triangle mesh at index `mesh` of a model file, relative to the project file where possible.
+    File { path: PathBuf, mesh: usize },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectLightKind {
+    Point,
+    Spot,
+}
+
+/// A light added in the UI. Spot lights are aimed at the models again, so their direction isn't saved.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ProjectLight {
+    pub kind: ProjectLightKind,
+    pub position: [f32; 3],
+    pub color: [u8; 4],
+    pub intensity: f32,
+}
+
+/// A model of an opened project whose file couldn't be loaded, listed with the error in place of the model.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MissingObject {
+    pub object: ProjectObject,
+    pub error: String,
+}
+
+/// `path` relative to the directory of the project, so the project can be moved together with its models.
+pub(crate) fn relative_path(path: &Path, project: &Path) -> PathBuf {
+    let directory = project.parent().unwrap_or(Path::new(""));
+    path.strip_prefix(directory).unwrap_or(path).to_owned()
+}
+
+/// A path saved in the project resolved against its directory, absolute paths stay as they are.
+pub(crate) fn resolve_path(path: &Path, project: &Path) -> PathBuf {
+    project.parent().unwrap_or(Path::new("")).join(path)
+}
@@ -0,0 +1,74 @@
+use three_d::*;
+
+/// Name of the uniform with the seconds of [`Custom3d::time`](crate::Custom3d::time), for animating shaders.
+pub const TIME_UNIFORM: &str = "u_time";
+
+/// An example of a material animated by the time uniform: the vertex colors, unlit, pulse in brightness.
+/// Materials of your own get the time the same way, by passing it to [`TIME_UNIFORM`] in `use_uniforms`.
+#[derive(Clone, Copy, Debug)]
+pub struct PulseMaterial {
+    /// Seconds since the animation started.
+    pub time: f32,
+    /// Seconds from one brightest moment to the next.
+    pub period: f32,
+}
+
+impl Default for PulseMaterial {
+    fn default() -> Self {
+        Self {
+            time: 0.0,
+            period: 2.0,
+        }
+    }
+}
+
+impl Material for PulseMaterial {
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        format!(
+            "
+            uniform float {TIME_UNIFORM};
+            uniform float period;
+
+            in vec4 col;
+
+            layout (location = 0) out vec4 outColor;
+
+            {}
+
+            void main()
+            {{
+                // Swings between 40% and the full color.
+                float brightness = 0.7 + 0.3 * sin({TIME_UNIFORM} * 6.2831853 / period);
+                outColor = vec4(color_mapping(col.rgb * brightness), col.a);
+            }}
+            ",
+            ColorMapping::fragment_shader_source()
+        )
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            color: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.color_mapping.use_uniforms(program);
+        program.use_uniform(TIME_UNIFORM, self.time);
+        program.use_uniform("period", self.period.max(f32::EPSILON));
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates::default()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+
+    fn id(&self) -> u16 {
+        // Next to the attribute colors of the debug views.
+        0b1u16 << 15 | 0b1u16 << 11
+    }
+}
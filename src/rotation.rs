@@ -0,0 +1,6 @@
+/// Turns `angle` (in radians) at `degrees_per_second` for `dt` seconds, wrapping it to less than a full turn.
+///
+/// Scaling by the elapsed time makes the rotation equally fast at any frame rate.
+pub fn advance_angle(angle: f32, degrees_per_second: f32, dt: f32) -> f32 {
+    (angle + degrees_per_second.to_radians() * dt) % std::f32::consts::TAU
+}
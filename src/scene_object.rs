@@ -0,0 +1,100 @@
+use three_d::*;
+
+use crate::primitives::Primitive;
+use crate::simplify::SimplifiedMesh;
+
+/// Placement of a scene object within the scene, applied before the transformation of the whole scene.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Transform {
+    pub translation: Vec3,
+    /// Rotation around the x, y and z axes in degrees, applied in that order.
+    pub rotation: Vec3,
+    pub scale: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: vec3(0.0, 0.0, 0.0),
+            rotation: vec3(0.0, 0.0, 0.0),
+            scale: 1.0,
+        }
+    }
+}
+
+impl Transform {
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_translation(self.translation)
+            * Mat4::from_angle_z(degrees(self.rotation.z))
+            * Mat4::from_angle_y(degrees(self.rotation.y))
+            * Mat4::from_angle_x(degrees(self.rotation.x))
+            * Mat4::from_scale(self.scale)
+    }
+}
+
+/// Where a scene object came from, so a project can load it again.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ObjectSource {
+    Primitive(Primitive),
+    /// The triangle mesh at index `mesh` of a model file.
+    File {
+        path: std::path::PathBuf,
+        mesh: usize,
+    },
+}
+
+/// A model listed in the scene panel, one for each mesh of the loaded files.
+pub struct SceneObject {
+    pub name: String,
+    pub transform: Transform,
+    pub visible: bool,
+    pub model: Gm<Mesh, PhysicalMaterial>,
+    /// `None` for models made from a `CpuModel` that wasn't read from a file.
+    pub source: Option<ObjectSource>,
+    /// The mesh of the model, kept for the lines drawn from it.
+    pub(crate) cpu_mesh: CpuMesh,
+    /// Shown instead of the model's mesh when the level of detail is enabled.
+    pub(crate) simplified: Option<SimplifiedMesh>,
+    /// Outside the view of the main camera in the last frame, so it's left out of rendering.
+    pub(crate) culled: bool,
+}
+
+impl SceneObject {
+    pub fn new(
+        name: impl Into<String>,
+        model: Gm<Mesh, PhysicalMaterial>,
+        cpu_mesh: CpuMesh,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            transform: Transform::default(),
+            visible: true,
+            model,
+            source: None,
+            cpu_mesh,
+            simplified: None,
+            culled: false,
+        }
+    }
+
+    /// Whether the model is rendered this frame, shown and not culled.
+    pub(crate) fn is_drawn(&self) -> bool {
+        self.visible && !self.culled
+    }
+
+    /// The simplified mesh when it's shown this frame, or else the full one.
+    pub(crate) fn shown_geometry(&self) -> &Mesh {
+        match &self.simplified {
+            Some(simplified) if simplified.shown => &simplified.mesh,
+            _ => &self.model.geometry,
+        }
+    }
+
+    /// The mesh moved to where the transform places it in the scene.
+    pub(crate) fn placed_mesh(&self) -> CpuMesh {
+        let mut cpu_mesh = self.cpu_mesh.clone();
+        // The transform is always invertible unless it's scaled to nothing, which leaves nothing to draw anyway.
+        cpu_mesh.transform(&self.transform.matrix()).ok();
+        cpu_mesh
+    }
+}
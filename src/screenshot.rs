@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+
+/// A capture of the scene into an image file, performed on the next paint.
+pub struct ScreenshotRequest {
+    pub width: u32,
+    pub height: u32,
+    pub path: PathBuf,
+    pub format: ScreenshotFormat,
+}
+
+/// An export of the scene as numbered PNGs while the camera orbits it once, saving a frame on each paint.
+#[derive(Clone, Debug)]
+pub struct TurntableRequest {
+    /// Frames of the full turn, evenly spaced.
+    pub frames: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Where the frames are written, created if it doesn't exist.
+    pub directory: PathBuf,
+}
+
+impl TurntableRequest {
+    /// Path of the frame at `index`, numbered from 0 and padded so the files sort in order.
+    pub fn frame_path(&self, index: u32) -> PathBuf {
+        self.directory.join(format!("frame-{index:04}.png"))
+    }
+}
+
+/// A turntable export in progress.
+pub(crate) struct Turntable {
+    pub request: TurntableRequest,
+    /// Frames saved so far.
+    pub saved: u32,
+    /// The camera before the export, put back once it's done.
+    pub camera: three_d::Camera,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScreenshotFormat {
+    /// 8 bit sRGB colors, as shown on screen.
+    Png,
+    /// Linear floating point colors in the Radiance format, before the exposure and tone mapping.
+    Hdr,
+}
+
+impl ScreenshotFormat {
+    pub const ALL: [ScreenshotFormat; 2] = [ScreenshotFormat::Png, ScreenshotFormat::Hdr];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Png => "PNG (tone mapped)",
+            ScreenshotFormat::Hdr => "HDR (linear)",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Png => "png",
+            ScreenshotFormat::Hdr => "hdr",
+        }
+    }
+}
+
+/// Writes top-to-bottom RGBA pixels, as returned by `RenderTarget::read_color`, to a PNG file.
+pub fn save_png(
+    path: &Path,
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 4]>,
+) -> Result<(), image::ImageError> {
+    let bytes = pixels.into_iter().flatten().collect();
+    let image = image::RgbaImage::from_raw(width, height, bytes)
+        .expect("the pixel count must match the image size");
+    image.save_with_format(path, image::ImageFormat::Png)
+}
+
+/// Writes top-to-bottom linear RGBA pixels to a Radiance HDR file, leaving out the alpha it can't store.
+pub fn save_hdr(
+    path: &Path,
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 4]>,
+) -> Result<(), image::ImageError> {
+    assert_eq!(
+        pixels.len(),
+        (width * height) as usize,
+        "the pixel count must match the image size"
+    );
+    let pixels: Vec<_> = pixels
+        .into_iter()
+        .map(|[r, g, b, _]| image::Rgb([r, g, b]))
+        .collect();
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    image::codecs::hdr::HdrEncoder::new(file).encode(&pixels, width as usize, height as usize)
+}
@@ -0,0 +1,125 @@
+use std::collections::{HashMap, HashSet};
+
+use three_d::*;
+
+/// Settings of the simplified meshes shown in place of the full meshes of the models, to keep heavy models fast.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LevelOfDetail {
+    pub enabled: bool,
+    /// How much detail the simplified meshes keep, from 0 for the coarsest to 1 for the finest.
+    pub quality: f32,
+    /// Shows the full meshes up close and the simplified ones only beyond `distance`, instead of always.
+    pub automatic: bool,
+    /// Distance from the camera to a model, in multiples of its size, beyond which it's simplified.
+    pub distance: f32,
+}
+
+impl Default for LevelOfDetail {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            quality: 0.3,
+            automatic: false,
+            distance: 3.0,
+        }
+    }
+}
+
+/// The simplified mesh of a scene object, uploaded next to its full mesh.
+pub(crate) struct SimplifiedMesh {
+    pub mesh: Mesh,
+    pub triangles: usize,
+    /// Whether it's shown instead of the full mesh this frame.
+    pub shown: bool,
+}
+
+/// Simplifies a mesh by vertex clustering: the vertices in each cell of a grid over its bounds merge into one
+/// at their average position, and the triangles left with fewer than three corners disappear. `quality` sets
+/// the cells along the longest side, from 2 at 0 to 128 at 1. `None` if no triangle is left.
+pub(crate) fn simplify(cpu_mesh: &CpuMesh, quality: f32) -> Option<CpuMesh> {
+    let positions = cpu_mesh.positions.to_f32();
+    let aabb = cpu_mesh.compute_aabb();
+    let size = aabb.size();
+    let cells = 2.0 + quality.clamp(0.0, 1.0) * 126.0;
+    let cell_size = (size.x.max(size.y).max(size.z) / cells).max(f32::EPSILON);
+    let cell = |position: Vec3| {
+        let offset = (position - aabb.min()) / cell_size;
+        (offset.x as i32, offset.y as i32, offset.z as i32)
+    };
+
+    // The vertex each original vertex merges into, and the sums the merged vertices are averaged from.
+    let mut clusters = HashMap::new();
+    let mut sums: Vec<(Vec3, Vec3, u32)> = Vec::new();
+    let mut firsts = Vec::new();
+    let merged: Vec<u32> = positions
+        .iter()
+        .enumerate()
+        .map(|(index, &position)| {
+            let cluster = *clusters.entry(cell(position)).or_insert_with(|| {
+                sums.push((Vec3::zero(), Vec3::zero(), 0));
+                firsts.push(index);
+                sums.len() - 1
+            });
+            let normal = cpu_mesh
+                .normals
+                .as_ref()
+                .map_or(Vec3::zero(), |normals| normals[index]);
+            let sum = &mut sums[cluster];
+            *sum = (sum.0 + position, sum.1 + normal, sum.2 + 1);
+            cluster as u32
+        })
+        .collect();
+
+    let mut indices = Vec::new();
+    let mut seen = HashSet::new();
+    cpu_mesh.for_each_triangle(|a, b, c| {
+        let corners = [merged[a], merged[b], merged[c]];
+        if corners[0] == corners[1] || corners[1] == corners[2] || corners[2] == corners[0] {
+            return;
+        }
+        // Triangles merging into the same corners, like both sides of a thin part, are kept once.
+        let mut key = corners;
+        key.sort_unstable();
+        if seen.insert(key) {
+            indices.extend(corners);
+        }
+    });
+    if indices.is_empty() {
+        return None;
+    }
+
+    // The other attributes are taken from the first vertex of each cluster, as averaging them would smear the
+    // texture coordinates across seams.
+    let mut simplified = CpuMesh {
+        positions: Positions::F32(
+            sums.iter()
+                .map(|(sum, _, count)| sum / *count as f32)
+                .collect(),
+        ),
+        indices: Indices::U32(indices),
+        normals: cpu_mesh.normals.as_ref().map(|_| {
+            sums.iter()
+                .map(|(_, normal, _)| {
+                    if normal.magnitude2() > 0.0 {
+                        normal.normalize()
+                    } else {
+                        *normal
+                    }
+                })
+                .collect()
+        }),
+        uvs: cpu_mesh
+            .uvs
+            .as_ref()
+            .map(|uvs| firsts.iter().map(|&index| uvs[index]).collect()),
+        colors: cpu_mesh
+            .colors
+            .as_ref()
+            .map(|colors| firsts.iter().map(|&index| colors[index]).collect()),
+        ..Default::default()
+    };
+    if cpu_mesh.tangents.is_some() {
+        simplified.compute_tangents();
+    }
+    Some(simplified)
+}
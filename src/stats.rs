@@ -0,0 +1,171 @@
+use std::collections::VecDeque;
+
+// `std::time::Instant` panics on the web, `web_time` uses `performance.now()` there instead.
+use web_time::Instant;
+
+/// Frame timing, smoothed over the last few frames so the readout doesn't flicker.
+#[derive(Default)]
+pub struct FrameStats {
+    last_frame: Option<Instant>,
+    /// Smoothed frame time in seconds.
+    frame_time: f32,
+    /// Unsmoothed frame times in seconds of up to the last `HISTORY_LENGTH` frames, oldest first.
+    history: VecDeque<f32>,
+}
+
+impl FrameStats {
+    /// Weight of the newest frame in the exponential moving average.
+    const SMOOTHING: f32 = 0.1;
+    /// Number of frames kept in the history.
+    pub const HISTORY_LENGTH: usize = 300;
+
+    /// Must be called exactly once per frame.
+    pub fn record_frame(&mut self) {
+        let now = Instant::now();
+        if let Some(last_frame) = self.last_frame {
+            let frame_time = (now - last_frame).as_secs_f32();
+            if self.history.len() == Self::HISTORY_LENGTH {
+                self.history.pop_front();
+            }
+            self.history.push_back(frame_time);
+            self.frame_time = if self.frame_time == 0.0 {
+                frame_time
+            } else {
+                self.frame_time + (frame_time - self.frame_time) * Self::SMOOTHING
+            };
+        }
+        self.last_frame = Some(now);
+    }
+
+    /// Time since the current frame was recorded.
+    pub fn time_in_frame(&self) -> std::time::Duration {
+        self.last_frame
+            .map_or(std::time::Duration::ZERO, |last_frame| last_frame.elapsed())
+    }
+
+    /// Frame times in seconds of the last frames, oldest first.
+    pub fn history(&self) -> &VecDeque<f32> {
+        &self.history
+    }
+
+    pub fn frame_time_ms(&self) -> f32 {
+        self.frame_time * 1000.0
+    }
+
+    pub fn fps(&self) -> f32 {
+        if self.frame_time > 0.0 {
+            1.0 / self.frame_time
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Counts the draw calls of a frame. The scene renders through shared references, so the count is kept in a cell.
+#[derive(Default)]
+pub struct DrawCalls {
+    current: std::cell::Cell<u32>,
+    last_frame: u32,
+}
+
+impl DrawCalls {
+    pub fn add(&self, count: usize) {
+        self.current.set(self.current.get() + count as u32);
+    }
+
+    /// Starts counting a new frame, keeping the count of the one before.
+    pub fn start_frame(&mut self) {
+        self.last_frame = self.current.replace(0);
+    }
+
+    /// Draw calls of the last complete frame.
+    pub fn last_frame(&self) -> u32 {
+        self.last_frame
+    }
+}
+
+/// What rendering the scene costs, shown in the diagnostics of the demo.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct RenderStats {
+    /// Draw calls issued for the last frame, including shadow maps and post-processing passes.
+    pub draw_calls: u32,
+    /// Triangles of all models, counted once no matter how often they are drawn.
+    pub triangles: usize,
+    /// Rough size of the vertex and index buffers of all models, in bytes.
+    pub buffer_bytes: usize,
+    /// Visible models rendered in the main view of the last frame.
+    pub rendered_objects: usize,
+    /// Visible models skipped in the last frame for being outside the main view.
+    pub culled_objects: usize,
+}
+
+impl RenderStats {
+    /// Adds the triangles and buffer sizes of the meshes, assuming three-d uploads them as 32 bit floats.
+    pub fn add_meshes<'a>(&mut self, meshes: impl IntoIterator<Item = &'a three_d::CpuMesh>) {
+        use three_d::Indices;
+
+        for mesh in meshes {
+            self.triangles += mesh.triangle_count();
+            let per_vertex = 3 * 4
+                + mesh.normals.as_ref().map_or(0, |_| 3 * 4)
+                + mesh.tangents.as_ref().map_or(0, |_| 4 * 4)
+                + mesh.uvs.as_ref().map_or(0, |_| 2 * 4)
+                + mesh.colors.as_ref().map_or(0, |_| 4);
+            let index_bytes = match &mesh.indices {
+                Indices::None => 0,
+                Indices::U8(indices) => indices.len(),
+                Indices::U16(indices) => indices.len() * 2,
+                Indices::U32(indices) => indices.len() * 4,
+            };
+            self.buffer_bytes += mesh.vertex_count() * per_vertex + index_bytes;
+        }
+    }
+}
+
+/// Counts and bounds of the meshes of a model file as it was imported, for checking that it imported correctly
+/// and at the expected scale.
+#[derive(Clone, Debug, Default)]
+pub struct ImportStats {
+    /// The file the model was read from.
+    pub path: Option<std::path::PathBuf>,
+    pub meshes: Vec<MeshStats>,
+}
+
+impl ImportStats {
+    pub fn vertices(&self) -> usize {
+        self.meshes.iter().map(|mesh| mesh.vertices).sum()
+    }
+
+    pub fn triangles(&self) -> usize {
+        self.meshes.iter().map(|mesh| mesh.triangles).sum()
+    }
+
+    /// Bounds of all the meshes together.
+    pub fn aabb(&self) -> three_d::AxisAlignedBoundingBox {
+        let mut aabb = three_d::AxisAlignedBoundingBox::EMPTY;
+        for mesh in &self.meshes {
+            aabb.expand_with_aabb(&mesh.aabb);
+        }
+        aabb
+    }
+}
+
+/// Counts and bounds of a single mesh, before the model is placed in the scene.
+#[derive(Clone, Debug)]
+pub struct MeshStats {
+    pub name: String,
+    pub vertices: usize,
+    pub triangles: usize,
+    pub aabb: three_d::AxisAlignedBoundingBox,
+}
+
+impl MeshStats {
+    pub fn new(name: impl Into<String>, mesh: &three_d::CpuMesh) -> Self {
+        Self {
+            name: name.into(),
+            vertices: mesh.vertex_count(),
+            triangles: mesh.triangle_count(),
+            aabb: mesh.compute_aabb(),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use three_d::*;
+
+/// How textures are sampled, set on the textures when they're uploaded.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TextureSettings {
+    /// Generates mipmaps and blends between them when the texture is minified, which stops it from shimmering
+    /// at grazing angles.
+    pub mipmaps: bool,
+    /// Interpolation when the texture is minified.
+    pub min_filter: Interpolation,
+    /// Interpolation when the texture is magnified.
+    pub mag_filter: Interpolation,
+    /// Sampling outside the texture coordinate range of 0 to 1, in both directions.
+    pub wrapping: Wrapping,
+}
+
+/// The defaults of three-d.
+impl Default for TextureSettings {
+    fn default() -> Self {
+        Self {
+            mipmaps: true,
+            min_filter: Interpolation::Linear,
+            mag_filter: Interpolation::Linear,
+            wrapping: Wrapping::Repeat,
+        }
+    }
+}
+
+impl TextureSettings {
+    pub fn apply(&self, cpu_texture: &mut CpuTexture) {
+        cpu_texture.min_filter = self.min_filter;
+        cpu_texture.mag_filter = self.mag_filter;
+        cpu_texture.mip_map_filter = self.mipmaps.then_some(self.min_filter);
+        cpu_texture.wrap_s = self.wrapping;
+        cpu_texture.wrap_t = self.wrapping;
+    }
+
+    /// Applies the settings to all textures of the material.
+    pub fn apply_to_material(&self, cpu_material: &mut CpuMaterial) {
+        for cpu_texture in [
+            &mut cpu_material.albedo_texture,
+            &mut cpu_material.metallic_roughness_texture,
+            &mut cpu_material.occlusion_metallic_roughness_texture,
+            &mut cpu_material.normal_texture,
+            &mut cpu_material.occlusion_texture,
+            &mut cpu_material.emissive_texture,
+            &mut cpu_material.transmission_texture,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            self.apply(cpu_texture);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use three_d::*;
+
+/// Copies an HDR color texture to the screen, scaling it by the exposure before applying the tone and color mapping of the camera.
+/// Like `ScreenEffect`, which has no exposure.
+pub struct ExposureEffect {
+    pub exposure: f32,
+}
+
+impl Effect for ExposureEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        let color_texture = color_texture.expect("the exposure effect needs a color texture");
+        format!(
+            "{}{}{}
+            uniform float exposure;
+
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                outColor = sample_color(uvs);
+                outColor.rgb = tone_mapping(outColor.rgb * exposure);
+                outColor.rgb = color_mapping(outColor.rgb);
+            }}
+            ",
+            color_texture.fragment_shader_source(),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        // None of the effects of three-d set the highest bit.
+        0b1u16 << 15 | 0b1u16 << 11 | color_texture.map_or(0, |t| t.id())
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        camera.tone_mapping.use_uniforms(program);
+        camera.color_mapping.use_uniforms(program);
+        program.use_uniform("exposure", self.exposure);
+        if let Some(color_texture) = color_texture {
+            color_texture.use_uniforms(program);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
+
+/// The linear color that ends up as `color` on screen after the exposure, tone mapping and sRGB encoding,
+/// so clearing to it keeps the background color the same as without post-processing.
+pub fn unmapped_color(color: Srgba, tone_mapping: ToneMapping, exposure: f32) -> Vec4 {
+    let linear = color.to_linear_srgb();
+    let unmap = |y: f32| inverse_tone_mapping(tone_mapping, y) / exposure.max(1e-3);
+    vec4(unmap(linear.x), unmap(linear.y), unmap(linear.z), linear.w)
+}
+
+/// The curves of `ToneMapping::fragment_shader_source`, for a single channel.
+fn tone_mapping_curve(tone_mapping: ToneMapping, x: f32) -> f32 {
+    match tone_mapping {
+        ToneMapping::None => x,
+        ToneMapping::Reinhard => (x / (x + 1.0)).clamp(0.0, 1.0),
+        ToneMapping::Aces => {
+            (x * (2.51 * x + 0.03) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0.0, 1.0)
+        }
+        ToneMapping::Filmic => {
+            let (a, b, c, d, e, f, w) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30, 11.2);
+            let curve = |x: f32| (x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f) - e / f;
+            (curve(x) / curve(w)).clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Finds the input the tone mapping maps to `y` by bisection, as all the curves are increasing.
+fn inverse_tone_mapping(tone_mapping: ToneMapping, y: f32) -> f32 {
+    if tone_mapping == ToneMapping::None {
+        return y;
+    }
+    let (mut low, mut high) = (0.0, 1000.0);
+    for _ in 0..40 {
+        let mid = (low + high) / 2.0;
+        if tone_mapping_curve(tone_mapping, mid) < y {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
@@ -0,0 +1,152 @@
+use three_d::*;
+use three_d_asset::ProjectionType;
+
+use crate::picking::Ray;
+
+/// Arrows along the world axes at the selected object, dragged to move it along one axis.
+pub struct TranslateGizmo {
+    handles: [Gm<Mesh, ColorMaterial>; 3],
+    drag: Option<HandleDrag>,
+}
+
+/// The handle held since the mouse was pressed on it.
+struct HandleDrag {
+    axis: usize,
+    origin: Vec3,
+    /// Distance along the axis from the origin to where the handle was grabbed.
+    start: f32,
+    /// Translation of the object when the handle was grabbed.
+    translation: Vec3,
+}
+
+impl TranslateGizmo {
+    const AXES: [Vec3; 3] = [
+        vec3(1.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        vec3(0.0, 0.0, 1.0),
+    ];
+    const COLORS: [Srgba; 3] = [Srgba::RED, Srgba::GREEN, Srgba::BLUE];
+    const GRABBED_COLOR: Srgba = Srgba::new(255, 255, 0, 255);
+    /// Length of the handles as a fraction of the view height at the object, so they keep their size on screen.
+    const SIZE: f32 = 0.15;
+    /// Radius of the arrow heads relative to the handle length.
+    const RADIUS: f32 = 0.06;
+
+    pub fn new(context: &Context) -> Self {
+        let rotations = [
+            Mat4::identity(),
+            Mat4::from_angle_z(degrees(90.0)),
+            Mat4::from_angle_y(degrees(-90.0)),
+        ];
+        let handles = [0, 1, 2].map(|axis| {
+            let mut cpu_mesh = CpuMesh::arrow(0.8, 0.4, 16);
+            // Always succeeds, the matrix is invertible.
+            cpu_mesh
+                .transform(
+                    &(rotations[axis]
+                        * Mat4::from_nonuniform_scale(1.0, Self::RADIUS, Self::RADIUS)),
+                )
+                .unwrap();
+            Gm::new(
+                Mesh::new(context, &cpu_mesh),
+                ColorMaterial {
+                    color: Self::COLORS[axis],
+                    // Drawn over the object and everything else, so the handles can always be grabbed.
+                    render_states: RenderStates {
+                        depth_test: DepthTest::Always,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+        });
+        Self {
+            handles,
+            drag: None,
+        }
+    }
+
+    /// Length of the handles at `origin` as seen by `camera`.
+    pub fn handle_length(camera: &Camera, origin: Vec3) -> f32 {
+        let view_height = match *camera.projection_type() {
+            ProjectionType::Perspective { field_of_view_y } => {
+                2.0 * camera.position().distance(origin) * (field_of_view_y.0 / 2.0).tan()
+            }
+            ProjectionType::Orthographic { height } => height,
+        };
+        (view_height * Self::SIZE).max(f32::EPSILON)
+    }
+
+    /// Places the handles at `origin`, highlighting the grabbed one.
+    pub fn update(&mut self, origin: Vec3, length: f32) {
+        for (axis, handle) in self.handles.iter_mut().enumerate() {
+            handle.set_transformation(Mat4::from_translation(origin) * Mat4::from_scale(length));
+            let grabbed = self.drag.as_ref().is_some_and(|drag| drag.axis == axis);
+            handle.material.color = if grabbed {
+                Self::GRABBED_COLOR
+            } else {
+                Self::COLORS[axis]
+            };
+        }
+    }
+
+    pub fn handles(&self) -> &[Gm<Mesh, ColorMaterial>] {
+        &self.handles
+    }
+
+    /// Grabs the handle closest along the ray, treating each handle as a capsule a bit thicker than its arrow head
+    /// so it's easier to hit. Returns whether a handle was grabbed.
+    pub fn grab(&mut self, ray: &Ray, origin: Vec3, length: f32, translation: Vec3) -> bool {
+        let radius = length * Self::RADIUS * 1.5;
+        let hit = (0..3)
+            .filter_map(|axis| {
+                let along =
+                    Self::closest_on_axis(ray, origin, Self::AXES[axis])?.clamp(0.0, length);
+                let point = origin + Self::AXES[axis] * along;
+                let depth = (point - ray.origin)
+                    .dot(ray.direction)
+                    .clamp(0.0, ray.length);
+                let distance = (ray.origin + ray.direction * depth).distance(point);
+                (distance <= radius).then_some((axis, depth))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        self.drag = hit.and_then(|(axis, _)| {
+            Some(HandleDrag {
+                axis,
+                origin,
+                start: Self::closest_on_axis(ray, origin, Self::AXES[axis])?,
+                translation,
+            })
+        });
+        self.drag.is_some()
+    }
+
+    /// The world space offset of the grabbed handle from where it was grabbed, following the ray along its axis,
+    /// together with the translation the object had then. `None` if no handle is held or the ray runs along the axis.
+    pub fn drag(&self, ray: &Ray) -> Option<(Vec3, Vec3)> {
+        let drag = self.drag.as_ref()?;
+        let axis = Self::AXES[drag.axis];
+        let along = Self::closest_on_axis(ray, drag.origin, axis)?;
+        Some((axis * (along - drag.start), drag.translation))
+    }
+
+    pub fn release(&mut self) {
+        self.drag = None;
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Distance from `origin` along the unit `axis` to the point of the axis line closest to the ray line,
+    /// `None` if they're parallel.
+    fn closest_on_axis(ray: &Ray, origin: Vec3, axis: Vec3) -> Option<f32> {
+        let cos = ray.direction.dot(axis);
+        let denominator = 1.0 - cos * cos;
+        if denominator < 1e-6 {
+            return None;
+        }
+        let offset = ray.origin - origin;
+        Some((axis.dot(offset) - cos * ray.direction.dot(offset)) / denominator)
+    }
+}
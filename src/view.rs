@@ -0,0 +1,248 @@
+use std::sync::Arc;
+
+use eframe::egui::mutex::{Mutex, MutexGuard};
+use eframe::{egui, egui_glow, egui_glow::glow};
+
+use crate::custom3d::{Custom3d, ViewPreset};
+use crate::key_bindings::{Action, KeyBindings};
+use crate::orbit::OrbitInput;
+#[cfg(target_arch = "wasm32")]
+use crate::page_scroll;
+
+/// A 3D canvas that can be added to the `egui::Ui` of any eframe app using the glow backend.
+///
+/// The scene is painted by a paint callback, so it sits behind a mutex shared with the callback.
+pub struct ThreeDView {
+    custom_3d: Arc<Mutex<Custom3d>>,
+    /// Transformation of the models in the scene, applied when painting.
+    pub transformation: three_d::Mat4,
+    /// Keys moving the camera and running the actions of the view while the canvas is hovered.
+    pub key_bindings: KeyBindings,
+    /// Renders the scene into an offscreen texture shown as an `egui::Image`, instead of painting it with a paint
+    /// callback. It's still rendered while building the frame, after handling the input, so it's no frame behind,
+    /// but reading the pixels back waits for the GPU to finish and they're uploaded again as an egui texture.
+    /// The axes gizmo is left out.
+    pub render_to_texture: bool,
+    /// The egui texture showing the scene with `render_to_texture`, resized along with the canvas.
+    texture: Option<egui::TextureHandle>,
+    /// Leaves scrolling over the view to the page, for embedding the web build in a longer page. Scrolling with
+    /// ctrl or cmd held still zooms. The view stops the wheel events from reaching the canvas with a listener on
+    /// the window, installed the first time it's shown with this set.
+    #[cfg(target_arch = "wasm32")]
+    pub page_scroll: bool,
+}
+
+impl ThreeDView {
+    /// The canvas never shrinks below this, a zero sized viewport would break the projection matrix.
+    const MIN_CANVAS_SIZE: egui::Vec2 = egui::vec2(64.0, 64.0);
+
+    /// Shows the built-in triangle. Fails like [`Custom3d::new`] if three-d can't render with the GL context.
+    pub fn new(gl: &Arc<glow::Context>) -> Result<Self, String> {
+        Custom3d::new(gl).map(Self::from_scene)
+    }
+
+    pub fn from_scene(custom_3d: Custom3d) -> Self {
+        use three_d::SquareMatrix;
+
+        Self {
+            custom_3d: Arc::new(Mutex::new(custom_3d)),
+            transformation: three_d::Mat4::identity(),
+            key_bindings: KeyBindings::default(),
+            render_to_texture: false,
+            texture: None,
+            #[cfg(target_arch = "wasm32")]
+            page_scroll: false,
+        }
+    }
+
+    /// Locks the scene for reading or changing its settings. Don't hold on to the lock while calling [`Self::ui`].
+    pub fn scene(&self) -> MutexGuard<'_, Custom3d> {
+        self.custom_3d.lock()
+    }
+
+    /// Shows the canvas in all of the available space, moving the camera by the input on it, picking the model
+    /// under a click, moving the selected model by its translate handles, focusing on the point under a double-click
+    /// and running the actions of the pressed keys.
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> egui::Response {
+        let size = ui.available_size().max(Self::MIN_CANVAS_SIZE);
+        let (canvas, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        // The split view shows the top-down view on the right, only the main view on the left takes input.
+        let split_view = self.scene().split_view;
+        let (rect, side_rect) = if split_view {
+            let (left, right) = canvas.split_left_right_at_x(canvas.center().x);
+            (left, Some(right))
+        } else {
+            (canvas, None)
+        };
+        let response = ui.interact(rect, ui.id().with("3d_view"), egui::Sense::click_and_drag());
+
+        #[cfg(target_arch = "wasm32")]
+        let page_scroll = self.page_scroll;
+        #[cfg(not(target_arch = "wasm32"))]
+        let page_scroll = false;
+        #[cfg(target_arch = "wasm32")]
+        page_scroll::update(ui.ctx().frame_nr(), response.hovered() && page_scroll);
+        let mut input = OrbitInput::from_response(ui, &response, &self.key_bindings, page_scroll);
+        let pressed =
+            |action| response.hovered() && ui.input(|i| self.key_bindings.pressed(i, action));
+        if input.movement != three_d::vec3(0.0, 0.0, 0.0) || input.roll != 0.0 {
+            // Keep moving while the keys are held, even without other input.
+            ui.ctx().request_repaint();
+        }
+        let (labels, calibration) = {
+            let mut custom_3d = self.scene();
+            // A drag starting on a translate handle moves the selected object instead of the camera.
+            if response.drag_started_by(egui::PointerButton::Primary) {
+                let (origin, shift) = ui.input(|i| (i.pointer.press_origin(), i.modifiers.shift));
+                if let Some(pos) = origin.filter(|_| !shift) {
+                    custom_3d.grab_handle(rect, pos);
+                }
+            }
+            if custom_3d.is_dragging_handle() {
+                input.orbit = egui::Vec2::ZERO;
+                if let Some(pos) = response.interact_pointer_pos() {
+                    custom_3d.drag_handle(rect, pos);
+                }
+                if response.drag_released() {
+                    custom_3d.release_handle();
+                }
+            }
+            custom_3d.handle_input(&input);
+            custom_3d.set_cursor(rect, response.hover_pos());
+            if pressed(Action::FrameSelection) {
+                custom_3d.frame_selection();
+            }
+            if pressed(Action::ResetView) {
+                custom_3d.reset_view();
+            }
+            for (action, preset) in [
+                (Action::FrontView, ViewPreset::Front),
+                (Action::SideView, ViewPreset::Right),
+                (Action::TopView, ViewPreset::Top),
+            ] {
+                if pressed(action) {
+                    let ctrl = ui.input(|i| i.modifiers.ctrl);
+                    custom_3d.snap_to_view(if ctrl { preset.opposite() } else { preset });
+                }
+            }
+            let nudge = |positive, negative| {
+                pressed(positive) as i32 as f32 - pressed(negative) as i32 as f32
+            };
+            let steps = three_d::vec3(
+                nudge(Action::NudgeRight, Action::NudgeLeft),
+                nudge(Action::NudgeUp, Action::NudgeDown),
+                nudge(Action::NudgeAway, Action::NudgeCloser),
+            );
+            if steps != three_d::vec3(0.0, 0.0, 0.0) {
+                let big = ui.input(|i| i.modifiers.shift);
+                custom_3d.nudge_selected(if big { steps * 10.0 } else { steps });
+            }
+            if let Some(pos) = response
+                .interact_pointer_pos()
+                .filter(|_| response.clicked())
+            {
+                custom_3d.request_pick(rect, pos);
+            }
+            if let Some(pos) = response
+                .interact_pointer_pos()
+                .filter(|_| response.double_clicked())
+            {
+                custom_3d.request_focus(rect, pos);
+                // The camera only starts moving once the paint callback found the point, so paint again after it.
+                ui.ctx().request_repaint();
+            }
+            (
+                custom_3d.vertex_labels(self.transformation, rect),
+                custom_3d.calibration,
+            )
+        };
+
+        let transformation = self.transformation;
+        if self.render_to_texture {
+            self.show_texture(ui, rect);
+        } else {
+            self.texture = None;
+            let custom_3d = self.custom_3d.clone();
+            let callback = egui::PaintCallback {
+                rect,
+                callback: Arc::new(egui_glow::CallbackFn::new(move |info, _painter| {
+                    custom_3d.lock().paint(&info, transformation);
+                })),
+            };
+            ui.painter().add(callback);
+        }
+
+        if let Some(side_rect) = side_rect {
+            let custom_3d = self.custom_3d.clone();
+            ui.painter().add(egui::PaintCallback {
+                rect: side_rect,
+                callback: Arc::new(egui_glow::CallbackFn::new(move |info, _painter| {
+                    custom_3d.lock().paint_side_view(&info);
+                })),
+            });
+            ui.painter_at(side_rect).text(
+                side_rect.left_top() + egui::vec2(6.0, 4.0),
+                egui::Align2::LEFT_TOP,
+                "Top",
+                egui::FontId::proportional(14.0),
+                egui::Color32::WHITE,
+            );
+        }
+
+        // Painted after the callback, so the labels end up on top of the 3D content.
+        let painter = ui.painter_at(rect);
+        for (label, color, pos) in labels {
+            painter.text(
+                pos + egui::vec2(4.0, -4.0),
+                egui::Align2::LEFT_BOTTOM,
+                label,
+                egui::FontId::proportional(16.0),
+                egui::Color32::WHITE,
+            );
+            if calibration {
+                // An egui swatch of the same value right next to the corner, they should look identical.
+                let swatch =
+                    egui::Rect::from_min_size(pos + egui::vec2(4.0, 4.0), egui::vec2(24.0, 24.0));
+                painter.rect(
+                    swatch,
+                    0.0,
+                    egui::Color32::from_rgb(color.r, color.g, color.b),
+                    egui::Stroke::new(1.0, egui::Color32::WHITE),
+                );
+            }
+        }
+        response
+    }
+
+    /// Renders the scene at the pixel size of `rect` and shows it there as an image.
+    fn show_texture(&mut self, ui: &mut egui::Ui, rect: egui::Rect) {
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let [width, height] =
+            [rect.width(), rect.height()].map(|side| (side * pixels_per_point).round() as u32);
+        let pixels =
+            self.scene()
+                .paint_to_pixels(width, height, pixels_per_point, self.transformation);
+        if pixels.is_empty() {
+            return;
+        }
+        let image = egui::ColorImage {
+            size: [width as usize, height as usize],
+            pixels: pixels
+                .into_iter()
+                .map(|[r, g, b, a]| egui::Color32::from_rgba_premultiplied(r, g, b, a))
+                .collect(),
+        };
+        // Setting an image of another size resizes the texture.
+        let options = egui::TextureOptions::LINEAR;
+        let texture = match &mut self.texture {
+            Some(texture) => {
+                texture.set(image, options);
+                texture
+            }
+            None => self
+                .texture
+                .insert(ui.ctx().load_texture("three_d_view", image, options)),
+        };
+        ui.put(rect, egui::Image::new((texture.id(), rect.size())));
+    }
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use egui_three_d_demo::{Benchmark, BenchmarkReport, Primitive};
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn options_override_the_defaults() {
+    let benchmark = Benchmark::from_args(args(&[
+        "--frames", "10", "--size", "64x32", "--shape", "torus",
+    ]))
+    .unwrap();
+    assert_eq!(
+        benchmark,
+        Benchmark {
+            frames: 10,
+            width: 64,
+            height: 32,
+            primitive: Primitive::Torus,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        Benchmark::from_args(args(&[])).unwrap(),
+        Benchmark::default()
+    );
+
+    assert!(Benchmark::from_args(args(&["--frames"])).is_err());
+    assert!(Benchmark::from_args(args(&["--frames", "0"])).is_err());
+    assert!(Benchmark::from_args(args(&["--size", "64"])).is_err());
+    assert!(Benchmark::from_args(args(&["--shape", "teapot"])).is_err());
+    assert!(Benchmark::from_args(args(&["--fast"])).is_err());
+}
+
+#[test]
+fn percentiles_take_the_nearest_rank() {
+    let report = BenchmarkReport {
+        frame_times: (1..=100).rev().map(|ms| ms as f32).collect(),
+    };
+    assert_eq!(report.average(), 50.5);
+    assert_eq!(report.percentile(50.0), 50.0);
+    assert_eq!(report.percentile(99.0), 99.0);
+    assert_eq!(report.percentile(100.0), 100.0);
+}
+
+#[test]
+fn every_frame_is_timed() {
+    let headless = headless_gl_or_skip!();
+    let benchmark = Benchmark {
+        frames: 5,
+        width: 32,
+        height: 32,
+        instances: Some(100),
+        ..Default::default()
+    };
+    let report = benchmark.run(&headless.gl).unwrap();
+    assert_eq!(report.frame_times.len(), 5);
+    assert!(report.frame_times.iter().all(|&ms| ms > 0.0));
+}
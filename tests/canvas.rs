@@ -0,0 +1,42 @@
+use eframe::egui;
+use egui_three_d_demo::ThreeDCanvas;
+
+/// Shows the canvas in an otherwise empty frame, returning its response and the rects of the paint callbacks.
+fn show(
+    canvas: ThreeDCanvas<fn(&three_d::Context, three_d::Viewport)>,
+) -> (egui::Response, Vec<egui::Rect>) {
+    let ctx = egui::Context::default();
+    let mut response = None;
+    let output = ctx.run(egui::RawInput::default(), |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            response = Some(canvas.show(ui));
+        });
+    });
+    let callbacks = output
+        .shapes
+        .iter()
+        .filter_map(|clipped| match &clipped.shape {
+            egui::Shape::Callback(callback) => Some(callback.rect),
+            _ => None,
+        })
+        .collect();
+    (response.unwrap(), callbacks)
+}
+
+fn render(_: &three_d::Context, _: three_d::Viewport) {}
+
+#[test]
+fn paints_into_the_requested_size() {
+    let (response, callbacks) = show(ThreeDCanvas::new(render as _).size(egui::vec2(200.0, 100.0)));
+    assert_eq!(response.rect.size(), egui::vec2(200.0, 100.0));
+    assert_eq!(callbacks, vec![response.rect]);
+}
+
+#[test]
+fn input_capture_can_be_turned_off() {
+    let (response, _) = show(ThreeDCanvas::new(render as _));
+    assert!(response.sense.click && response.sense.drag);
+
+    let (response, _) = show(ThreeDCanvas::new(render as _).capture_input(false));
+    assert!(!response.sense.click && !response.sense.drag);
+}
@@ -0,0 +1,15 @@
+pub use egui_three_d_demo::headless_gl;
+
+/// Gets a headless context, or prints why the calling test is skipped.
+#[macro_export]
+macro_rules! headless_gl_or_skip {
+    () => {
+        match common::headless_gl() {
+            Some(headless) => headless,
+            None => {
+                eprintln!("skipping, no headless OpenGL context could be created");
+                return;
+            }
+        }
+    };
+}
@@ -0,0 +1,52 @@
+use egui_three_d_demo::Frustum;
+use three_d::*;
+
+fn cube_at(center: Vec3) -> AxisAlignedBoundingBox {
+    AxisAlignedBoundingBox::new_with_positions(&[
+        center - vec3(0.5, 0.5, 0.5),
+        center + vec3(0.5, 0.5, 0.5),
+    ])
+}
+
+#[test]
+fn boxes_outside_the_planes_are_culled() {
+    let camera = Camera::new_perspective(
+        Viewport::new_at_origo(100, 100),
+        vec3(0.0, 0.0, 5.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        1.0,
+        10.0,
+    );
+    let frustum = Frustum::new(&camera);
+    assert!(frustum.intersects(&cube_at(vec3(0.0, 0.0, 0.0))));
+    // Straddling the left plane.
+    assert!(frustum.intersects(&cube_at(vec3(-2.5, 0.0, 0.0))));
+    for outside in [
+        vec3(-10.0, 0.0, 0.0),
+        vec3(0.0, 10.0, 0.0),
+        vec3(0.0, 0.0, 6.0),
+        vec3(0.0, 0.0, -20.0),
+    ] {
+        assert!(!frustum.intersects(&cube_at(outside)), "{outside:?}");
+    }
+    assert!(!frustum.intersects(&AxisAlignedBoundingBox::EMPTY));
+}
+
+#[test]
+fn orthographic_frusta_are_boxes() {
+    let camera = Camera::new_orthographic(
+        Viewport::new_at_origo(200, 100),
+        vec3(0.0, 0.0, 5.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        2.0,
+        1.0,
+        10.0,
+    );
+    let frustum = Frustum::new(&camera);
+    // Two wide and one high, so the cubes at ±1.8 only fit sideways.
+    assert!(frustum.intersects(&cube_at(vec3(1.8, 0.0, 0.0))));
+    assert!(!frustum.intersects(&cube_at(vec3(0.0, 1.8, 0.0))));
+}
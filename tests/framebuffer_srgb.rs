@@ -0,0 +1,73 @@
+mod common;
+
+use eframe::egui_glow::glow::{self, HasContext};
+use egui_three_d_demo::{FramebufferSrgb, SrgbSupport};
+use three_d::*;
+
+/// Draws a mid-gray quad into a pixel of a framebuffer storing sRGB colors with `srgb` applied, returning the stored red.
+fn render_gray(context: &Context, srgb: FramebufferSrgb) -> u8 {
+    let gl: &glow::Context = context;
+    unsafe {
+        let renderbuffer = gl.create_renderbuffer().unwrap();
+        gl.bind_renderbuffer(glow::RENDERBUFFER, Some(renderbuffer));
+        gl.renderbuffer_storage(glow::RENDERBUFFER, glow::SRGB8_ALPHA8, 1, 1);
+        let framebuffer = gl.create_framebuffer().unwrap();
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+        gl.framebuffer_renderbuffer(
+            glow::FRAMEBUFFER,
+            glow::COLOR_ATTACHMENT0,
+            glow::RENDERBUFFER,
+            Some(renderbuffer),
+        );
+
+        let previous = srgb.apply(gl, &SrgbSupport::query(gl));
+        let camera = Camera::new_2d(Viewport::new_at_origo(1, 1));
+        let quad = Gm::new(
+            Rectangle::new(context, vec2(0.5, 0.5), degrees(0.0), 2.0, 2.0),
+            ColorMaterial {
+                color: Srgba::new(128, 128, 128, 255),
+                ..Default::default()
+            },
+        );
+        let target = RenderTarget::from_framebuffer(context, 1, 1, framebuffer);
+        target
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+            .render(&camera, [&quad], &[]);
+        FramebufferSrgb::restore(gl, previous);
+
+        let mut pixel = [0u8; 4];
+        gl.read_pixels(
+            0,
+            0,
+            1,
+            1,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            glow::PixelPackData::Slice(&mut pixel),
+        );
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        gl.delete_framebuffer(framebuffer);
+        gl.delete_renderbuffer(renderbuffer);
+        pixel[0]
+    }
+}
+
+#[test]
+fn gray_is_encoded_once_unless_the_gpu_encodes_again() {
+    let headless = headless_gl_or_skip!();
+    let context = Context::from_gl_context(headless.gl.clone()).unwrap();
+    let support = SrgbSupport::query(&context);
+    if !support.write_control {
+        eprintln!("skipping, GL_FRAMEBUFFER_SRGB can't be switched");
+        return;
+    }
+
+    // The material encodes the gray itself, so it's stored as is without the GPU encoding.
+    let disabled = render_gray(&context, FramebufferSrgb::Disabled);
+    assert!(disabled.abs_diff(128) <= 1, "stored {disabled}");
+    // Encoding 128 / 255 once more gives about 188.
+    let enabled = render_gray(&context, FramebufferSrgb::Enabled);
+    assert!(enabled.abs_diff(188) <= 2, "stored {enabled}");
+    // The state is restored afterwards.
+    assert_eq!(SrgbSupport::query(&context), support);
+}
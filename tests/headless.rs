@@ -0,0 +1,876 @@
+mod common;
+
+use eframe::egui;
+use egui_three_d_demo::{
+    headless_scene, render_triangle, BuiltinSky, Custom3d, DebugDraw, DebugView, HeadlessGl,
+    LevelOfDetail, Primitive, ProjectionMode, Shading, Transform, TurntableRequest, ViewPreset,
+};
+use three_d::{InnerSpace, Mat4, SquareMatrix};
+
+const SIZE: u32 = 100;
+
+/// Pixel showing the point `(x, y)` on the triangle's plane, which the default camera views from 2 units away
+/// with a 45° vertical field of view.
+fn pixel(pixels: &[[u8; 4]], x: f32, y: f32) -> [u8; 4] {
+    let half_height = 2.0 * 22.5_f32.to_radians().tan();
+    let column = ((x / half_height + 1.0) / 2.0 * SIZE as f32) as usize;
+    let row = ((1.0 - y / half_height) / 2.0 * SIZE as f32) as usize;
+    pixels[row * SIZE as usize + column]
+}
+
+fn scene(headless: &HeadlessGl) -> Custom3d {
+    headless_scene(&headless.gl).unwrap()
+}
+
+/// A model with a single cube, like one read from a file.
+fn cube_model() -> three_d::CpuModel {
+    let cube = three_d_asset::Primitive {
+        name: "Cube".to_owned(),
+        transformation: Mat4::identity(),
+        animations: Vec::new(),
+        geometry: three_d_asset::Geometry::Triangles(three_d::CpuMesh::cube()),
+        material_index: None,
+    };
+    three_d::CpuModel {
+        name: "cube".to_owned(),
+        geometries: vec![cube],
+        materials: Vec::new(),
+    }
+}
+
+#[test]
+fn vertex_colors_are_interpolated() {
+    let headless = headless_gl_or_skip!();
+    let pixels = render_triangle(&headless.gl, SIZE, SIZE).unwrap();
+    assert_eq!(pixels.len(), (SIZE * SIZE) as usize);
+
+    // Close to the red bottom right and the blue top corner, moving a fifth of the way towards the center.
+    let [r, g, b, _] = pixel(&pixels, 0.4, -0.433);
+    assert!(
+        r > 2 * g.max(b),
+        "expected red near the bottom right, got {:?}",
+        [r, g, b]
+    );
+    let [r, g, b, _] = pixel(&pixels, 0.0, 0.367);
+    assert!(
+        b > 2 * r.max(g),
+        "expected blue near the top, got {:?}",
+        [r, g, b]
+    );
+
+    // Outside the triangle only the background is left.
+    assert_eq!(pixel(&pixels, -0.45, 0.4), pixels[0]);
+}
+
+#[test]
+fn flat_shading_colors_the_triangle_by_its_last_corner() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    custom_3d.set_shading(Shading::Flat);
+
+    // Blue all over, even next to the red bottom right corner.
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    for (x, y) in [(0.4, -0.433), (0.0, 0.367)] {
+        let [r, g, b, _] = pixel(&pixels, x, y);
+        assert!(
+            b > 2 * r.max(g),
+            "expected blue at ({x}, {y}), got {:?}",
+            [r, g, b]
+        );
+    }
+}
+
+#[test]
+fn added_models_take_the_flat_shading() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    custom_3d.set_shading(Shading::Flat);
+    custom_3d.remove_object(0);
+
+    // The built-in triangle's corners, which blend into red near the first one when smooth.
+    let triangle = three_d::CpuMesh {
+        positions: three_d::Positions::F32(vec![
+            three_d::vec3(0.5, -0.5, 0.0),
+            three_d::vec3(-0.5, -0.5, 0.0),
+            three_d::vec3(0.0, 0.5, 0.0),
+        ]),
+        colors: Some(vec![
+            three_d::Srgba::new(255, 0, 0, 255),
+            three_d::Srgba::new(0, 255, 0, 255),
+            three_d::Srgba::new(0, 0, 255, 255),
+        ]),
+        ..Default::default()
+    };
+    custom_3d.add_mesh("Triangle", triangle).unwrap();
+
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    let count = |dominant: fn([u32; 3]) -> bool| {
+        pixels
+            .iter()
+            .filter(|[r, g, b, _]| dominant([*r, *g, *b].map(u32::from)))
+            .count()
+    };
+    let red = count(|[r, g, b]| r > 2 * g.max(b));
+    let blue = count(|[r, g, b]| b > 2 * r.max(g));
+    assert!(red == 0 && blue > 100, "{red} red and {blue} blue pixels");
+}
+
+#[test]
+fn zero_sized_canvas_is_skipped() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = Custom3d::new(&headless.gl).unwrap();
+
+    // A panel dragged shut leaves a canvas without width, a minimized window one without any size.
+    for size in [egui::vec2(0.0, 200.0), egui::vec2(0.0, 0.0)] {
+        let rect = egui::Rect::from_min_size(egui::pos2(10.0, 20.0), size);
+        let info = egui::PaintCallbackInfo {
+            viewport: rect,
+            clip_rect: rect,
+            pixels_per_point: 1.0,
+            screen_size_px: [SIZE, SIZE],
+        };
+        custom_3d.paint(&info, Mat4::identity());
+        custom_3d.paint_side_view(&info);
+    }
+
+    assert!(custom_3d.render_to_pixels(0, SIZE).is_empty());
+}
+
+#[test]
+fn render_stats_count_the_triangle() {
+    let headless = headless_gl_or_skip!();
+    let custom_3d = Custom3d::new(&headless.gl).unwrap();
+
+    let stats = custom_3d.render_stats();
+    assert_eq!(stats.triangles, 1);
+    // Positions, normals, uvs and colors of the three corners, without indices.
+    assert_eq!(stats.buffer_bytes, 3 * (12 + 12 + 8 + 4));
+    // Nothing was painted yet.
+    assert_eq!(stats.draw_calls, 0);
+}
+
+#[test]
+fn linear_pixels_are_not_srgb_encoded() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    custom_3d.clear_color = three_d::Srgba::new(128, 128, 128, 255);
+
+    let linear = custom_3d.render_to_linear_pixels(SIZE, SIZE);
+    assert_eq!(linear.len(), (SIZE * SIZE) as usize);
+    // sRGB 128 is about 0.22 in linear light, it would be 0.5 if the encoded value was stored.
+    let [r, g, b, _] = linear[0];
+    for channel in [r, g, b] {
+        assert!((channel - 0.216).abs() < 0.01, "background {:?}", [r, g, b]);
+    }
+}
+
+#[test]
+fn hidden_objects_are_not_rendered() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    custom_3d.set_object_visible(0, false);
+
+    let pixels = custom_3d.render_to_pixels(SIZE, SIZE);
+    assert!(pixels.iter().all(|pixel| *pixel == pixels[0]));
+}
+
+#[test]
+fn removing_an_object_keeps_the_selection() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = Custom3d::new(&headless.gl).unwrap();
+    custom_3d.add_model(cube_model(), None).unwrap();
+    assert_eq!(custom_3d.objects().len(), 2);
+
+    custom_3d.select(Some(1));
+    custom_3d.remove_object(0);
+    assert_eq!(custom_3d.objects()[0].name, "Cube");
+    assert_eq!(custom_3d.selected(), Some(0));
+    // Nothing is left to label once the triangle is gone.
+    assert!(custom_3d
+        .vertex_labels(Mat4::identity(), egui::Rect::EVERYTHING)
+        .is_empty());
+}
+
+#[test]
+fn painting_to_pixels_places_the_objects() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert_ne!(pixel(&pixels, -0.3, -0.433), pixels[0]);
+
+    // Moved half a unit to the right, the bottom left of the triangle is left empty.
+    let transform = Transform {
+        translation: three_d::vec3(0.5, 0.0, 0.0),
+        ..Default::default()
+    };
+    custom_3d.set_object_transform(0, transform);
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert_eq!(pixel(&pixels, -0.3, -0.433), pixels[0]);
+}
+
+#[test]
+fn depth_under_the_cursor_is_the_distance_to_the_triangle() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    custom_3d.probe_depth = true;
+    let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(SIZE as f32, SIZE as f32));
+
+    // The triangle covers the center of the view, 2 units in front of the camera.
+    custom_3d.set_cursor(rect, Some(rect.center()));
+    custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    let sample = custom_3d.cursor_depth().unwrap();
+    assert!((sample.distance - 2.0).abs() < 0.01, "{}", sample.distance);
+    assert!(sample.position.z.abs() < 0.01);
+
+    custom_3d.set_cursor(rect, Some(rect.left_top()));
+    custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert!(custom_3d.cursor_depth().is_none());
+}
+
+#[test]
+fn particles_spawn_and_expire() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    custom_3d.set_object_visible(0, false);
+    custom_3d.show_particles = true;
+    let background = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+
+    custom_3d.particles.max_count = 50;
+    for _ in 0..10 {
+        custom_3d.animate(0.05);
+    }
+    assert_eq!(custom_3d.particles.count(), 50);
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert_ne!(pixels, background);
+
+    // Without spawning, all of them are gone once their lifetime is over.
+    custom_3d.particles.spawn_rate = 0.0;
+    for _ in 0..40 {
+        custom_3d.animate(0.1);
+    }
+    assert_eq!(custom_3d.particles.count(), 0);
+}
+
+#[test]
+fn the_top_view_sees_the_triangle_edge_on() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    let covered = |pixels: &[[u8; 4]]| pixels.iter().filter(|p| **p != pixels[0]).count();
+
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert!(covered(&pixels) > pixels.len() / 10);
+
+    custom_3d.snap_to_view(ViewPreset::Top);
+    custom_3d.animate(1.0);
+    assert!(custom_3d.projection_mode == ProjectionMode::Orthographic);
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert!(covered(&pixels) < pixels.len() / 50);
+}
+
+#[test]
+fn depth_of_field_blurs_outside_the_focal_plane() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    let sharp = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    let differing = |pixels: &[[u8; 4]]| {
+        pixels
+            .iter()
+            .zip(&sharp)
+            .filter(|(a, b)| a.iter().zip(*b).any(|(a, b)| a.abs_diff(*b) > 8))
+            .count()
+    };
+
+    // The triangle is 2 units in front of the camera, in focus it stays sharp against the blurred background.
+    custom_3d.depth_of_field.enabled = true;
+    custom_3d.depth_of_field.aperture = 8.0;
+    custom_3d.depth_of_field.focus_distance = 2.0;
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert!(differing(&pixels) < 10, "{}", differing(&pixels));
+
+    custom_3d.depth_of_field.focus_distance = 0.5;
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert!(differing(&pixels) > 100, "{}", differing(&pixels));
+}
+
+#[test]
+fn ambient_occlusion_darkens_where_the_triangle_meets_the_ground() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    custom_3d.show_ground = true;
+    custom_3d.ambient_occlusion.enabled = true;
+    custom_3d.ambient_occlusion.show_only = true;
+
+    // The ground is level with the bottom of the triangle, seen just in front of its bottom edge and off to the side.
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    let (contact, open) = (pixel(&pixels, 0.0, -0.49), pixel(&pixels, 0.75, -0.49));
+    assert_eq!(open, [255, 255, 255, 255]);
+    assert!(contact[0] < 220, "{contact:?}");
+}
+
+#[test]
+fn choosing_a_builtin_sky_shows_it() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = Custom3d::new(&headless.gl).unwrap();
+    assert!(!custom_3d.show_skybox);
+    assert_eq!(custom_3d.builtin_sky(), Some(BuiltinSky::Daylight));
+
+    // Deriving the lighting from a sky takes seconds on a software rasterizer, so it's left to the next paint.
+    custom_3d.set_builtin_sky(BuiltinSky::Sunset);
+    assert!(custom_3d.show_skybox);
+    assert_eq!(custom_3d.builtin_sky(), Some(BuiltinSky::Sunset));
+}
+
+#[test]
+fn simplifying_reduces_the_triangles_of_the_sphere() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = Custom3d::new(&headless.gl).unwrap();
+    custom_3d.set_primitive(Primitive::Sphere);
+    let full = custom_3d.render_stats().triangles;
+    assert_eq!(custom_3d.simplified_triangles(), None);
+
+    custom_3d.set_level_of_detail(LevelOfDetail {
+        enabled: true,
+        quality: 0.1,
+        ..Default::default()
+    });
+    let (coarse, total) = custom_3d.simplified_triangles().unwrap();
+    assert_eq!(total, full);
+    assert!(
+        coarse > 0 && coarse < full / 2,
+        "{coarse} of {full} triangles"
+    );
+
+    // Finer with a higher quality, and gone once disabled.
+    let mut lod = custom_3d.level_of_detail();
+    lod.quality = 0.5;
+    custom_3d.set_level_of_detail(lod);
+    assert!(custom_3d.simplified_triangles().unwrap().0 > coarse);
+    custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    lod.enabled = false;
+    custom_3d.set_level_of_detail(lod);
+    assert_eq!(custom_3d.simplified_triangles(), None);
+}
+
+#[test]
+fn added_models_are_simplified_too() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = Custom3d::new(&headless.gl).unwrap();
+    custom_3d.set_primitive(Primitive::Sphere);
+    custom_3d.set_level_of_detail(LevelOfDetail {
+        enabled: true,
+        quality: 0.1,
+        ..Default::default()
+    });
+    let (_, sphere) = custom_3d.simplified_triangles().unwrap();
+
+    let torus = Primitive::Torus.cpu_mesh().unwrap();
+    let triangles = torus.triangle_count();
+    custom_3d.add_mesh("Torus", torus).unwrap();
+    let (coarse, total) = custom_3d.simplified_triangles().unwrap();
+    assert_eq!(total, sphere + triangles);
+    assert!(coarse < total / 2, "{coarse} of {total} triangles");
+}
+
+#[test]
+fn debug_views_color_the_triangle_by_its_attributes() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    let close = |actual: [u8; 4], expected: [u8; 3]| {
+        actual
+            .iter()
+            .zip(expected)
+            .all(|(&a, e)| a.abs_diff(e) <= 3)
+    };
+
+    // The corners wind clockwise as seen by the camera, so the normal points away from it along -z, which the
+    // lit materials hide by turning it around on back faces.
+    custom_3d.debug_view = DebugView::WorldNormals;
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    let color = pixel(&pixels, 0.0, 0.0);
+    assert!(close(color, [128, 128, 0]), "got {color:?}");
+
+    // Near the top corner u is a half and v is small.
+    custom_3d.debug_view = DebugView::Uvs;
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    let [r, g, b, _] = pixel(&pixels, 0.0, 0.367);
+    assert!(
+        r.abs_diff(128) <= 8 && g < 64 && b == 0,
+        "got {:?}",
+        [r, g, b]
+    );
+}
+
+#[test]
+fn projects_reopen_the_scene_and_list_missing_models() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = Custom3d::new(&headless.gl).unwrap();
+    let directory = std::env::temp_dir().join(format!("project-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let project = directory.join("project.json");
+
+    // A model read from a file that's gone by the time the project is opened again.
+    custom_3d
+        .add_model(cube_model(), Some(&directory.join("cube.glb")))
+        .unwrap();
+    let transform = Transform {
+        translation: three_d::vec3(0.5, 0.0, -1.0),
+        rotation: three_d::vec3(0.0, 30.0, 0.0),
+        scale: 2.0,
+    };
+    custom_3d.set_object_transform(0, transform);
+    custom_3d.save_project(&project, 1.5).unwrap();
+
+    custom_3d.set_primitive(Primitive::Sphere);
+    let angle = custom_3d.open_project(&project).unwrap();
+    std::fs::remove_dir_all(&directory).unwrap();
+    assert_eq!(angle, 1.5);
+    assert_eq!(custom_3d.objects().len(), 1);
+    assert_eq!(custom_3d.objects()[0].name, "Triangle");
+    assert_eq!(custom_3d.objects()[0].transform, transform);
+    let missing = custom_3d.missing_objects();
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].object.name, "Cube");
+    assert!(
+        missing[0].error.contains("cube.glb"),
+        "{}",
+        missing[0].error
+    );
+}
+
+#[test]
+fn the_model_under_the_cursor_is_tinted() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(SIZE as f32, SIZE as f32));
+    let plain = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+
+    custom_3d.set_cursor(rect, Some(rect.center()));
+    let tinted = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert_eq!(custom_3d.hovered(), Some(0));
+    let (before, after) = (pixel(&plain, 0.0, 0.0), pixel(&tinted, 0.0, 0.0));
+    assert!(
+        (0..3).all(|i| after[i] > before[i]),
+        "{before:?} -> {after:?}"
+    );
+
+    // Off the triangle and off the canvas, nothing is hovered.
+    custom_3d.set_cursor(rect, Some(rect.left_top() + egui::vec2(1.0, 1.0)));
+    custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert_eq!(custom_3d.hovered(), None);
+    custom_3d.set_cursor(rect, Some(rect.center()));
+    custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    custom_3d.set_cursor(rect, None);
+    assert_eq!(custom_3d.hovered(), None);
+}
+
+#[test]
+fn the_pulsing_triangle_follows_the_time() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    custom_3d.pulse = true;
+    let brightness = |pixels: &[[u8; 4]]| {
+        let [r, g, b, _] = pixel(pixels, 0.0, 0.0);
+        r as u32 + g as u32 + b as u32
+    };
+
+    // Brightest a quarter into the default period of two seconds, darkest three quarters in.
+    custom_3d.animate(0.5);
+    let bright = brightness(&custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity()));
+    custom_3d.animate(1.0);
+    assert_eq!(custom_3d.time(), 1.5);
+    let dark = brightness(&custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity()));
+    assert!(dark < bright * 3 / 4, "{bright} -> {dark}");
+}
+
+#[test]
+fn vertex_colors_are_blended_in_linear_light() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    custom_3d.compare_interpolation = true;
+
+    // The middle of the triangle is half blue and a quarter each red and green, which encode to about 137 and 188
+    // blended in linear light, and stay at 64 and 128 blended as raw sRGB values.
+    let [r, g, b, _] = pixel(
+        &custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity()),
+        0.0,
+        0.0,
+    );
+    assert!(r > 110 && g > 110 && b > 160, "{r} {g} {b}");
+    let raw = Mat4::from_translation(three_d::vec3(-1.2, 0.0, 0.0));
+    let [r, g, b, _] = pixel(&custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, raw), 0.0, 0.0);
+    assert!(r < 90 && g < 90 && b < 150, "{r} {g} {b}");
+}
+
+#[test]
+fn replaced_models_free_their_gpu_resources() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = Custom3d::new(&headless.gl).unwrap();
+    // Every buffer and texture three-d uploads holds on to the GL context until it's dropped.
+    let mut cycle = || {
+        for primitive in Primitive::ALL {
+            custom_3d.set_primitive(primitive);
+            custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+        }
+        std::sync::Arc::strong_count(&headless.gl)
+    };
+
+    // The first cycle compiles the shaders, which are kept.
+    cycle();
+    let resources = cycle();
+    for _ in 0..3 {
+        assert_eq!(cycle(), resources);
+    }
+}
+
+#[test]
+fn meshes_with_indices_out_of_range_are_rejected() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = Custom3d::new(&headless.gl).unwrap();
+    // A square of two triangles sharing the diagonal.
+    let square = |indices: Vec<u16>| three_d::CpuMesh {
+        positions: three_d::Positions::F32(vec![
+            three_d::vec3(0.0, 0.0, 0.0),
+            three_d::vec3(1.0, 0.0, 0.0),
+            three_d::vec3(1.0, 1.0, 0.0),
+            three_d::vec3(0.0, 1.0, 0.0),
+        ]),
+        indices: three_d::Indices::U16(indices),
+        ..Default::default()
+    };
+
+    custom_3d
+        .add_mesh("Square", square(vec![0, 1, 2, 0, 2, 3]))
+        .unwrap();
+    assert_eq!(custom_3d.objects().len(), 2);
+    assert_eq!(custom_3d.render_stats().triangles, 3);
+
+    let err = custom_3d
+        .add_mesh("Broken", square(vec![0, 1, 2, 0, 2, 7]))
+        .unwrap_err();
+    assert!(err.to_string().contains("max index 7"), "{err}");
+    assert_eq!(custom_3d.objects().len(), 2);
+}
+
+#[test]
+fn loading_a_model_reports_its_counts_and_bounds() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = Custom3d::new(&headless.gl).unwrap();
+    let primitive = |name: &str, transformation, mesh| three_d_asset::Primitive {
+        name: name.to_owned(),
+        transformation,
+        animations: Vec::new(),
+        geometry: three_d_asset::Geometry::Triangles(mesh),
+        material_index: None,
+    };
+    let cpu_model = three_d::CpuModel {
+        name: "parts".to_owned(),
+        geometries: vec![
+            primitive("Cube", Mat4::from_scale(2.0), three_d::CpuMesh::cube()),
+            primitive("Square", Mat4::identity(), three_d::CpuMesh::square()),
+        ],
+        materials: Vec::new(),
+    };
+    custom_3d
+        .set_model(cpu_model, Some(std::path::Path::new("parts.glb")))
+        .unwrap();
+
+    let stats = custom_3d.import_stats().unwrap();
+    assert_eq!(
+        stats.path.as_deref(),
+        Some(std::path::Path::new("parts.glb"))
+    );
+    assert_eq!(stats.meshes.len(), 2);
+    assert_eq!(
+        (stats.meshes[0].vertices, stats.meshes[0].triangles),
+        (36, 12)
+    );
+    assert_eq!(
+        (stats.meshes[1].vertices, stats.meshes[1].triangles),
+        (4, 2)
+    );
+    assert_eq!((stats.vertices(), stats.triangles()), (40, 14));
+    // The scale of the cube is baked into its mesh, the square lies inside it.
+    assert_eq!(stats.meshes[0].aabb.size(), three_d::vec3(4.0, 4.0, 4.0));
+    assert_eq!(stats.aabb().min(), three_d::vec3(-2.0, -2.0, -2.0));
+
+    custom_3d.set_primitive(Primitive::Cube);
+    assert!(custom_3d.import_stats().is_none());
+}
+
+#[test]
+fn rolling_the_camera_turns_the_view_until_leveled() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    let blue = |pixels: &[[u8; 4]], x, y| {
+        let [r, g, b, _] = pixel(pixels, x, y);
+        b > 2 * r.max(g)
+    };
+
+    // Rolled a quarter turn counterclockwise, the top corner of the triangle shows on the right.
+    custom_3d.set_roll(90.0);
+    assert!(
+        (custom_3d.roll() - 90.0).abs() < 1e-3,
+        "{}",
+        custom_3d.roll()
+    );
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert!(blue(&pixels, 0.367, 0.0));
+    assert!(!blue(&pixels, 0.0, 0.367));
+
+    custom_3d.level_horizon();
+    assert!(custom_3d.roll().abs() < 1e-3, "{}", custom_3d.roll());
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert!(blue(&pixels, 0.0, 0.367));
+}
+
+#[test]
+fn rolling_works_in_the_top_view() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    custom_3d.set_primitive(Primitive::Cube);
+    custom_3d.snap_to_view(ViewPreset::Top);
+    custom_3d.animate(1.0);
+    let covered = |pixels: &[[u8; 4]]| pixels.iter().filter(|p| **p != pixels[0]).count();
+    let top = covered(&custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity()));
+    assert!(top > (SIZE * SIZE / 10) as usize);
+
+    // Straight down there's no horizon, which must not leave the camera without an up direction.
+    custom_3d.set_roll(45.0);
+    let rolled = covered(&custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity()));
+    assert!(rolled > top, "{top} -> {rolled} pixels covered");
+    custom_3d.level_horizon();
+    let leveled = covered(&custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity()));
+    assert!(
+        leveled.abs_diff(top) < top / 20,
+        "{top} -> {leveled} pixels covered"
+    );
+}
+
+#[test]
+fn turntables_save_a_frame_per_paint_and_restore_the_camera() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = Custom3d::new(&headless.gl).unwrap();
+    let directory = std::env::temp_dir().join(format!("turntable-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    let before = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+
+    let request = TurntableRequest {
+        frames: 4,
+        width: 32,
+        height: 24,
+        directory: directory.clone(),
+    };
+    custom_3d.start_turntable(request.clone()).unwrap();
+    assert_eq!(custom_3d.turntable_progress(), Some((0, 4)));
+    custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert_eq!(custom_3d.turntable_progress(), Some((1, 4)));
+    for _ in 0..3 {
+        custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    }
+    assert_eq!(custom_3d.turntable_progress(), None);
+    assert_eq!(custom_3d.turntable_result, Some(Ok(directory.clone())));
+
+    let frames: Vec<_> = (0..4)
+        .map(|index| image::open(request.frame_path(index)).unwrap().to_rgba8())
+        .collect();
+    assert_eq!(frames[0].dimensions(), (32, 24));
+    // A quarter turn shows the triangle edge on.
+    assert_ne!(frames[0], frames[1]);
+    assert!(!request.frame_path(4).exists());
+    let after = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert_eq!(before, after);
+
+    // Canceling keeps the frames saved so far and puts the camera back too.
+    custom_3d.start_turntable(request).unwrap();
+    custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    custom_3d.cancel_turntable();
+    assert_eq!(custom_3d.turntable_progress(), None);
+    assert_eq!(custom_3d.turntable_result, None);
+    assert_eq!(
+        custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity()),
+        before
+    );
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn turntables_turn_the_top_view_too() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    // A sphere off to the side of the cube, so the frames show which way the view turned.
+    custom_3d.set_primitive(Primitive::Cube);
+    custom_3d
+        .add_mesh("Sphere", three_d::CpuMesh::sphere(16))
+        .unwrap();
+    custom_3d.set_object_transform(
+        1,
+        Transform {
+            translation: three_d::vec3(3.0, 0.0, 0.0),
+            ..Default::default()
+        },
+    );
+    // The views snap to the bounds of the last paint.
+    custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    custom_3d.snap_to_view(ViewPreset::Top);
+    custom_3d.animate(1.0);
+    let directory = std::env::temp_dir().join(format!("turntable-top-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+
+    let request = TurntableRequest {
+        frames: 4,
+        width: 32,
+        height: 32,
+        directory: directory.clone(),
+    };
+    custom_3d.start_turntable(request.clone()).unwrap();
+    for _ in 0..4 {
+        custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    }
+    assert_eq!(custom_3d.turntable_result, Some(Ok(directory.clone())));
+    let frames: Vec<_> = (0..4)
+        .map(|index| image::open(request.frame_path(index)).unwrap().to_rgba8())
+        .collect();
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    // Half a turn around the vertical axis turns the view from above upside down.
+    let covered = |frame: &image::RgbaImage| {
+        let background = *frame.get_pixel(0, 0);
+        frame.pixels().map(|p| *p != background).collect::<Vec<_>>()
+    };
+    let turned = image::imageops::rotate180(&frames[0]);
+    let differing = covered(&frames[2])
+        .iter()
+        .zip(covered(&turned))
+        .filter(|(a, b)| **a != *b)
+        .count();
+    assert!(covered(&frames[0]).iter().filter(|c| **c).count() > 32 * 32 / 20);
+    assert!(differing < 32 * 32 / 50, "{differing} pixels differ");
+}
+
+#[test]
+fn auto_clip_planes_fit_around_the_scene() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    // Ten times as large and as far away, the triangle looks the same but is beyond the far plane.
+    let transform = Transform {
+        translation: three_d::vec3(0.0, 0.0, -18.0),
+        scale: 10.0,
+        ..Default::default()
+    };
+    custom_3d.set_object_transform(0, transform);
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert_eq!(pixel(&pixels, 0.0, 0.0), pixels[0]);
+
+    custom_3d.auto_clip_planes = true;
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    assert_ne!(pixel(&pixels, 0.0, 0.0), pixels[0]);
+    // Half of the 20 units to the triangle, and past it by the diagonal of its bounds.
+    let (z_near, z_far) = custom_3d.clip_planes();
+    assert!((z_near - 10.0).abs() < 1e-3, "{z_near}");
+    assert!(z_far > 20.0 && z_far < 40.0, "{z_far}");
+}
+
+#[test]
+fn edited_materials_apply_on_the_next_paint() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    custom_3d.set_primitive(Primitive::Cube);
+    let before = pixel(
+        &custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity()),
+        0.0,
+        0.0,
+    );
+
+    custom_3d.object_material_mut(0).albedo = three_d::Srgba::new(0, 0, 0, 255);
+    custom_3d.object_material_mut(0).emissive = three_d::Srgba::new(255, 0, 0, 255);
+    let [r, g, b, _] = pixel(
+        &custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity()),
+        0.0,
+        0.0,
+    );
+    // The black albedo still reflects a little of the lights.
+    assert!(r > 200 && r > 3 * g.max(b), "{before:?} -> {:?}", [r, g, b]);
+}
+
+#[test]
+fn debug_drawings_last_for_one_paint() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = scene(&headless);
+    let before = custom_3d.paint_to_pixels(SIZE, SIZE, 2.0, Mat4::identity());
+    // Pixels of the color that weren't before, the corners of the triangle come close to the colors.
+    let count = |pixels: &[[u8; 4]], matches: fn([u8; 4]) -> bool| {
+        pixels
+            .iter()
+            .zip(&before)
+            .filter(|(pixel, before)| matches(**pixel) && pixel != before)
+            .count()
+    };
+    let yellow = |[r, g, b, _]: [u8; 4]| r > 200 && g > 200 && b < 50;
+    let green = |[r, g, b, _]: [u8; 4]| g > 200 && r < 50 && b < 50;
+
+    // Above the triangle and below its left corner.
+    let mut drawn = DebugDraw::default();
+    drawn.line(
+        three_d::vec3(-0.6, 0.7, 0.0),
+        three_d::vec3(0.6, 0.7, 0.0),
+        three_d::Srgba::new(255, 255, 0, 255),
+    );
+    drawn.point(
+        three_d::vec3(-0.6, -0.6, 0.0),
+        three_d::Srgba::new(0, 255, 0, 255),
+    );
+    assert!(!drawn.is_empty());
+    custom_3d.debug_draw = drawn;
+    let pixels = custom_3d.paint_to_pixels(SIZE, SIZE, 2.0, Mat4::identity());
+    assert!(count(&pixels, yellow) > 50, "the line is missing");
+    assert!(count(&pixels, green) > 0, "the point is missing");
+    assert!(custom_3d.debug_draw.is_empty());
+
+    let after = custom_3d.paint_to_pixels(SIZE, SIZE, 2.0, Mat4::identity());
+    assert!(after == before, "the drawings are still there");
+}
+
+#[test]
+fn nudging_moves_the_selected_object_along_the_screen() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = Custom3d::new(&headless.gl).unwrap();
+    custom_3d.nudge_step = 0.5;
+    custom_3d.nudge_selected(three_d::vec3(1.0, 0.0, 0.0));
+    assert_eq!(custom_3d.objects()[0].transform, Transform::default());
+
+    // The default camera looks down -z with +y up, so the screen axes are the world axes.
+    custom_3d.select(Some(0));
+    custom_3d.nudge_selected(three_d::vec3(1.0, 2.0, 0.0));
+    custom_3d.nudge_selected(three_d::vec3(0.0, 0.0, 1.0));
+    let translation = custom_3d.objects()[0].transform.translation;
+    assert!(
+        (translation - three_d::vec3(0.5, 1.0, -0.5)).magnitude() < 1e-5,
+        "{translation:?}"
+    );
+}
+
+#[test]
+fn models_outside_the_view_are_culled() {
+    let headless = headless_gl_or_skip!();
+    let mut custom_3d = Custom3d::new(&headless.gl).unwrap();
+    custom_3d
+        .add_mesh("Cube", three_d::CpuMesh::cube())
+        .unwrap();
+    // Moved far to the side, after adding it framed the camera to fit both.
+    let transform = Transform {
+        translation: three_d::vec3(100.0, 0.0, 0.0),
+        ..Default::default()
+    };
+    custom_3d.set_object_transform(1, transform);
+
+    custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    let stats = custom_3d.render_stats();
+    assert_eq!((stats.rendered_objects, stats.culled_objects), (1, 1));
+
+    custom_3d.frustum_culling = false;
+    custom_3d.paint_to_pixels(SIZE, SIZE, 1.0, Mat4::identity());
+    let stats = custom_3d.render_stats();
+    assert_eq!((stats.rendered_objects, stats.culled_objects), (2, 0));
+}
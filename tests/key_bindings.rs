@@ -0,0 +1,25 @@
+use eframe::egui::Key;
+use egui_three_d_demo::{Action, KeyBindings};
+
+#[test]
+fn rebinding_to_a_used_key_swaps_the_keys() {
+    let mut bindings = KeyBindings::default();
+    bindings.set(Action::MoveForward, Key::S);
+    assert_eq!(bindings.key(Action::MoveForward), Key::S);
+    assert_eq!(bindings.key(Action::MoveBack), Key::W);
+}
+
+#[test]
+fn bindings_survive_a_round_trip_through_json() {
+    let mut bindings = KeyBindings::default();
+    bindings.set(Action::FrameSelection, Key::ArrowUp);
+    bindings.set(Action::MoveUp, Key::Space);
+    let json = bindings.to_json().unwrap();
+    assert_eq!(KeyBindings::from_json(&json).unwrap(), bindings);
+}
+
+#[test]
+fn unknown_keys_fall_back_to_the_default() {
+    let bindings = KeyBindings::from_json(r#"{ "ResetView": "NotAKey" }"#).unwrap();
+    assert_eq!(bindings, KeyBindings::default());
+}
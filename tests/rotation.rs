@@ -0,0 +1,25 @@
+use egui_three_d_demo::advance_angle;
+
+/// Advances the angle from zero in `frames` equal steps lasting `seconds` in total.
+fn rotate(degrees_per_second: f32, seconds: f32, frames: u32) -> f32 {
+    let dt = seconds / frames as f32;
+    (0..frames).fold(0.0, |angle, _| advance_angle(angle, degrees_per_second, dt))
+}
+
+#[test]
+fn angle_advances_with_elapsed_time() {
+    let one_second = rotate(45.0, 1.0, 60);
+    assert!((one_second - 45.0_f32.to_radians()).abs() < 1e-4);
+    assert!((rotate(45.0, 2.0, 120) - 2.0 * one_second).abs() < 1e-4);
+}
+
+#[test]
+fn angle_is_independent_of_frame_rate() {
+    for frames in [30, 60, 144] {
+        let angle = rotate(90.0, 1.5, frames);
+        assert!(
+            (angle - 135.0_f32.to_radians()).abs() < 1e-4,
+            "rotated to {angle} at {frames} frames"
+        );
+    }
+}
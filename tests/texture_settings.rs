@@ -0,0 +1,30 @@
+use egui_three_d_demo::TextureSettings;
+use three_d::{CpuTexture, Interpolation, Wrapping};
+
+#[test]
+fn settings_are_set_on_the_texture() {
+    let settings = TextureSettings {
+        mipmaps: false,
+        min_filter: Interpolation::Nearest,
+        mag_filter: Interpolation::Nearest,
+        wrapping: Wrapping::ClampToEdge,
+    };
+    let mut cpu_texture = CpuTexture::default();
+    settings.apply(&mut cpu_texture);
+    assert_eq!(cpu_texture.min_filter, Interpolation::Nearest);
+    assert_eq!(cpu_texture.mag_filter, Interpolation::Nearest);
+    assert_eq!(cpu_texture.mip_map_filter, None);
+    assert_eq!(cpu_texture.wrap_s, Wrapping::ClampToEdge);
+    assert_eq!(cpu_texture.wrap_t, Wrapping::ClampToEdge);
+}
+
+#[test]
+fn mipmaps_blend_like_the_minification_filter() {
+    let settings = TextureSettings {
+        min_filter: Interpolation::Nearest,
+        ..Default::default()
+    };
+    let mut cpu_texture = CpuTexture::default();
+    settings.apply(&mut cpu_texture);
+    assert_eq!(cpu_texture.mip_map_filter, Some(Interpolation::Nearest));
+}